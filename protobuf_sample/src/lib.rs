@@ -3,9 +3,14 @@ pub mod sample {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use super::sample;
-    use structpath::StructPath;
+    use std::collections::HashMap;
+    use structpath::{
+        CompiledPath, FromPaths, Path, StructInfo, StructPath, StructPathError, Value,
+    };
+    use structpath_types::{FieldType, ProtoField, ProtoLabel};
 
     /// Create a new user with arbitrary values
     fn create_test_user() -> sample::User {
@@ -167,4 +172,626 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_get_value_field_not_found() {
+        let group = sample::Group {
+            name: "My Group".to_string(),
+            admin: Some(create_test_user()),
+            members: vec![create_test_user()],
+        };
+
+        let err = group.get_value("members[0].pets[0].nmae").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Field not found: nmae in Pet at component 2 of `members[0].pets[0].nmae`, did you mean `name`?"
+        );
+        match err {
+            StructPathError::FieldNotFound {
+                field,
+                index,
+                type_name,
+                valid_fields,
+                ..
+            } => {
+                assert_eq!(field, "nmae");
+                assert_eq!(index, 2);
+                assert_eq!(type_name, "Pet");
+                assert_eq!(valid_fields, vec!["name", "birth_year"]);
+            }
+            _ => panic!("Expected FieldNotFound"),
+        }
+
+        // Nested access only lists the fields holding structs
+        let err = group.get_value("admn.name").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Field not found: admn in Group at component 0 of `admn.name`, did you mean `admin`?"
+        );
+    }
+
+    #[test]
+    fn test_get_value_index_out_of_bounds() {
+        let group = sample::Group {
+            name: "My Group".to_string(),
+            admin: Some(create_test_user()),
+            members: vec![create_test_user()],
+        };
+
+        let err = group.get_value("members[5].name").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Index 5 out of bounds for members of length 1 in Group at component 0 of `members[5].name`"
+        );
+
+        let err = group.get_value("members[0].pets[7]").unwrap_err();
+        match err {
+            StructPathError::IndexOutOfBounds {
+                field,
+                index,
+                len,
+                path,
+                component,
+                type_name,
+            } => {
+                assert_eq!(field, "pets");
+                assert_eq!((index, len), (7, 2));
+                assert_eq!(path, "members[0].pets[7]");
+                assert_eq!(component, 1);
+                assert_eq!(type_name, "User");
+            }
+            _ => panic!("Expected IndexOutOfBounds"),
+        }
+    }
+
+    #[test]
+    fn test_get_as() -> Result<(), Box<dyn std::error::Error>> {
+        let group = sample::Group {
+            name: "My Group".to_string(),
+            admin: Some(create_test_user()),
+            members: vec![create_test_user()],
+        };
+
+        assert_eq!(group.get_as::<String>("admin.name")?, "John Doe");
+        assert_eq!(group.get_as::<i64>("members[0].pets[1].birth_year")?, 2022);
+        assert_eq!(group.get_as::<f64>("members[0].age")?, 30.0);
+        assert_eq!(
+            group.get_as::<String>("admin.email")?,
+            "john.doe@example.com"
+        );
+        assert_eq!(
+            group.get_as::<Option<String>>("admin.email")?,
+            Some("john.doe@example.com".to_string())
+        );
+        assert_eq!(group.get_as::<sample::User>("admin")?, create_test_user());
+        assert_eq!(
+            group
+                .get_as::<sample::user::Pet>("members[0].pets[0]")?
+                .name,
+            "Buddy"
+        );
+        assert_eq!(group.get_as::<Vec<sample::User>>("members")?.len(), 1);
+
+        assert!(matches!(
+            group.get_as::<bool>("name"),
+            Err(StructPathError::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            sample::Group::default().get_as::<sample::User>("admin"),
+            Err(StructPathError::NullValue)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_macro() -> Result<(), StructPathError> {
+        let group = sample::Group {
+            name: "My Group".to_string(),
+            admin: Some(create_test_user()),
+            members: vec![create_test_user()],
+        };
+
+        let birth_year = structpath::path!(sample::Group, members[0].pets[1].birth_year);
+        assert_eq!(birth_year(&group)?, 2022);
+
+        let email = structpath::path!(sample::Group, admin.email);
+        assert_eq!(email(&group)?, "john.doe@example.com");
+        assert!(matches!(
+            email(&sample::Group::default()),
+            Err(StructPathError::NullValue)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compiled_path() -> Result<(), StructPathError> {
+        let groups = [
+            sample::Group {
+                name: "My Group".to_string(),
+                admin: Some(create_test_user()),
+                members: vec![create_test_user()],
+            },
+            sample::Group::default(),
+        ];
+
+        for path in ["name", "admin", "admin.email", "admin.pets[1].name"] {
+            let compiled = CompiledPath::<sample::Group>::from_str(path)?;
+            assert_eq!(compiled.path().to_string(), path);
+            for group in &groups {
+                assert_eq!(compiled.get_value(group)?, group.get_value(path)?);
+            }
+        }
+
+        let birth_year = CompiledPath::<sample::Group>::from_str("members[0].pets[0].birth_year")?;
+        assert_eq!(birth_year.get_value(&groups[0])?.as_i64(), 2020);
+
+        let err = CompiledPath::<sample::Group>::from_str("members[0].pets[0].nmae").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            groups[0]
+                .get_value("members[0].pets[0].nmae")
+                .unwrap_err()
+                .to_string()
+        );
+
+        // Indices out of bounds fail when evaluated, as with `get_value`
+        assert!(matches!(
+            birth_year.get_value(&groups[1]),
+            Err(StructPathError::IndexOutOfBounds { index: 0, .. })
+        ));
+        for path in [
+            "members[1].name",
+            "members[0].pets[3].name",
+            "members[0].pets[2]",
+        ] {
+            let compiled = CompiledPath::<sample::Group>::from_str(path)?;
+            assert_eq!(
+                compiled.get_value(&groups[0]).unwrap_err().to_string(),
+                groups[0].get_value(path).unwrap_err().to_string()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_values() -> Result<(), Box<dyn std::error::Error>> {
+        let paths = [
+            "name",
+            "admin",
+            "admin.name",
+            "admin.email",
+            "admin.favourite_pet.name",
+            "admin.pets[1].birth_year",
+            "admin.nmae",
+            "admin.pets[0].nmae",
+            "members[0].pets[0].name",
+            "name",
+            "members[3].name",
+            "admin.pets[5].name",
+        ];
+        let parsed: Vec<Path> = paths
+            .iter()
+            .map(|p| Path::from_str(p))
+            .collect::<Result<_, _>>()?;
+
+        let groups = [
+            sample::Group {
+                name: "My Group".to_string(),
+                admin: Some(create_test_user()),
+                members: vec![create_test_user()],
+            },
+            sample::Group {
+                members: vec![create_test_user()],
+                ..Default::default()
+            },
+        ];
+
+        for group in &groups {
+            let values = group.get_values(&parsed);
+            assert_eq!(values.len(), paths.len());
+            for (path, value) in paths.iter().zip(values) {
+                match (value, group.get_value(path)) {
+                    (Ok(batch), Ok(single)) => assert_eq!(batch, single, "{path}"),
+                    (Err(batch), Err(single)) => {
+                        assert_eq!(batch.to_string(), single.to_string(), "{path}")
+                    }
+                    (batch, single) => panic!("{path}: {batch:?} != {single:?}"),
+                }
+            }
+        }
+
+        let values = groups[0].get_values(&parsed);
+        assert_eq!(values[5].as_ref().unwrap().as_i64(), 2022);
+        assert_eq!(values[8].as_ref().unwrap().as_str(), "Buddy");
+        // Indices out of bounds fail their own paths only
+        assert!(matches!(
+            values[10],
+            Err(StructPathError::IndexOutOfBounds { index: 3, .. })
+        ));
+        assert!(matches!(
+            values[11],
+            Err(StructPathError::IndexOutOfBounds { index: 5, .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_paths() -> Result<(), Box<dyn std::error::Error>> {
+        let user = create_test_user();
+        let paths = [
+            "name",
+            "age",
+            "email",
+            "is_active",
+            "loyalty",
+            "favourite_pet.name",
+            "favourite_pet.birth_year",
+            "tags[0]",
+            "tags[1]",
+            "pets[0].name",
+            "pets[0].birth_year",
+            "pets[1].name",
+            "pets[1].birth_year",
+        ];
+        let mut source = HashMap::new();
+        for path in paths {
+            source.insert(path.to_string(), user.get_value(path)?);
+        }
+        assert_eq!(sample::User::from_paths(&source, &[])?, user);
+
+        source.insert("email".to_string(), Value::Option(None));
+        source.remove("favourite_pet.name");
+        source.remove("favourite_pet.birth_year");
+        let user = sample::User::from_paths(&source, &[])?;
+        assert_eq!(user.email, None);
+        assert_eq!(user.favourite_pet, None);
+
+        source.remove("age");
+        let err = sample::User::from_paths(&source, &[]).unwrap_err();
+        assert!(matches!(err, StructPathError::MissingPath(path) if path == "age"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_messages() -> Result<(), Box<dyn std::error::Error>> {
+        let father = sample::user::Father {
+            name: "Jack Doe".to_string(),
+            age: 60,
+            childhood_pet: Some(sample::user::Pet {
+                name: "Rex".to_string(),
+                birth_year: 1975,
+            }),
+        };
+        assert_eq!(father.get_value("childhood_pet.name")?.as_str(), "Rex");
+
+        let mut user = create_test_user();
+        user.loyalty = sample::user::Loyalty::Gold as i32;
+        user.favorite_parent = Some(sample::user::FavoriteParent::Father(father));
+        user.labels.insert("team".to_string(), "core".to_string());
+
+        // Enums are their number, oneofs have a field per variant and maps
+        // are navigated by key
+        assert_eq!(user.get_value("loyalty")?.as_i64(), 1);
+        assert_eq!(user.get_value("#7")?.as_i64(), 1);
+        assert_eq!(
+            user.get_value("favorite_parent.father.name")?.as_str(),
+            "Jack Doe"
+        );
+        assert_eq!(
+            user.get_value("favorite_parent.#9.childhood_pet.name")?
+                .as_str(),
+            "Rex"
+        );
+        assert_eq!(user.get_value("favorite_parent.mother")?.as_option(), None);
+        assert_eq!(
+            user.get_value("favorite_parent.mother.name")?.as_option(),
+            None
+        );
+        assert_eq!(
+            user.get_value("favorite_parent")?
+                .unwrap()
+                .as_unboxed::<sample::user::FavoriteParent>(),
+            user.favorite_parent.as_ref().unwrap()
+        );
+        assert_eq!(user.get_value("labels.team")?.as_str(), "core");
+        assert_eq!(
+            user.get_value("labels")?
+                .as_unboxed::<HashMap<String, String>>()
+                .get("team")
+                .map(String::as_str),
+            Some("core")
+        );
+
+        let err = user.get_value("favorite_parent.sister").unwrap_err();
+        assert!(matches!(
+            err,
+            StructPathError::FieldNotFound { index: 1, ref valid_fields, .. }
+                if valid_fields == &["father", "mother"]
+        ));
+        let err = user.get_value("labels.city").unwrap_err();
+        assert!(matches!(
+            err,
+            StructPathError::FieldNotFound { index: 1, ref valid_fields, .. }
+                if valid_fields == &["team"]
+        ));
+
+        let father_age = CompiledPath::<sample::User>::from_str("favorite_parent.father.age")?;
+        assert_eq!(father_age.get_value(&user)?.as_i64(), 60);
+        let team = CompiledPath::<sample::User>::from_str("labels.team")?;
+        assert_eq!(team.get_value(&user)?.as_str(), "core");
+        let father_name = structpath::path!(sample::User, favorite_parent.father.name);
+        assert_eq!(father_name(&user)?, "Jack Doe");
+        let mother_name = structpath::path!(sample::User, favorite_parent.mother.name);
+        assert!(matches!(
+            mother_name(&user),
+            Err(StructPathError::NullValue)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_paths_oneof() -> Result<(), Box<dyn std::error::Error>> {
+        let mut user = create_test_user();
+        user.loyalty = sample::user::Loyalty::Platinum as i32;
+        user.favorite_parent = Some(sample::user::FavoriteParent::Mother(sample::user::Mother {
+            name: "Jane Doe".to_string(),
+            age: 58,
+        }));
+
+        let paths = [
+            "name",
+            "age",
+            "is_active",
+            "loyalty",
+            "favorite_parent.mother.name",
+            "favorite_parent.mother.age",
+        ];
+        let mut source = HashMap::new();
+        for path in paths {
+            source.insert(path.to_string(), user.get_value(path)?);
+        }
+        let read = sample::User::from_paths(&source, &[])?;
+        assert_eq!(read.loyalty, user.loyalty);
+        assert_eq!(read.favorite_parent, user.favorite_parent);
+
+        source.remove("favorite_parent.mother.name");
+        source.remove("favorite_parent.mother.age");
+        assert_eq!(
+            sample::User::from_paths(&source, &[])?.favorite_parent,
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_proto_field_info() -> Result<(), Box<dyn std::error::Error>> {
+        let info = sample::User::get_fields_info();
+        assert_eq!(info.name, "User");
+        assert_eq!(info.proto_name.as_deref(), Some("sample.User"));
+        assert_eq!(
+            sample::user::Pet::get_fields_info().proto_name.as_deref(),
+            Some("sample.User.Pet")
+        );
+
+        let proto = |path: &str| -> Result<Option<ProtoField>, Box<dyn std::error::Error>> {
+            Ok(sample::Group::get_field_info(&Path::from_str(path)?)?.proto)
+        };
+        let field = |number, r#type: &str, label| {
+            Some(ProtoField {
+                number,
+                r#type: r#type.to_string(),
+                label,
+            })
+        };
+        assert_eq!(proto("name")?, field(1, "string", ProtoLabel::Singular));
+        assert_eq!(proto("admin.age")?, field(2, "int64", ProtoLabel::Singular));
+        assert_eq!(
+            proto("admin.email")?,
+            field(3, "string", ProtoLabel::Optional)
+        );
+        assert_eq!(
+            proto("admin.favourite_pet")?,
+            field(5, "message", ProtoLabel::Optional)
+        );
+        assert_eq!(
+            proto("members[0].pets")?,
+            field(8, "message", ProtoLabel::Repeated)
+        );
+        assert_eq!(
+            proto("admin.loyalty")?,
+            field(7, "enum", ProtoLabel::Singular)
+        );
+        assert_eq!(
+            proto("admin.labels")?,
+            field(11, "map", ProtoLabel::Repeated)
+        );
+        assert_eq!(proto("admin.favorite_parent")?, None);
+        assert_eq!(
+            proto("admin.favorite_parent.mother")?,
+            field(10, "message", ProtoLabel::Singular)
+        );
+
+        let r#type = |path: &str| -> Result<FieldType, Box<dyn std::error::Error>> {
+            Ok(sample::User::get_field_info(&Path::from_str(path)?)?.r#type)
+        };
+        assert_eq!(r#type("loyalty")?, FieldType::Integer);
+        assert_eq!(
+            r#type("favorite_parent.father")?,
+            FieldType::Option(Box::new(FieldType::StructPath))
+        );
+        assert_eq!(
+            r#type("favorite_parent.father.childhood_pet.name")?,
+            FieldType::Option(Box::new(FieldType::String))
+        );
+        assert_eq!(r#type("labels")?, FieldType::StructPath);
+
+        let birth_year =
+            sample::Group::get_field_info(&Path::from_str("admin.pets[0].birth_year")?)?;
+        assert_eq!(
+            birth_year.r#type,
+            FieldType::Option(Box::new(FieldType::Integer))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_numbers() -> Result<(), Box<dyn std::error::Error>> {
+        let user = create_test_user();
+
+        assert_eq!(user.get_value("#1")?.as_str(), "John Doe");
+        assert_eq!(user.get_value("#5.#2")?.as_i64(), 2020);
+        assert_eq!(user.get_as::<String>("#8[1].#1")?, "Max");
+        assert_eq!(user.get_value("#8[1].#1")?, user.get_value("pets[1].name")?);
+        assert!(matches!(
+            user.get_value("#42"),
+            Err(StructPathError::FieldNotFound { .. })
+        ));
+
+        let compiled = CompiledPath::<sample::User>::from_str("#6[0]")?;
+        assert_eq!(compiled.get_value(&user)?.as_str(), "premium");
+
+        let paths = [Path::from_str("#5.#1")?, Path::from_str("#4")?];
+        let values = user.get_values(&paths);
+        assert_eq!(values[0].as_ref().unwrap().as_str(), "Buddy");
+        assert!(values[1].as_ref().unwrap().as_bool());
+
+        let info = sample::User::get_field_info(&Path::from_str("#8[0].#2")?)?;
+        assert_eq!(info.name, "birth_year");
+        Ok(())
+    }
+
+    #[test]
+    fn test_well_known_types() -> Result<(), Box<dyn std::error::Error>> {
+        use prost::Message;
+        use prost_types::{value::Kind, Any, Duration, Struct, Timestamp};
+
+        let mut metadata = Struct::default();
+        metadata.fields.insert(
+            "source".to_string(),
+            prost_types::Value {
+                kind: Some(Kind::StringValue("api".to_string())),
+            },
+        );
+        let pet = sample::user::Pet {
+            name: "Max".to_string(),
+            birth_year: 2022,
+        };
+        let created_at = Timestamp {
+            seconds: 1_714_564_800,
+            nanos: 0,
+        };
+        let event = sample::Event {
+            name: "signup".to_string(),
+            created_at: Some(created_at),
+            elapsed: Some(Duration {
+                seconds: 1,
+                nanos: 500_000_000,
+            }),
+            note: Some("first".to_string()),
+            attempts: None,
+            metadata: Some(metadata),
+            payload: Some(Any::from_msg(&pet)?),
+            retries: vec![created_at],
+            checksum: vec![0xca, 0xfe],
+            priority: Some(-2),
+            sequence: Some(u64::MAX),
+            score: Some(0.5),
+        };
+
+        // Timestamps and durations are native values, wrappers are optional
+        // scalars
+        assert_eq!(
+            event.get_value("created_at")?.unwrap(),
+            &Value::DateTime {
+                seconds: 1_714_564_800,
+                nanos: 0
+            }
+        );
+        assert_eq!(event.get_as::<Duration>("elapsed")?.nanos, 500_000_000);
+        assert_eq!(
+            event.get_as::<std::time::Duration>("#3")?,
+            std::time::Duration::from_millis(1500)
+        );
+        assert_eq!(event.get_as::<String>("note")?, "first");
+        assert_eq!(event.get_as::<Option<i64>>("attempts")?, None);
+        assert_eq!(event.get_as::<Timestamp>("retries[0]")?, created_at);
+        assert_eq!(event.get_as::<i32>("priority")?, -2);
+        assert_eq!(
+            event.get_value("sequence")?.unwrap(),
+            &Value::BigUint(u64::MAX.into())
+        );
+        assert_eq!(event.get_as::<f32>("score")?, 0.5);
+
+        // Structs are navigated by key, and Anys expose their type URL
+        assert_eq!(event.get_as::<String>("metadata.source")?, "api");
+        assert_eq!(
+            event.get_as::<String>("payload.type_url")?,
+            <sample::user::Pet as prost::Name>::type_url()
+        );
+        let source = CompiledPath::<sample::Event>::from_str("metadata.source")?;
+        assert_eq!(source.get_value(&event)?.as_str(), "api");
+
+        let info = sample::Event::get_fields_info();
+        let types: Vec<_> = info
+            .fields
+            .iter()
+            .map(|field| field.r#type.clone())
+            .collect();
+        let optional = |field_type| FieldType::Option(Box::new(field_type));
+        assert_eq!(
+            types,
+            vec![
+                FieldType::String,
+                optional(FieldType::DateTime),
+                optional(FieldType::Duration),
+                optional(FieldType::String),
+                optional(FieldType::Integer),
+                optional(FieldType::StructPath),
+                optional(FieldType::StructPath),
+                FieldType::Vec(Box::new(FieldType::DateTime)),
+                FieldType::Bytes,
+                optional(FieldType::Integer),
+                optional(FieldType::Integer),
+                optional(FieldType::Float),
+            ]
+        );
+
+        // Bytes are binary values, like the encoded message of an `Any`
+        assert_eq!(event.get_value("checksum")?.as_bytes(), &[0xca, 0xfe]);
+        assert_eq!(
+            event.get_value("payload.value")?.as_bytes(),
+            pet.encode_to_vec()
+        );
+
+        let paths = [
+            "name",
+            "created_at",
+            "elapsed",
+            "note",
+            "attempts",
+            "retries[0]",
+            "checksum",
+            "priority",
+            "sequence",
+            "score",
+        ];
+        let mut source = HashMap::new();
+        for path in paths {
+            source.insert(path.to_string(), event.get_value(path)?);
+        }
+        let read = sample::Event::from_paths(&source, &[])?;
+        assert_eq!(read.created_at, event.created_at);
+        assert_eq!(read.elapsed, event.elapsed);
+        assert_eq!(read.note, event.note);
+        assert_eq!(read.retries, event.retries);
+        assert_eq!(read.checksum, event.checksum);
+        assert_eq!(read.priority, event.priority);
+        assert_eq!(read.sequence, event.sequence);
+        assert_eq!(read.score, event.score);
+        Ok(())
+    }
 }
@@ -0,0 +1,121 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use structpath::{CompiledPath, Path, PathBatch, StructPath};
+
+#[derive(StructPath, Debug, Clone)]
+struct Pet {
+    name: String,
+    birth_year: i64,
+}
+
+#[derive(StructPath, Debug, Clone)]
+struct User {
+    name: String,
+    age: i64,
+    #[type_hint = "struct"]
+    pets: Vec<Pet>,
+}
+
+#[derive(StructPath, Debug, Clone)]
+struct Group {
+    name: String,
+    #[type_hint = "struct"]
+    admin: Option<User>,
+    #[type_hint = "struct"]
+    members: Vec<User>,
+}
+
+fn build_groups(count: usize) -> Vec<Group> {
+    let user = User {
+        name: "John".to_string(),
+        age: 30,
+        pets: vec![Pet {
+            name: "Buddy".to_string(),
+            birth_year: 2020,
+        }],
+    };
+    (0..count)
+        .map(|i| Group {
+            name: format!("group {i}"),
+            admin: Some(user.clone()),
+            members: vec![user.clone(); 3],
+        })
+        .collect()
+}
+
+fn bench_paths(c: &mut Criterion) {
+    let groups = build_groups(1000);
+
+    for path in ["name", "admin.age", "members[0].pets[0].birth_year"] {
+        let mut group = c.benchmark_group(path);
+
+        group.bench_function("get_value", |b| {
+            b.iter(|| {
+                for g in &groups {
+                    black_box(g.get_value(black_box(path)).unwrap());
+                }
+            })
+        });
+
+        let compiled = CompiledPath::<Group>::from_str(path).unwrap();
+        group.bench_function("compiled", |b| {
+            b.iter(|| {
+                for g in &groups {
+                    black_box(compiled.get_value(black_box(g)).unwrap());
+                }
+            })
+        });
+
+        group.finish();
+    }
+}
+
+fn bench_batch(c: &mut Criterion) {
+    let groups = build_groups(1000);
+    let paths: Vec<Path> = [
+        "name",
+        "admin.name",
+        "admin.age",
+        "admin.pets[0].name",
+        "admin.pets[0].birth_year",
+        "members[0].name",
+        "members[0].age",
+        "members[0].pets[0].name",
+        "members[0].pets[0].birth_year",
+    ]
+    .iter()
+    .map(|path| Path::from_str(path).unwrap())
+    .collect();
+
+    let mut group = c.benchmark_group("batch");
+
+    group.bench_function("get_value_by_path", |b| {
+        b.iter(|| {
+            for g in &groups {
+                let values: Vec<_> = paths.iter().map(|path| g.get_value_by_path(path)).collect();
+                black_box(values);
+            }
+        })
+    });
+
+    group.bench_function("get_values", |b| {
+        b.iter(|| {
+            for g in &groups {
+                black_box(g.get_values(&paths));
+            }
+        })
+    });
+
+    let batch = PathBatch::new(paths.clone());
+    group.bench_function("get_values_batch", |b| {
+        b.iter(|| {
+            for g in &groups {
+                black_box(g.get_values_batch(&batch));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_paths, bench_batch);
+criterion_main!(benches);
@@ -1,3 +1,6 @@
+// The examples compare booleans with `assert_eq!` like the other values
+#![allow(clippy::bool_assert_comparison)]
+
 use structpath::StructPath;
 
 fn main() {
@@ -0,0 +1,539 @@
+use crate::{
+    error::StructPathError,
+    path::{components_to_string, Path, PathComponent},
+    traits::{Borrowed, StructInfo, StructPath},
+    value::{to_scale, Value},
+};
+use arrow::array::{
+    ArrayBuilder, ArrayRef, BinaryBuilder, BooleanBuilder, Date32Builder, Decimal128Builder,
+    DurationNanosecondBuilder, Float64Builder, Int64Builder, ListArray, StringBuilder, StructArray,
+    TimestampNanosecondBuilder,
+};
+use arrow::buffer::{NullBuffer, OffsetBuffer};
+#[cfg(feature = "rayon")]
+use arrow::datatypes::DataType;
+use arrow::datatypes::{Field, Schema};
+use arrow::error::ArrowError;
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use std::sync::Arc;
+use structpath_types::{FieldType, FieldsInfo};
+
+/// Where a value to extract is: the struct holding it, `None` below a missing
+/// option, and its index when it is an element of a list field
+type Slot<'a> = (Option<&'a dyn StructPath>, Option<usize>);
+
+/// The component reaching the values of a column from their slots, or none
+/// for the slots themselves, whose index is set for each element of a list
+struct Lookup(Vec<PathComponent>);
+
+impl Lookup {
+    fn new(component: Option<PathComponent>) -> Self {
+        Lookup(component.into_iter().collect())
+    }
+
+    fn components(&mut self, slot: Slot) -> &[PathComponent] {
+        if let (Some(PathComponent::ArrayIndex(_, index)), Some(slot_index)) =
+            (self.0.first_mut(), slot.1)
+        {
+            *index = slot_index;
+        }
+        &self.0
+    }
+
+    /// The value at `slot`, with `Option` layers stripped
+    fn value(&mut self, slot: Slot) -> Result<Option<Value>, StructPathError> {
+        match slot.0 {
+            Some(parent) => Ok(parent
+                .get_value_by_components(self.components(slot))?
+                .flatten()),
+            None => Ok(None),
+        }
+    }
+
+    /// The struct or list at `slot`, without cloning it
+    fn borrow<'a>(&mut self, slot: Slot<'a>) -> Result<Borrowed<'a>, StructPathError> {
+        match slot.0 {
+            Some(parent) => parent.borrow_by_components(self.components(slot)),
+            None => Ok(Borrowed::None),
+        }
+    }
+
+    fn mismatch(&self, expected: &str) -> StructPathError {
+        StructPathError::TypeMismatch {
+            expected: expected.to_string(),
+            actual: format!("the value at `{}`", components_to_string(&self.0)),
+        }
+    }
+}
+
+/// Extract `paths` from every record into an Arrow `RecordBatch`
+///
+/// Each path becomes a column named after it, typed from [`StructInfo`]:
+/// nested structs become `StructArray`s, `Vec`s become `ListArray`s and
+/// missing `Option`s become nulls. Date-times are nanosecond timestamps in
+/// UTC, without time zone, as in the Polars `Datetime` columns of
+/// [`DataFrameBuilder`](crate::DataFrameBuilder), durations are nanosecond
+/// durations, dates are `Date32`, bytes are `Binary`, decimals
+/// `Decimal128(38, scale)` of the largest scale among their values and
+/// `i128` and `u128` integers `Decimal128(38, 0)`. Fields of unknown types
+/// are left out of `StructArray`s, and recursive structs can only be
+/// extracted field by field.
+///
+/// An array index in `paths` beyond the list of a record gives an
+/// `IndexOutOfBounds` error, like [`StructPath::get_value`].
+///
+/// ```ignore
+/// let paths = ["name", "admin", "members"].map(|p| Path::from_str(p).unwrap());
+/// let batch = structpath::to_record_batch(&groups, &paths)?;
+/// ```
+pub fn to_record_batch<T: StructPath + StructInfo>(
+    records: &[T],
+    paths: &[Path],
+) -> Result<RecordBatch, StructPathError> {
+    let mut fields = Vec::with_capacity(paths.len());
+    let mut columns = Vec::with_capacity(paths.len());
+    for path in paths {
+        let info = T::get_field_info(path)?;
+        if !is_supported(&info.r#type) {
+            return Err(StructPathError::Arrow(format!(
+                "`{path}` has a type that cannot be converted to Arrow"
+            )));
+        }
+        let (array, nullable) = path_array(records, path, &info.r#type, info.nested)?;
+        fields.push(Field::new(
+            path.to_string(),
+            array.data_type().clone(),
+            nullable,
+        ));
+        columns.push(array);
+    }
+
+    let options = RecordBatchOptions::new().with_row_count(Some(records.len()));
+    RecordBatch::try_new_with_options(Arc::new(Schema::new(fields)), columns, &options)
+        .map_err(arrow_error)
+}
+
+/// Extract `paths` like [`to_record_batch`], building batches of `chunk_size`
+/// records in parallel before concatenating them
+#[cfg(feature = "rayon")]
+pub fn par_to_record_batch<T: StructPath + StructInfo + Sync>(
+    records: &[T],
+    paths: &[Path],
+    chunk_size: usize,
+) -> Result<RecordBatch, StructPathError> {
+    use rayon::prelude::*;
+
+    let batches = records
+        .par_chunks(chunk_size.max(1))
+        .map(|chunk| to_record_batch(chunk, paths))
+        .collect::<Result<Vec<_>, _>>()?;
+    let batches = unify_decimal_scales(batches)?;
+    match batches.first() {
+        Some(first) => {
+            arrow::compute::concat_batches(&first.schema(), &batches).map_err(arrow_error)
+        }
+        None => to_record_batch(records, paths),
+    }
+}
+
+/// Build the column of `path`, of type `field_type`
+///
+/// The structs holding the values are borrowed from each record, and the
+/// values looked up in them.
+fn path_array<T: StructPath>(
+    records: &[T],
+    path: &Path,
+    field_type: &FieldType,
+    nested: Option<fn() -> FieldsInfo>,
+) -> Result<(ArrayRef, bool), StructPathError> {
+    let Some((last, parents)) = path.components.split_last() else {
+        let slots: Vec<Slot> = records
+            .iter()
+            .map(|record| (Some(record as &dyn StructPath), None))
+            .collect();
+        return build_array(&slots, None, field_type, nested, false, &mut Vec::new());
+    };
+
+    let mut slots = Vec::with_capacity(records.len());
+    for record in records {
+        let parent = match record
+            .borrow_by_components(parents)
+            .map_err(|e| e.nested_at(&path.components, 0))?
+        {
+            Borrowed::Struct(parent) => Some(parent),
+            Borrowed::None => None,
+            Borrowed::List(_) | Borrowed::Value => {
+                return Err(StructPathError::TypeMismatch {
+                    expected: "StructPath".to_string(),
+                    actual: format!("the value at `{}`", components_to_string(parents)),
+                })
+            }
+        };
+        let index = match last {
+            PathComponent::ArrayIndex(_, index) => Some(*index),
+            PathComponent::Field(_) => None,
+        };
+        slots.push((parent, index));
+    }
+    build_array(
+        &slots,
+        Some(last.clone()),
+        field_type,
+        nested,
+        false,
+        &mut Vec::new(),
+    )
+    .map_err(|e| e.nested_at(&path.components, parents.len()))
+}
+
+/// Cast the decimals of `batches`, at any depth, to the largest scale among
+/// them, so that the batches can be concatenated
+#[cfg(feature = "rayon")]
+fn unify_decimal_scales(batches: Vec<RecordBatch>) -> Result<Vec<RecordBatch>, StructPathError> {
+    use arrow::compute::{cast_with_options, CastOptions};
+
+    let Some((first, others)) = batches.split_first() else {
+        return Ok(batches);
+    };
+    let mut fields: Vec<Field> = first
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.as_ref().clone())
+        .collect();
+    for batch in others {
+        for (field, other) in fields.iter_mut().zip(batch.schema().fields()) {
+            let data_type = widest_decimals(field.data_type(), other.data_type());
+            *field = field.clone().with_data_type(data_type);
+        }
+    }
+    let schema = Arc::new(Schema::new(fields));
+
+    // Values with too many digits at the scale fail rather than become null
+    let options = CastOptions {
+        safe: false,
+        ..Default::default()
+    };
+    batches
+        .into_iter()
+        .map(|batch| {
+            if batch.schema() == schema {
+                return Ok(batch);
+            }
+            let columns = batch
+                .columns()
+                .iter()
+                .zip(schema.fields())
+                .map(|(column, field)| cast_with_options(column, field.data_type(), &options))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(arrow_error)?;
+            let options = RecordBatchOptions::new().with_row_count(Some(batch.num_rows()));
+            RecordBatch::try_new_with_options(schema.clone(), columns, &options)
+                .map_err(arrow_error)
+        })
+        .collect()
+}
+
+/// The type `a`, with its decimals at the larger of their scale and that of
+/// the same decimals in `b`
+#[cfg(feature = "rayon")]
+fn widest_decimals(a: &DataType, b: &DataType) -> DataType {
+    let field = |a: &Field, b: &Field| {
+        a.clone()
+            .with_data_type(widest_decimals(a.data_type(), b.data_type()))
+    };
+    match (a, b) {
+        (DataType::Decimal128(precision, a), DataType::Decimal128(_, b)) => {
+            DataType::Decimal128(*precision, *a.max(b))
+        }
+        (DataType::Struct(a), DataType::Struct(b)) => {
+            DataType::Struct(a.iter().zip(b.iter()).map(|(a, b)| field(a, b)).collect())
+        }
+        (DataType::List(a), DataType::List(b)) => DataType::List(Arc::new(field(a, b))),
+        (a, _) => a.clone(),
+    }
+}
+
+fn arrow_error(error: ArrowError) -> StructPathError {
+    StructPathError::Arrow(error.to_string())
+}
+
+fn is_supported(field_type: &FieldType) -> bool {
+    match field_type {
+        FieldType::Option(inner) | FieldType::Vec(inner) => is_supported(inner),
+        FieldType::Unknown => false,
+        _ => true,
+    }
+}
+
+/// An `i128` or `u128` integer, which must fit in 38 digits
+fn big_integer(value: &Value) -> Result<i128, StructPathError> {
+    let integer = match value {
+        Value::BigUint(value) => i128::try_from(*value).ok(),
+        value => Some(value.try_as_i128()?),
+    };
+    integer
+        .filter(|integer| integer.unsigned_abs() < 10u128.pow(38))
+        .ok_or_else(|| StructPathError::Arrow(format!("{value:?} has more than 38 digits")))
+}
+
+/// Nanoseconds of a date-time or duration, which must fit in an `i64`
+fn nanos(value: &Value) -> Result<i64, StructPathError> {
+    value
+        .total_nanos()?
+        .ok_or_else(|| StructPathError::Arrow(format!("{value:?} is out of the nanosecond range")))
+}
+
+/// Build the decimals at `slots`, brought to the largest scale among them
+fn build_decimal(slots: &[Slot], mut lookup: Lookup) -> Result<ArrayRef, StructPathError> {
+    let values = slots
+        .iter()
+        .map(|slot| {
+            lookup
+                .value(*slot)?
+                .map(|value| value.try_as_decimal())
+                .transpose()
+        })
+        .collect::<Result<Vec<_>, StructPathError>>()?;
+    let scale = values.iter().flatten().map(|(_, scale)| *scale).max();
+    let scale = scale.unwrap_or_default();
+    let mut builder = Decimal128Builder::with_capacity(values.len())
+        .with_precision_and_scale(38, i8::try_from(scale).unwrap_or(i8::MAX))
+        .map_err(arrow_error)?;
+    for value in values {
+        let mantissa = value
+            .map(|(mantissa, from)| {
+                to_scale(mantissa, from, scale)
+                    .filter(|mantissa| mantissa.unsigned_abs() < 10u128.pow(38))
+                    .ok_or_else(|| {
+                        StructPathError::Arrow(format!(
+                            "decimal {mantissa}e-{from} has more than 38 digits at scale {scale}"
+                        ))
+                    })
+            })
+            .transpose()?;
+        builder.append_option(mantissa);
+    }
+    Ok(Arc::new(builder.finish()))
+}
+
+fn build_leaf<B, F>(
+    slots: &[Slot],
+    mut lookup: Lookup,
+    mut builder: B,
+    mut append: F,
+) -> Result<ArrayRef, StructPathError>
+where
+    B: ArrayBuilder,
+    F: FnMut(&mut B, Option<Value>) -> Result<(), StructPathError>,
+{
+    for slot in slots {
+        append(&mut builder, lookup.value(*slot)?)?;
+    }
+    Ok(builder.finish())
+}
+
+/// Build the array of the values reached by `component` from `slots`, of type
+/// `field_type`
+///
+/// `nullable` tells whether the values can be missing because of an optional
+/// ancestor. Returns the array and whether its field is nullable.
+fn build_array(
+    slots: &[Slot],
+    component: Option<PathComponent>,
+    field_type: &FieldType,
+    nested: Option<fn() -> FieldsInfo>,
+    nullable: bool,
+    ancestors: &mut Vec<String>,
+) -> Result<(ArrayRef, bool), StructPathError> {
+    let (field_type, nullable) = match field_type {
+        FieldType::Option(inner) => (inner.as_ref(), true),
+        field_type => (field_type, nullable),
+    };
+
+    let lookup = Lookup::new(component);
+    let array = match field_type {
+        FieldType::String => build_leaf(slots, lookup, StringBuilder::new(), |b, value| {
+            match value {
+                Some(value) => b.append_value(value.try_as_str()?),
+                None => b.append_null(),
+            }
+            Ok(())
+        })?,
+        FieldType::Integer => build_leaf(slots, lookup, Int64Builder::new(), |b, value| {
+            b.append_option(value.map(|v| v.try_as_i64()).transpose()?);
+            Ok(())
+        })?,
+        FieldType::Float => build_leaf(slots, lookup, Float64Builder::new(), |b, value| {
+            b.append_option(value.map(|v| v.try_as_f64()).transpose()?);
+            Ok(())
+        })?,
+        FieldType::Boolean => build_leaf(slots, lookup, BooleanBuilder::new(), |b, value| {
+            b.append_option(value.map(|v| v.try_as_bool()).transpose()?);
+            Ok(())
+        })?,
+        FieldType::DateTime => build_leaf(
+            slots,
+            lookup,
+            TimestampNanosecondBuilder::new(),
+            |b, value| {
+                b.append_option(value.map(|v| nanos(&v)).transpose()?);
+                Ok(())
+            },
+        )?,
+        FieldType::Date => build_leaf(slots, lookup, Date32Builder::new(), |b, value| {
+            b.append_option(value.map(|v| v.try_as_date()).transpose()?);
+            Ok(())
+        })?,
+        FieldType::Duration => build_leaf(
+            slots,
+            lookup,
+            DurationNanosecondBuilder::new(),
+            |b, value| {
+                b.append_option(value.map(|v| nanos(&v)).transpose()?);
+                Ok(())
+            },
+        )?,
+        FieldType::Bytes => build_leaf(slots, lookup, BinaryBuilder::new(), |b, value| {
+            b.append_option(value.as_ref().map(|v| v.try_as_bytes()).transpose()?);
+            Ok(())
+        })?,
+        FieldType::BigInt => build_leaf(
+            slots,
+            lookup,
+            Decimal128Builder::new()
+                .with_precision_and_scale(38, 0)
+                .map_err(arrow_error)?,
+            |b, value| {
+                b.append_option(value.map(|v| big_integer(&v)).transpose()?);
+                Ok(())
+            },
+        )?,
+        FieldType::Decimal => build_decimal(slots, lookup)?,
+        FieldType::StructPath => {
+            let info = nested.ok_or_else(|| StructPathError::TypeMismatch {
+                expected: "StructInfo".to_string(),
+                actual: "StructPath".to_string(),
+            })?;
+            build_struct(slots, lookup, info(), nullable, ancestors)?
+        }
+        FieldType::Vec(elem_type) => {
+            build_list(slots, lookup, elem_type, nested, nullable, ancestors)?
+        }
+        FieldType::Option(_) | FieldType::Unknown => {
+            return Err(StructPathError::Arrow(format!(
+                "{field_type:?} cannot be converted to Arrow"
+            )))
+        }
+    };
+    Ok((array, nullable))
+}
+
+fn build_struct(
+    slots: &[Slot],
+    mut lookup: Lookup,
+    info: FieldsInfo,
+    nullable: bool,
+    ancestors: &mut Vec<String>,
+) -> Result<ArrayRef, StructPathError> {
+    if ancestors.contains(&info.name) {
+        return Err(StructPathError::Arrow(format!(
+            "recursive struct {} cannot be converted to Arrow, select its fields instead",
+            info.name
+        )));
+    }
+
+    // The structs are the slots of their fields
+    let child_slots = slots
+        .iter()
+        .map(|slot| match lookup.borrow(*slot)? {
+            Borrowed::Struct(value) => Ok((Some(value), None)),
+            Borrowed::None => Ok((None, None)),
+            Borrowed::List(_) | Borrowed::Value => Err(lookup.mismatch("StructPath")),
+        })
+        .collect::<Result<Vec<Slot>, _>>()?;
+    let nulls = nullable
+        .then(|| NullBuffer::from_iter(child_slots.iter().map(|(value, _)| value.is_some())));
+
+    ancestors.push(info.name.clone());
+    let mut fields = Vec::new();
+    let mut arrays = Vec::new();
+    for child in info
+        .fields
+        .iter()
+        .filter(|child| is_supported(&child.r#type))
+    {
+        let (array, child_nullable) = build_array(
+            &child_slots,
+            Some(PathComponent::Field(child.name.clone())),
+            &child.r#type,
+            child.nested,
+            nullable,
+            ancestors,
+        )?;
+        fields.push(Field::new(
+            &child.name,
+            array.data_type().clone(),
+            child_nullable,
+        ));
+        arrays.push(array);
+    }
+    ancestors.pop();
+
+    if fields.is_empty() {
+        return Ok(Arc::new(StructArray::new_empty_fields(slots.len(), nulls)));
+    }
+    let array = StructArray::try_new(fields.into(), arrays, nulls).map_err(arrow_error)?;
+    Ok(Arc::new(array))
+}
+
+fn build_list(
+    slots: &[Slot],
+    mut lookup: Lookup,
+    elem_type: &FieldType,
+    nested: Option<fn() -> FieldsInfo>,
+    nullable: bool,
+    ancestors: &mut Vec<String>,
+) -> Result<ArrayRef, StructPathError> {
+    // Elements are reached by indexing the field of the list in the struct
+    // holding it
+    let Some(PathComponent::Field(name)) = lookup.0.first() else {
+        return Err(StructPathError::Arrow(
+            "nested lists cannot be converted to Arrow".to_string(),
+        ));
+    };
+    let elem_component = PathComponent::ArrayIndex(name.clone(), 0);
+
+    let mut lengths = Vec::with_capacity(slots.len());
+    let mut validity = Vec::with_capacity(slots.len());
+    let mut elem_slots = Vec::new();
+    for slot in slots {
+        let (len, valid) = match lookup.borrow(*slot)? {
+            Borrowed::List(len) => (len, true),
+            Borrowed::None => (0, false),
+            Borrowed::Struct(_) | Borrowed::Value => return Err(lookup.mismatch("Vec")),
+        };
+        lengths.push(len);
+        validity.push(valid);
+        elem_slots.extend((0..len).map(|i| (slot.0, Some(i))));
+    }
+
+    let (values, elem_nullable) = build_array(
+        &elem_slots,
+        Some(elem_component),
+        elem_type,
+        nested,
+        false,
+        ancestors,
+    )?;
+    let item = Field::new_list_field(values.data_type().clone(), elem_nullable);
+    let nulls = nullable.then(|| NullBuffer::from(validity));
+    let array = ListArray::try_new(
+        Arc::new(item),
+        OffsetBuffer::from_lengths(lengths),
+        values,
+        nulls,
+    )
+    .map_err(arrow_error)?;
+    Ok(Arc::new(array))
+}
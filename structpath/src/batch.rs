@@ -0,0 +1,177 @@
+use crate::{
+    error::StructPathError,
+    path::{Path, PathComponent},
+    traits::StructPath,
+    value::Value,
+};
+
+/// Slots for the results of a batch extraction, indexed like the input paths
+pub type BatchResults = [Option<Result<Value, StructPathError>>];
+
+/// Prefix tree of paths, used to extract many paths in a single traversal
+///
+/// Each node is reached through one path component. `leaves` holds the index,
+/// in the input slice, of every path ending at the node.
+#[derive(Debug, Default)]
+pub struct PathTrie {
+    children: Vec<(PathComponent, PathTrie)>,
+    leaves: Vec<usize>,
+}
+
+impl PathTrie {
+    /// Build the trie of `paths`
+    pub fn new(paths: &[Path]) -> Self {
+        let mut root = Self::default();
+        for (i, path) in paths.iter().enumerate() {
+            let mut node = &mut root;
+            for component in &path.components {
+                node = node.child(component);
+            }
+            node.leaves.push(i);
+        }
+        root
+    }
+
+    fn child(&mut self, component: &PathComponent) -> &mut PathTrie {
+        let position = match self.children.iter().position(|(c, _)| c == component) {
+            Some(position) => position,
+            None => {
+                self.children.push((component.clone(), PathTrie::default()));
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[position].1
+    }
+
+    /// Child nodes, with the component leading to each of them
+    pub fn children(&self) -> &[(PathComponent, PathTrie)] {
+        &self.children
+    }
+
+    /// Indices of the paths ending at this node
+    pub fn leaves(&self) -> &[usize] {
+        &self.leaves
+    }
+
+    /// Indices of the paths ending at this node or below it
+    pub fn subtree_leaves(&self) -> Vec<usize> {
+        let mut leaves = self.leaves.clone();
+        for (_, child) in &self.children {
+            leaves.extend(child.subtree_leaves());
+        }
+        leaves
+    }
+
+    /// Set the result of every path ending at this node or below it
+    pub fn fill(
+        &self,
+        out: &mut BatchResults,
+        result: impl Fn() -> Result<Value, StructPathError>,
+    ) {
+        for i in self.subtree_leaves() {
+            out[i] = Some(result());
+        }
+    }
+}
+
+/// A set of paths prepared for repeated batch extraction
+///
+/// Building the prefix tree has a cost comparable to a few path lookups, so
+/// keep a `PathBatch` around when extracting the same paths from many records.
+#[derive(Debug)]
+pub struct PathBatch {
+    paths: Vec<Path>,
+    trie: PathTrie,
+}
+
+impl PathBatch {
+    pub fn new(paths: Vec<Path>) -> Self {
+        let trie = PathTrie::new(&paths);
+        Self { paths, trie }
+    }
+
+    pub fn paths(&self) -> &[Path] {
+        &self.paths
+    }
+
+    pub fn trie(&self) -> &PathTrie {
+        &self.trie
+    }
+}
+
+/// Store `result` for every path ending at `node`
+///
+/// The result is cloned only when several identical paths were requested.
+#[doc(hidden)]
+pub fn assign(
+    node: &PathTrie,
+    paths: &[Path],
+    depth: usize,
+    out: &mut BatchResults,
+    result: Result<Value, StructPathError>,
+) {
+    let Some((&last, others)) = node.leaves.split_last() else {
+        return;
+    };
+    for &i in others {
+        out[i] = Some(
+            result
+                .clone()
+                .map_err(|e| e.nested_at(&paths[i].components, depth)),
+        );
+    }
+    out[last] = Some(result.map_err(|e| e.nested_at(&paths[last].components, depth)));
+}
+
+/// Resolve every path below `node` independently on `value`
+///
+/// `node` is reached through the component at `depth` of its paths. Used as
+/// the fallback when a nested struct cannot be visited as a whole, so errors
+/// are the ones `get_value_by_components` reports.
+#[doc(hidden)]
+pub fn resolve_each<T: StructPath + ?Sized>(
+    value: &T,
+    node: &PathTrie,
+    paths: &[Path],
+    depth: usize,
+    out: &mut BatchResults,
+) {
+    for i in node.subtree_leaves() {
+        let components = &paths[i].components;
+        out[i] = Some(
+            value
+                .get_value_by_components(&components[depth..])
+                .map_err(|e| e.nested_at(components, depth)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_trie() {
+        let paths: Vec<Path> = [
+            "admin.name",
+            "name",
+            "admin.age",
+            "admin",
+            "members[0].name",
+        ]
+        .iter()
+        .map(|p| Path::from_str(p).unwrap())
+        .collect();
+        let trie = PathTrie::new(&paths);
+
+        assert!(trie.leaves().is_empty());
+        let children: Vec<String> = trie.children().iter().map(|(c, _)| c.to_string()).collect();
+        assert_eq!(children, vec!["admin", "name", "members[0]"]);
+
+        let (_, admin) = &trie.children()[0];
+        assert_eq!(admin.leaves(), &[3]);
+        assert_eq!(admin.children().len(), 2);
+        assert_eq!(admin.subtree_leaves(), vec![3, 0, 2]);
+        assert_eq!(trie.subtree_leaves().len(), paths.len());
+    }
+}
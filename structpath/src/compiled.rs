@@ -0,0 +1,95 @@
+use crate::{
+    error::StructPathError,
+    path::{Path, PathComponent},
+    traits::StructPath,
+    value::Value,
+};
+
+/// A path resolved against a type, ready to be evaluated
+pub type PathFn<T> = Box<dyn Fn(&T) -> Result<Value, StructPathError> + Send + Sync>;
+
+/// Trait for types whose paths can be resolved ahead of evaluation
+///
+/// Implemented by the `StructPath` derive. Field names are matched once, when
+/// compiling, and the returned closure only walks the struct.
+pub trait CompilePath: Sized + 'static {
+    fn compile_components(components: &[PathComponent]) -> Result<PathFn<Self>, StructPathError>;
+}
+
+/// A path compiled for a given type `T`
+///
+/// Use it instead of `get_value` when the same path is evaluated over many
+/// records: parsing and field name matching happen only once.
+///
+/// ```ignore
+/// let birth_year = CompiledPath::<Group>::from_str("members[0].pets[0].birth_year")?;
+/// for group in &groups {
+///     let value = birth_year.get_value(group)?;
+/// }
+/// ```
+pub struct CompiledPath<T> {
+    path: Path,
+    eval: PathFn<T>,
+}
+
+impl<T: CompilePath> CompiledPath<T> {
+    /// Compile a parsed path for `T`
+    pub fn new(path: Path) -> Result<Self, StructPathError> {
+        let eval = T::compile_components(&path.components)?;
+        Ok(Self { path, eval })
+    }
+
+    /// Parse and compile a path for `T`
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(path: &str) -> Result<Self, StructPathError> {
+        Self::new(Path::from_str(path)?)
+    }
+
+    /// The path this was compiled from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Evaluate the path on `value`
+    pub fn get_value(&self, value: &T) -> Result<Value, StructPathError> {
+        (self.eval)(value)
+    }
+}
+
+impl<T> std::fmt::Debug for CompiledPath<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompiledPath")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// Compile the `rest` of a path through a nested struct
+///
+/// `project` reaches the nested struct from the outer one, returning `None`
+/// when an optional nested struct is missing, and fails for an index out of
+/// bounds. Used by the derived `CompilePath` implementations.
+#[doc(hidden)]
+pub fn compile_nested<O, I, P>(
+    components: &[PathComponent],
+    project: P,
+) -> Result<PathFn<O>, StructPathError>
+where
+    O: 'static,
+    I: CompilePath,
+    P: for<'a> Fn(&'a O) -> Result<Option<&'a I>, StructPathError> + Send + Sync + 'static,
+{
+    let inner = I::compile_components(&components[1..]).map_err(|e| e.nested(components))?;
+    let components = components.to_vec();
+    Ok(Box::new(move |outer| match project(outer)? {
+        Some(nested) => inner(nested).map_err(|e| e.nested(&components)),
+        None => Ok(Value::Option(None)),
+    }))
+}
+
+/// Compile `components` to be resolved when evaluated, for types whose fields
+/// are only known at runtime
+pub(crate) fn compile_dynamic<T: StructPath + 'static>(components: &[PathComponent]) -> PathFn<T> {
+    let components = components.to_vec();
+    Box::new(move |record: &T| record.get_value_by_components(&components))
+}
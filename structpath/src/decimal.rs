@@ -0,0 +1,84 @@
+//! Decimals of the `rust_decimal` crate
+//!
+//! A `rust_decimal::Decimal` becomes a `Value::Decimal` of the same mantissa
+//! and scale, so no precision is lost either way.
+
+use crate::{
+    error::StructPathError,
+    leaf::path_leaf,
+    value::{FromValue, Value},
+};
+use rust_decimal::Decimal;
+
+path_leaf! {
+    Decimal => Decimal,
+}
+
+impl From<Decimal> for Value {
+    fn from(value: Decimal) -> Self {
+        Value::Decimal {
+            mantissa: value.mantissa(),
+            scale: value.scale(),
+        }
+    }
+}
+
+/// Also reads from `Integer`, `BigInt` and `BigUint`. Values beyond the 96
+/// bits of mantissa or 28 digits of scale of `Decimal` give a `TypeMismatch`.
+impl FromValue for Decimal {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        let value = value.flatten().ok_or(StructPathError::NullValue)?;
+        let (mantissa, scale) = match value {
+            Value::Integer(integer) => return Ok(integer.into()),
+            Value::BigInt(integer) => (Some(integer), 0),
+            Value::BigUint(integer) => (i128::try_from(integer).ok(), 0),
+            ref value => {
+                let (mantissa, scale) = value.try_as_decimal()?;
+                (Some(mantissa), scale)
+            }
+        };
+        mantissa
+            .and_then(|mantissa| Decimal::try_from_i128_with_scale(mantissa, scale).ok())
+            .ok_or_else(|| StructPathError::TypeMismatch {
+                expected: "rust_decimal::Decimal".to_string(),
+                actual: format!("{value:?}"),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_decimal() {
+        let price = Decimal::from_str("-12.50").unwrap();
+        let value = Value::from(price);
+        assert_eq!(
+            value,
+            Value::Decimal {
+                mantissa: -1250,
+                scale: 2
+            }
+        );
+        let read = Decimal::from_value(value.clone()).unwrap();
+        assert_eq!((read, read.scale()), (price, 2));
+        assert_eq!(
+            value.compare(&Value::from(Decimal::from_str("-12.5").unwrap())),
+            Some(Ordering::Equal)
+        );
+
+        assert_eq!(
+            Decimal::from_value(Value::BigInt(7)).unwrap(),
+            Decimal::from(7)
+        );
+        assert!(Decimal::from_value(Value::BigUint(u128::MAX)).is_err());
+        assert!(Decimal::from_value(Value::Decimal {
+            mantissa: 1,
+            scale: 29
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use crate::{error::StructPathError, path::PathComponent, traits::StructPath, value::Value};
+use std::borrow::Cow;
+
+/// A node of a dynamic document, such as a JSON value
+pub(crate) enum Node<'a, D> {
+    // TOML has no null
+    #[cfg_attr(
+        not(any(feature = "json", feature = "prost", feature = "yaml")),
+        allow(dead_code)
+    )]
+    Null,
+    Boolean(bool),
+    // Protobuf `Value`s only hold doubles
+    #[cfg_attr(
+        not(any(feature = "json", feature = "toml", feature = "yaml")),
+        allow(dead_code)
+    )]
+    Integer(i64),
+    /// Integers beyond `i64`, which JSON and YAML numbers can hold
+    #[cfg_attr(not(any(feature = "json", feature = "yaml")), allow(dead_code))]
+    Unsigned(u64),
+    Float(f64),
+    String(Cow<'a, str>),
+    Array(&'a [D]),
+    Object,
+}
+
+impl<D> Node<'_, D> {
+    /// Name of the node kind, as reported in `TypeMismatch` errors
+    fn kind(&self) -> &'static str {
+        match self {
+            Node::Null => "null",
+            Node::Boolean(_) => "boolean",
+            Node::Integer(_) | Node::Unsigned(_) => "integer",
+            Node::Float(_) => "float",
+            Node::String(_) => "string",
+            Node::Array(_) => "array",
+            Node::Object => "object",
+        }
+    }
+
+    fn mismatch(&self, expected: &str) -> StructPathError {
+        StructPathError::TypeMismatch {
+            expected: expected.to_string(),
+            actual: self.kind().to_string(),
+        }
+    }
+}
+
+/// Objects whose fields are documents of type `D`
+pub(crate) trait Object<D> {
+    /// The value at key `name`, if `self` is an object holding it
+    fn field(&self, name: &str) -> Option<&D>;
+
+    /// The keys of `self` if it is an object, in document order
+    fn keys(&self) -> Vec<&str>;
+}
+
+/// Dynamic documents navigated by path: object keys are fields and arrays are
+/// indexed with `ArrayIndex`
+///
+/// Paths start with a key, so a document whose root is an array is a list at
+/// the empty path, and its elements are navigated one by one.
+pub(crate) trait Document:
+    Object<Self> + StructPath + Clone + Send + Sync + 'static
+{
+    /// Name of the document type, as reported in `FieldNotFound` errors
+    const TYPE_NAME: &'static str;
+
+    fn node(&self) -> Node<'_, Self>;
+}
+
+/// Resolve `components` in `document`
+///
+/// Nulls met along the way give `Value::Option(None)`, as missing optional
+/// structs do.
+pub(crate) fn get_components<D: Document>(
+    document: &D,
+    components: &[PathComponent],
+) -> Result<Value, StructPathError> {
+    get_components_at(document, components, 0)
+}
+
+/// Resolve the components of `components` from `depth` on in `document`
+fn get_components_at<D: Document>(
+    document: &D,
+    components: &[PathComponent],
+    depth: usize,
+) -> Result<Value, StructPathError> {
+    if depth == components.len() {
+        return Ok(to_value(document));
+    }
+    match document.node() {
+        Node::Null => Ok(Value::Option(None)),
+        Node::Object => get_field_components(document, components, depth),
+        node => Err(node.mismatch("object")),
+    }
+}
+
+/// Resolve the components of `components` from `depth` on in the fields of
+/// `object`, for objects that are not documents themselves
+pub(crate) fn get_field_components<D: Document>(
+    object: &(impl Object<D> + ?Sized),
+    components: &[PathComponent],
+    depth: usize,
+) -> Result<Value, StructPathError> {
+    let (field, index) = match &components[depth] {
+        PathComponent::Field(field) => (field, None),
+        PathComponent::ArrayIndex(field, index) => (field, Some(*index)),
+    };
+    let child = object.field(field).ok_or_else(|| {
+        StructPathError::field_not_found(
+            field.clone(),
+            &components[depth..],
+            D::TYPE_NAME,
+            &object.keys(),
+        )
+        .nested_at(components, depth)
+    })?;
+    let child = match index {
+        None => child,
+        Some(index) => match child.node() {
+            Node::Null => return Ok(Value::Option(None)),
+            Node::Array(items) => items.get(index).ok_or_else(|| {
+                StructPathError::index_out_of_bounds(
+                    field.clone(),
+                    index,
+                    items.len(),
+                    &components[depth..],
+                    D::TYPE_NAME,
+                )
+                .nested_at(components, depth)
+            })?,
+            node => return Err(node.mismatch("array")),
+        },
+    };
+    get_components_at(child, components, depth + 1)
+}
+
+/// Convert a node: arrays become lists and objects stay boxed documents
+pub(crate) fn to_value<D: Document>(document: &D) -> Value {
+    match document.node() {
+        Node::Null => Value::Option(None),
+        Node::Boolean(value) => Value::Boolean(value),
+        Node::Integer(value) => Value::Integer(value),
+        Node::Unsigned(value) => Value::BigUint(value.into()),
+        Node::Float(value) => Value::Float(value),
+        Node::String(value) => Value::String(value.into_owned()),
+        Node::Array(items) => Value::list(items.to_vec(), to_value::<D>),
+        Node::Object => Value::from_struct(document.clone()),
+    }
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use super::*;
+
+    impl Document for serde_json::Value {
+        const TYPE_NAME: &'static str = "JSON object";
+
+        fn node(&self) -> Node<'_, Self> {
+            match self {
+                serde_json::Value::Null => Node::Null,
+                serde_json::Value::Bool(value) => Node::Boolean(*value),
+                serde_json::Value::Number(number) => match number.as_i64() {
+                    Some(value) => Node::Integer(value),
+                    None => match number.as_u64() {
+                        Some(value) => Node::Unsigned(value),
+                        None => Node::Float(number.as_f64().unwrap_or(f64::NAN)),
+                    },
+                },
+                serde_json::Value::String(value) => Node::String(Cow::Borrowed(value)),
+                serde_json::Value::Array(items) => Node::Array(items),
+                serde_json::Value::Object(_) => Node::Object,
+            }
+        }
+    }
+
+    impl Object<Self> for serde_json::Value {
+        fn field(&self, name: &str) -> Option<&Self> {
+            self.as_object()?.get(name)
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            match self {
+                serde_json::Value::Object(map) => map.keys().map(String::as_str).collect(),
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    impl StructPath for serde_json::Value {
+        fn get_value_by_components(
+            &self,
+            components: &[PathComponent],
+        ) -> Result<Value, StructPathError> {
+            get_components(self, components)
+        }
+
+        fn value_fields(&self) -> Vec<String> {
+            self.keys().into_iter().map(str::to_string).collect()
+        }
+    }
+}
+
+#[cfg(feature = "toml")]
+mod toml {
+    use super::*;
+
+    impl Document for ::toml::Value {
+        const TYPE_NAME: &'static str = "TOML table";
+
+        fn node(&self) -> Node<'_, Self> {
+            match self {
+                ::toml::Value::Boolean(value) => Node::Boolean(*value),
+                ::toml::Value::Integer(value) => Node::Integer(*value),
+                ::toml::Value::Float(value) => Node::Float(*value),
+                ::toml::Value::String(value) => Node::String(Cow::Borrowed(value)),
+                ::toml::Value::Datetime(value) => Node::String(Cow::Owned(value.to_string())),
+                ::toml::Value::Array(items) => Node::Array(items),
+                ::toml::Value::Table(_) => Node::Object,
+            }
+        }
+    }
+
+    impl Object<Self> for ::toml::Value {
+        fn field(&self, name: &str) -> Option<&Self> {
+            self.as_table()?.get(name)
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            match self {
+                ::toml::Value::Table(table) => table.keys().map(String::as_str).collect(),
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    impl StructPath for ::toml::Value {
+        fn get_value_by_components(
+            &self,
+            components: &[PathComponent],
+        ) -> Result<Value, StructPathError> {
+            get_components(self, components)
+        }
+
+        fn value_fields(&self) -> Vec<String> {
+            self.keys().into_iter().map(str::to_string).collect()
+        }
+    }
+}
+
+#[cfg(feature = "yaml")]
+mod yaml {
+    use super::*;
+
+    /// Tags are ignored: a tagged value is navigated as the value it tags
+    impl Document for serde_yaml::Value {
+        const TYPE_NAME: &'static str = "YAML mapping";
+
+        fn node(&self) -> Node<'_, Self> {
+            match self {
+                serde_yaml::Value::Null => Node::Null,
+                serde_yaml::Value::Bool(value) => Node::Boolean(*value),
+                serde_yaml::Value::Number(number) => match number.as_i64() {
+                    Some(value) => Node::Integer(value),
+                    None => match number.as_u64() {
+                        Some(value) => Node::Unsigned(value),
+                        None => Node::Float(number.as_f64().unwrap_or(f64::NAN)),
+                    },
+                },
+                serde_yaml::Value::String(value) => Node::String(Cow::Borrowed(value)),
+                serde_yaml::Value::Sequence(items) => Node::Array(items),
+                serde_yaml::Value::Mapping(_) => Node::Object,
+                serde_yaml::Value::Tagged(tagged) => tagged.value.node(),
+            }
+        }
+    }
+
+    impl Object<Self> for serde_yaml::Value {
+        fn field(&self, name: &str) -> Option<&Self> {
+            match self {
+                serde_yaml::Value::Tagged(tagged) => tagged.value.field(name),
+                value => value.as_mapping()?.get(name),
+            }
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            match self {
+                serde_yaml::Value::Mapping(mapping) => mapping
+                    .keys()
+                    .filter_map(serde_yaml::Value::as_str)
+                    .collect(),
+                serde_yaml::Value::Tagged(tagged) => tagged.value.keys(),
+                _ => Vec::new(),
+            }
+        }
+    }
+
+    impl StructPath for serde_yaml::Value {
+        fn get_value_by_components(
+            &self,
+            components: &[PathComponent],
+        ) -> Result<Value, StructPathError> {
+            get_components(self, components)
+        }
+
+        fn value_fields(&self) -> Vec<String> {
+            self.keys().into_iter().map(str::to_string).collect()
+        }
+    }
+}
@@ -1,22 +1,297 @@
+use crate::path::{components_to_string, PathComponent, PathParseError, PathParseErrorKind};
 use thiserror::Error;
 
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum StructPathError {
     #[error("Invalid path: {0}")]
-    InvalidPath(String),
+    InvalidPath(#[from] PathParseError),
 
-    #[error("Field not found: {0}")]
-    FieldNotFound(String),
+    #[error(
+        "Field not found: {field} in {type_name} at component {index} of `{path}`{}",
+        suggestion_hint(field, valid_fields)
+    )]
+    FieldNotFound {
+        /// The field name that could not be resolved
+        field: String,
+        /// The full path being resolved
+        path: String,
+        /// Index of the failing component within `path`
+        index: usize,
+        /// Name of the type being traversed when the lookup failed
+        type_name: String,
+        /// Fields of `type_name` that accept this kind of access
+        valid_fields: Vec<String>,
+    },
 
     #[error("Type mismatch: expected {expected}, got {actual}")]
     TypeMismatch { expected: String, actual: String },
 
-    #[error("Vector index out of bounds: {0}")]
-    IndexOutOfBounds(usize),
+    #[error(
+        "Index {index} out of bounds for {field} of length {len} in {type_name} at component {component} of `{path}`"
+    )]
+    IndexOutOfBounds {
+        /// The list field being indexed
+        field: String,
+        /// The requested index
+        index: usize,
+        /// Length of the list
+        len: usize,
+        /// The full path being resolved
+        path: String,
+        /// Index of the failing component within `path`
+        component: usize,
+        /// Name of the type holding the list
+        type_name: String,
+    },
 
     #[error("Cannot access field of null value")]
     NullValue,
 
     #[error("Functionality not yet implemented")]
     NotImplemented,
+
+    #[error("No value at `{0}`")]
+    MissingPath(String),
+
+    #[error("Invalid value at `{path}`: {source}")]
+    InvalidValue {
+        path: String,
+        source: Box<StructPathError>,
+    },
+
+    #[cfg(feature = "arrow")]
+    #[error("Arrow error: {0}")]
+    Arrow(String),
+
+    #[cfg(feature = "polars")]
+    #[error("Polars error: {0}")]
+    Polars(String),
+
+    #[cfg(feature = "reflect")]
+    #[error("Descriptor error: {0}")]
+    Descriptor(String),
+
+    #[cfg(feature = "serde")]
+    #[error("Serialization error: {0}")]
+    Serialize(String),
+
+    #[cfg(feature = "prost")]
+    #[error("Cannot decode row {row}: {message}")]
+    Decode { row: usize, message: String },
+}
+
+impl StructPathError {
+    /// Build the error for an empty list of components, which names no field
+    ///
+    /// Derived structs report it rather than cloning themselves, so types
+    /// that are not `Clone` can derive `StructPath`.
+    pub fn empty_path() -> Self {
+        StructPathError::InvalidPath(PathParseError::new(PathParseErrorKind::EmptyPath, "", 0..0))
+    }
+
+    /// Build a `FieldNotFound` error for the first of `components`
+    pub fn field_not_found(
+        field: impl Into<String>,
+        components: &[PathComponent],
+        type_name: &str,
+        valid_fields: &[&str],
+    ) -> Self {
+        StructPathError::FieldNotFound {
+            field: field.into(),
+            path: components_to_string(components),
+            index: 0,
+            type_name: type_name.to_string(),
+            valid_fields: valid_fields.iter().map(|f| f.to_string()).collect(),
+        }
+    }
+
+    /// Build an `IndexOutOfBounds` error for the first of `components`
+    pub fn index_out_of_bounds(
+        field: impl Into<String>,
+        index: usize,
+        len: usize,
+        components: &[PathComponent],
+        type_name: &str,
+    ) -> Self {
+        StructPathError::IndexOutOfBounds {
+            field: field.into(),
+            index,
+            len,
+            path: components_to_string(components),
+            component: 0,
+            type_name: type_name.to_string(),
+        }
+    }
+
+    /// For `FieldNotFound`, the valid field closest to the requested one by
+    /// edit distance, if any is close enough
+    pub fn suggestion(&self) -> Option<&str> {
+        match self {
+            StructPathError::FieldNotFound {
+                field,
+                valid_fields,
+                ..
+            } => closest_match(field, valid_fields),
+            _ => None,
+        }
+    }
+
+    /// Re-anchor an error coming from a nested struct onto the enclosing path
+    ///
+    /// Called by the derived `get_value_by_path` when propagating errors up one
+    /// nesting level, so the reported path and component index are relative
+    /// to the outermost path.
+    #[doc(hidden)]
+    pub fn nested(self, parent: &[PathComponent]) -> Self {
+        self.nested_at(parent, 1)
+    }
+
+    /// Re-anchor an error raised `depth` components into `full_path`
+    #[doc(hidden)]
+    pub fn nested_at(self, full_path: &[PathComponent], depth: usize) -> Self {
+        match self {
+            StructPathError::FieldNotFound {
+                field,
+                index,
+                type_name,
+                valid_fields,
+                ..
+            } => StructPathError::FieldNotFound {
+                field,
+                path: components_to_string(full_path),
+                index: index + depth,
+                type_name,
+                valid_fields,
+            },
+            StructPathError::IndexOutOfBounds {
+                field,
+                index,
+                len,
+                component,
+                type_name,
+                ..
+            } => StructPathError::IndexOutOfBounds {
+                field,
+                index,
+                len,
+                path: components_to_string(full_path),
+                component: component + depth,
+                type_name,
+            },
+            other => other,
+        }
+    }
+}
+
+fn suggestion_hint(field: &str, valid_fields: &[String]) -> String {
+    match closest_match(field, valid_fields) {
+        Some(suggestion) => format!(", did you mean `{suggestion}`?"),
+        None => String::new(),
+    }
+}
+
+/// Find the candidate closest to `target`, if it is within a reasonable distance
+fn closest_match<'a, S: AsRef<str>>(target: &str, candidates: &'a [S]) -> Option<&'a str> {
+    let max_distance = (target.chars().count() / 3).max(2);
+    candidates
+        .iter()
+        .map(|candidate| {
+            let candidate = candidate.as_ref();
+            (edit_distance(target, candidate), candidate)
+        })
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::{Path, PathParseErrorKind};
+    use std::error::Error;
+
+    #[test]
+    fn test_invalid_path_source() {
+        let err: StructPathError = Path::from_str("pets[").unwrap_err().into();
+        assert_eq!(
+            err.to_string(),
+            "Invalid path: Unclosed array bracket at position 4"
+        );
+
+        let source = err
+            .source()
+            .and_then(|e| e.downcast_ref::<PathParseError>())
+            .unwrap();
+        assert_eq!(source.kind(), &PathParseErrorKind::UnclosedBracket);
+        assert_eq!(source.position(), 4);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("name", "name"), 0);
+        assert_eq!(edit_distance("nmae", "name"), 2);
+        assert_eq!(edit_distance("pet", "pets"), 1);
+        assert_eq!(edit_distance("", "age"), 3);
+    }
+
+    #[test]
+    fn test_field_not_found() {
+        let path = Path::from_str("nmae").unwrap();
+        let err = StructPathError::field_not_found(
+            "nmae",
+            &path.components,
+            "Pet",
+            &["name", "birth_year"],
+        );
+        assert_eq!(
+            err.to_string(),
+            "Field not found: nmae in Pet at component 0 of `nmae`, did you mean `name`?"
+        );
+
+        let parent = Path::from_str("members[0].pets[0].nmae").unwrap();
+        let err = err.nested(&parent.components).nested(&parent.components);
+        assert_eq!(err.suggestion(), Some("name"));
+        match err {
+            StructPathError::FieldNotFound {
+                path,
+                index,
+                valid_fields,
+                ..
+            } => {
+                assert_eq!(path, "members[0].pets[0].nmae");
+                assert_eq!(index, 2);
+                assert_eq!(valid_fields, vec!["name", "birth_year"]);
+            }
+            _ => panic!("Expected FieldNotFound"),
+        }
+
+        let err = StructPathError::field_not_found(
+            "xyz",
+            &path.components,
+            "Pet",
+            &["name", "birth_year"],
+        );
+        assert_eq!(
+            err.to_string(),
+            "Field not found: xyz in Pet at component 0 of `nmae`"
+        );
+    }
 }
@@ -0,0 +1,220 @@
+use crate::{
+    error::StructPathError,
+    path::{components_to_string, PathComponent},
+    value::{FromValue, Value},
+};
+use std::collections::HashMap;
+
+/// A source of values indexed by path, such as a row of a `DataFrame` whose
+/// columns are named after paths
+pub trait PathSource {
+    /// The value at `path`, `MissingPath` if the source has no such path
+    ///
+    /// Nulls are given as `Value::Option(None)`.
+    fn value_at(&self, path: &[PathComponent]) -> Result<Value, StructPathError>;
+
+    /// Whether there is a non-null value at `path` or below it
+    fn is_present(&self, path: &[PathComponent]) -> bool;
+
+    /// Number of elements of the list at `path`, one past the largest index
+    /// of the source below it
+    ///
+    /// Elements before it read as null when the source has no value for
+    /// them.
+    fn list_len(&self, path: &[PathComponent]) -> usize;
+}
+
+/// Trait for structs that can be built back from the values at their paths
+///
+/// The `FromPaths` derive reads each field at its path below `prefix`:
+///
+/// - strings, integers, floats and booleans are converted with [`FromValue`],
+///   and optional ones are `None` when null or missing
+/// - nested structs are read below their field, and optional ones are `None`
+///   when no value is present below them
+/// - `Vec`s are read at the indices `0`, `1`, ... up to the length given by
+///   the source
+/// - fields of other types implementing [`PathLeaf`](crate::PathLeaf) are
+///   converted with [`FromValue`], which they must implement
+/// - fields of types not implementing it, and `Option`s and `Vec`s of them,
+///   are set to their `Default`
+pub trait FromPaths: Sized {
+    fn from_paths(
+        source: &dyn PathSource,
+        prefix: &[PathComponent],
+    ) -> Result<Self, StructPathError>;
+}
+
+/// Values keyed by path string, as in `"members[0].name"`
+impl PathSource for HashMap<String, Value> {
+    fn value_at(&self, path: &[PathComponent]) -> Result<Value, StructPathError> {
+        let path = components_to_string(path);
+        match self.get(&path) {
+            Some(value) => Ok(value.clone()),
+            None => Err(StructPathError::MissingPath(path)),
+        }
+    }
+
+    fn is_present(&self, path: &[PathComponent]) -> bool {
+        let prefix = components_to_string(path);
+        self.iter().any(|(key, value)| {
+            let below = match key.strip_prefix(&prefix) {
+                Some(rest) => rest.is_empty() || rest.starts_with(['.', '[']),
+                None => false,
+            };
+            below && !matches!(value, Value::Option(None))
+        })
+    }
+
+    /// Keys with null values count, as in `"tags[2]"` for a list of three
+    /// options whose last one is `None`
+    fn list_len(&self, path: &[PathComponent]) -> usize {
+        let prefix = format!("{}[", components_to_string(path));
+        self.keys()
+            .filter_map(|key| {
+                let (index, _) = key.strip_prefix(&prefix)?.split_once(']')?;
+                index.parse::<usize>().ok()
+            })
+            .map(|index| index + 1)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// The path of field `name` below `prefix`
+#[doc(hidden)]
+pub fn child_path(prefix: &[PathComponent], name: &str) -> Vec<PathComponent> {
+    let mut path = prefix.to_vec();
+    path.push(PathComponent::Field(name.to_string()));
+    path
+}
+
+/// Read the value at `path` as `T`
+///
+/// When `optional`, a missing path reads as null. Used by the derived
+/// `FromPaths` implementations.
+#[doc(hidden)]
+pub fn read_value<T: FromValue>(
+    source: &dyn PathSource,
+    path: &[PathComponent],
+    optional: bool,
+) -> Result<T, StructPathError> {
+    let value = match source.value_at(path) {
+        Err(StructPathError::MissingPath(_)) if optional => Ok(Value::Option(None)),
+        value => value,
+    };
+    value.and_then(T::from_value).map_err(|e| match e {
+        StructPathError::MissingPath(_) => e,
+        e => StructPathError::InvalidValue {
+            path: components_to_string(path),
+            source: Box::new(e),
+        },
+    })
+}
+
+/// Read the elements of the list at `path` with `read`, up to the length
+/// given by the source
+///
+/// Used by the derived `FromPaths` implementations.
+#[doc(hidden)]
+pub fn read_list<T, F>(
+    source: &dyn PathSource,
+    path: &[PathComponent],
+    read: F,
+) -> Result<Vec<T>, StructPathError>
+where
+    F: Fn(&[PathComponent]) -> Result<T, StructPathError>,
+{
+    let Some((PathComponent::Field(name), parent)) = path.split_last() else {
+        return Err(StructPathError::MissingPath(components_to_string(path)));
+    };
+    let mut element = parent.to_vec();
+    element.push(PathComponent::ArrayIndex(name.clone(), 0));
+    (0..source.list_len(path))
+        .map(|index| {
+            element[parent.len()] = PathComponent::ArrayIndex(name.clone(), index);
+            read(&element)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::Path;
+
+    fn source() -> HashMap<String, Value> {
+        HashMap::from([
+            ("name".to_string(), Value::String("John".to_string())),
+            ("age".to_string(), Value::Option(None)),
+            ("pets[0].name".to_string(), Value::String("Max".to_string())),
+            (
+                "pets[1].name".to_string(),
+                Value::String("Buddy".to_string()),
+            ),
+        ])
+    }
+
+    fn components(path: &str) -> Vec<PathComponent> {
+        Path::from_str(path).unwrap().components
+    }
+
+    #[test]
+    fn test_hash_map_source() {
+        let source = source();
+        assert!(source.is_present(&components("pets")));
+        assert!(source.is_present(&components("pets[1]")));
+        assert!(!source.is_present(&components("pet")));
+        assert!(!source.is_present(&components("age")));
+        assert!(matches!(
+            source.value_at(&components("nmae")),
+            Err(StructPathError::MissingPath(path)) if path == "nmae"
+        ));
+    }
+
+    #[test]
+    fn test_read_value() {
+        let source = source();
+        let name: String = read_value(&source, &components("name"), false).unwrap();
+        assert_eq!(name, "John");
+        let age: Option<i64> = read_value(&source, &components("age"), true).unwrap();
+        assert_eq!(age, None);
+        let email: Option<String> = read_value(&source, &components("email"), true).unwrap();
+        assert_eq!(email, None);
+
+        let err = read_value::<i64>(&source, &components("age"), false).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid value at `age`: Cannot access field of null value"
+        );
+        let err = read_value::<i64>(&source, &components("name"), false).unwrap_err();
+        assert!(matches!(err, StructPathError::InvalidValue { .. }));
+    }
+
+    #[test]
+    fn test_read_list() {
+        let source = source();
+        let names = read_list(&source, &components("pets"), |element| {
+            read_value::<String>(&source, &child_path(element, "name"), false)
+        })
+        .unwrap();
+        assert_eq!(names, vec!["Max", "Buddy"]);
+
+        // Elements without values are null, up to the largest index
+        let source = HashMap::from([
+            ("tags[0]".to_string(), Value::String("a".to_string())),
+            ("tags[2]".to_string(), Value::String("c".to_string())),
+            ("tags[3]".to_string(), Value::Option(None)),
+        ]);
+        assert_eq!(source.list_len(&components("tags")), 4);
+        assert_eq!(source.list_len(&components("tag")), 0);
+        let tags = read_list(&source, &components("tags"), |element| {
+            read_value::<Option<String>>(&source, element, true)
+        })
+        .unwrap();
+        assert_eq!(
+            tags,
+            vec![Some("a".to_string()), None, Some("c".to_string()), None]
+        );
+    }
+}
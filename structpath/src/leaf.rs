@@ -0,0 +1,433 @@
+//! Leaf types: the types of fields that paths end on, declaring how they are
+//! represented as a `Value`
+
+use crate::{
+    error::StructPathError,
+    from_paths::{read_value, PathSource},
+    path::PathComponent,
+    value::{FromValue, Value},
+};
+use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use structpath_types::FieldType;
+
+/// Trait for the types of fields holding a single value, declaring their
+/// [`FieldType`] and converting themselves to a [`Value`]
+///
+/// The derives dispatch through this trait for the fields of types they do
+/// not know, falling back to boxing the field for types not implementing it.
+/// So any type can be extracted, including into Arrow and Polars columns,
+/// without changes to the derives. Implement [`FromValue`] too for
+/// `FromPaths` to read the field back.
+///
+/// The value must be of the declared type: a `FieldType::String` leaf
+/// converts to a `Value::String`. Types of other crates can be wrapped in a
+/// newtype.
+///
+/// ```ignore
+/// struct Email(String);
+///
+/// impl PathLeaf for Email {
+///     fn field_type() -> FieldType {
+///         FieldType::String
+///     }
+///
+///     fn to_value(&self) -> Value {
+///         Value::String(self.0.clone())
+///     }
+/// }
+/// ```
+pub trait PathLeaf {
+    /// The type reported by [`StructInfo`](crate::StructInfo) for fields of
+    /// this type
+    fn field_type() -> FieldType;
+
+    fn to_value(&self) -> Value;
+}
+
+/// Leaves converting with their `From` implementation of `Value`
+macro_rules! path_leaf {
+    ($($ty:ty => $field_type:ident),* $(,)?) => {
+        $(
+            impl $crate::PathLeaf for $ty {
+                fn field_type() -> ::structpath_types::FieldType {
+                    ::structpath_types::FieldType::$field_type
+                }
+
+                fn to_value(&self) -> $crate::Value {
+                    $crate::Value::from(self.clone())
+                }
+            }
+        )*
+    };
+}
+#[cfg_attr(
+    not(any(
+        feature = "chrono",
+        feature = "decimal",
+        feature = "prost",
+        feature = "time"
+    )),
+    allow(unused_imports)
+)]
+pub(crate) use path_leaf;
+
+path_leaf! {
+    String => String,
+    i64 => Integer,
+    f64 => Float,
+    bool => Boolean,
+    i8 => Integer,
+    i16 => Integer,
+    i32 => Integer,
+    u8 => Integer,
+    u16 => Integer,
+    u32 => Integer,
+    u64 => Integer,
+    f32 => Float,
+    i128 => BigInt,
+    u128 => BigInt,
+    std::time::SystemTime => DateTime,
+    std::time::Duration => Duration,
+}
+
+/// Addresses are strings, as in `192.168.0.1` or `::1`
+impl PathLeaf for IpAddr {
+    fn field_type() -> FieldType {
+        FieldType::String
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl PathLeaf for Ipv4Addr {
+    fn field_type() -> FieldType {
+        FieldType::String
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl PathLeaf for Ipv6Addr {
+    fn field_type() -> FieldType {
+        FieldType::String
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+/// Parse a string value as `T`, whose name is reported in errors
+fn parse<T: std::str::FromStr>(value: Value, target: &str) -> Result<T, StructPathError> {
+    let value = value.flatten().ok_or(StructPathError::NullValue)?;
+    value
+        .try_as_str()?
+        .parse()
+        .map_err(|_| StructPathError::TypeMismatch {
+            expected: target.to_string(),
+            actual: format!("{value:?}"),
+        })
+}
+
+impl FromValue for IpAddr {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        parse(value, "IpAddr")
+    }
+}
+
+impl FromValue for Ipv4Addr {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        parse(value, "Ipv4Addr")
+    }
+}
+
+impl FromValue for Ipv6Addr {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        parse(value, "Ipv6Addr")
+    }
+}
+
+/// UUIDs are strings in their hyphenated form, and read from 16 bytes too
+#[cfg(feature = "uuid")]
+impl PathLeaf for uuid::Uuid {
+    fn field_type() -> FieldType {
+        FieldType::String
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.hyphenated().to_string())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl FromValue for uuid::Uuid {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        match value.flatten().ok_or(StructPathError::NullValue)? {
+            Value::Bytes(bytes) => {
+                uuid::Uuid::from_slice(&bytes).map_err(|_| StructPathError::TypeMismatch {
+                    expected: "16 bytes".to_string(),
+                    actual: format!("{} bytes", bytes.len()),
+                })
+            }
+            value => parse(value, "Uuid"),
+        }
+    }
+}
+
+/// Dispatch of the derives between [`PathLeaf`] and boxing, for the fields of
+/// type `T`
+///
+/// Methods called on a `&Leaf<T>` resolve to [`LeafDispatch`] when `T`
+/// implements `PathLeaf`, and otherwise, through auto-referencing, to
+/// [`BoxedDispatch`].
+#[doc(hidden)]
+pub struct Leaf<T>(PhantomData<fn() -> T>);
+
+impl<T> Leaf<T> {
+    pub fn new() -> Self {
+        Leaf(PhantomData)
+    }
+
+    /// The dispatch for the type of `value`
+    pub fn of(_value: &T) -> Self {
+        Leaf::new()
+    }
+}
+
+impl<T> Default for Leaf<T> {
+    fn default() -> Self {
+        Leaf::new()
+    }
+}
+
+#[doc(hidden)]
+pub trait LeafDispatch<T> {
+    fn is_leaf(&self) -> bool;
+    fn field_type(&self) -> FieldType;
+    fn to_value(&self, value: &T) -> Value;
+    fn read_value(
+        &self,
+        source: &dyn PathSource,
+        path: &[PathComponent],
+    ) -> Result<T, StructPathError>
+    where
+        T: FromValue;
+    fn read_option(
+        &self,
+        source: &dyn PathSource,
+        path: &[PathComponent],
+    ) -> Result<Option<T>, StructPathError>
+    where
+        T: FromValue;
+    fn read_or_default(
+        &self,
+        source: &dyn PathSource,
+        path: &[PathComponent],
+    ) -> Result<T, StructPathError>
+    where
+        T: FromValue;
+}
+
+impl<T: PathLeaf> LeafDispatch<T> for Leaf<T> {
+    fn is_leaf(&self) -> bool {
+        true
+    }
+
+    fn field_type(&self) -> FieldType {
+        T::field_type()
+    }
+
+    fn to_value(&self, value: &T) -> Value {
+        value.to_value()
+    }
+
+    fn read_value(
+        &self,
+        source: &dyn PathSource,
+        path: &[PathComponent],
+    ) -> Result<T, StructPathError>
+    where
+        T: FromValue,
+    {
+        read_value(source, path, false)
+    }
+
+    fn read_option(
+        &self,
+        source: &dyn PathSource,
+        path: &[PathComponent],
+    ) -> Result<Option<T>, StructPathError>
+    where
+        T: FromValue,
+    {
+        read_value(source, path, true)
+    }
+
+    fn read_or_default(
+        &self,
+        source: &dyn PathSource,
+        path: &[PathComponent],
+    ) -> Result<T, StructPathError>
+    where
+        T: FromValue,
+    {
+        read_value(source, path, false)
+    }
+}
+
+/// Fields of other types are boxed, and `FromPaths` sets them to their
+/// `Default`
+#[doc(hidden)]
+pub trait BoxedDispatch<T> {
+    fn is_leaf(&self) -> bool;
+    fn field_type(&self) -> FieldType;
+    fn to_value(&self, value: &T) -> Value
+    where
+        T: Clone + Send + Sync + 'static;
+    fn read_value(
+        &self,
+        source: &dyn PathSource,
+        path: &[PathComponent],
+    ) -> Result<T, StructPathError>;
+    fn read_option(
+        &self,
+        source: &dyn PathSource,
+        path: &[PathComponent],
+    ) -> Result<Option<T>, StructPathError>;
+    fn read_or_default(
+        &self,
+        source: &dyn PathSource,
+        path: &[PathComponent],
+    ) -> Result<T, StructPathError>
+    where
+        T: Default;
+}
+
+impl<T> BoxedDispatch<T> for &Leaf<T> {
+    fn is_leaf(&self) -> bool {
+        false
+    }
+
+    fn field_type(&self) -> FieldType {
+        FieldType::Unknown
+    }
+
+    fn to_value(&self, value: &T) -> Value
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        Value::Boxed(Box::new(value.clone()))
+    }
+
+    /// Only called below a check of [`is_leaf`](Self::is_leaf)
+    fn read_value(
+        &self,
+        _source: &dyn PathSource,
+        _path: &[PathComponent],
+    ) -> Result<T, StructPathError> {
+        Err(StructPathError::TypeMismatch {
+            expected: "PathLeaf".to_string(),
+            actual: std::any::type_name::<T>().to_string(),
+        })
+    }
+
+    fn read_option(
+        &self,
+        _source: &dyn PathSource,
+        _path: &[PathComponent],
+    ) -> Result<Option<T>, StructPathError> {
+        Ok(None)
+    }
+
+    fn read_or_default(
+        &self,
+        _source: &dyn PathSource,
+        _path: &[PathComponent],
+    ) -> Result<T, StructPathError>
+    where
+        T: Default,
+    {
+        Ok(T::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Opaque;
+
+    // Calls go through a reference, as in the derives, for the dispatch to
+    // fall back to boxing
+    #[allow(clippy::needless_borrow)]
+    #[test]
+    fn test_dispatch() {
+        assert!((&Leaf::<Ipv4Addr>::new()).is_leaf());
+        assert_eq!(
+            (&Leaf::<std::time::SystemTime>::new()).field_type(),
+            FieldType::DateTime
+        );
+        let address = IpAddr::from([10, 0, 0, 1]);
+        assert_eq!(
+            (&Leaf::of(&address)).to_value(&address),
+            Value::String("10.0.0.1".to_string())
+        );
+        assert_eq!(IpAddr::from_value(address.to_value()).unwrap(), address);
+        assert!(Ipv6Addr::from_value(Value::String("10.0.0.1".to_string())).is_err());
+
+        assert!(!(&Leaf::<Opaque>::new()).is_leaf());
+        assert_eq!((&Leaf::<Opaque>::new()).field_type(), FieldType::Unknown);
+        assert!(matches!(
+            (&Leaf::of(&Opaque)).to_value(&Opaque),
+            Value::Boxed(_)
+        ));
+    }
+
+    #[test]
+    fn test_numeric_leaves() {
+        assert_eq!(i32::field_type(), FieldType::Integer);
+        assert_eq!(u32::field_type(), FieldType::Integer);
+        assert_eq!(u64::field_type(), FieldType::Integer);
+        assert_eq!(f32::field_type(), FieldType::Float);
+
+        assert_eq!((-7i32).to_value(), Value::Integer(-7));
+        assert_eq!(u32::MAX.to_value(), Value::Integer(u32::MAX.into()));
+        assert_eq!(7u64.to_value(), Value::Integer(7));
+        assert_eq!(u64::MAX.to_value(), Value::BigUint(u64::MAX.into()));
+        assert_eq!(5u128.to_value(), Value::BigInt(5));
+        assert_eq!(u128::MAX.to_value(), Value::BigUint(u128::MAX));
+        assert_eq!(1.5f32.to_value(), Value::Float(1.5));
+
+        assert_eq!(i32::from_value(Value::Integer(-7)).unwrap(), -7);
+        assert_eq!(u8::from_value(Value::Integer(255)).unwrap(), 255);
+        assert_eq!(u64::from_value(u64::MAX.to_value()).unwrap(), u64::MAX);
+        assert_eq!(u64::from_value(Value::Integer(3)).unwrap(), 3);
+        assert_eq!(f32::from_value(Value::Integer(2)).unwrap(), 2.0);
+
+        assert!(i32::from_value(Value::Integer(i64::MAX)).is_err());
+        assert!(u32::from_value(Value::Integer(-1)).is_err());
+        assert!(u64::from_value(Value::BigUint(u128::MAX)).is_err());
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_uuid() {
+        let id = uuid::Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+        let value = id.to_value();
+        assert_eq!(
+            value,
+            Value::String("67e55044-10b1-426f-9247-bb680e5fe0c8".to_string())
+        );
+        assert_eq!(uuid::Uuid::from_value(value).unwrap(), id);
+        assert_eq!(
+            uuid::Uuid::from_value(Value::Bytes(id.as_bytes().to_vec())).unwrap(),
+            id
+        );
+    }
+}
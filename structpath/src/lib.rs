@@ -1,12 +1,61 @@
+#[cfg(feature = "arrow")]
+mod arrow;
+mod batch;
+mod compiled;
+#[cfg(feature = "decimal")]
+mod decimal;
+#[cfg(any(
+    feature = "json",
+    feature = "prost",
+    feature = "toml",
+    feature = "yaml"
+))]
+mod document;
 mod error;
+mod from_paths;
+mod leaf;
+mod map;
 mod path;
+#[cfg(feature = "polars")]
+mod polars;
+#[cfg(feature = "reflect")]
+mod reflect;
+mod schema;
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(any(feature = "chrono", feature = "time"))]
+mod temporal;
 mod traits;
 mod value;
+#[cfg(feature = "prost")]
+mod well_known;
 
+#[cfg(all(feature = "arrow", feature = "rayon"))]
+pub use arrow::par_to_record_batch;
+#[cfg(feature = "arrow")]
+pub use arrow::to_record_batch;
+pub use batch::{assign, resolve_each, BatchResults, PathBatch, PathTrie};
+pub use compiled::{compile_nested, CompilePath, CompiledPath, PathFn};
 pub use error::StructPathError;
-pub use path::{Path, PathComponent};
-pub use traits::{StructInfo, StructPath};
-pub use value::Value;
+pub use from_paths::{child_path, read_list, read_value, FromPaths, PathSource};
+pub use leaf::PathLeaf;
+#[doc(hidden)]
+pub use leaf::{BoxedDispatch, Leaf, LeafDispatch};
+#[doc(hidden)]
+pub use map::MapValue;
+pub use path::{Path, PathComponent, PathParseError, PathParseErrorKind, PathPattern};
+#[cfg(all(feature = "polars", feature = "prost"))]
+pub use polars::{decode_paths, decode_paths_dtype};
+#[cfg(feature = "polars")]
+pub use polars::{from_dataframe, ChunkedDataFrameBuilder, Chunks, DataFrameBuilder};
+#[cfg(feature = "reflect")]
+pub use reflect::{load_descriptor_pool, message_field_info, message_fields_info};
+#[cfg(feature = "serde")]
+pub use serialize::{serialize_fields, to_json};
+pub use traits::{typed_accessor, StructInfo, StructPath};
+#[doc(hidden)]
+pub use traits::{Accessor, Borrowed, ByReference, ByValue, CopyAccessor, RefAccessor};
+pub use value::{BoxedValue, FromValue, ListValue, Value};
 
 #[cfg(feature = "derive")]
 extern crate structpath_derive;
@@ -16,3 +65,9 @@ pub use structpath_derive::StructPath;
 
 #[cfg(feature = "derive")]
 pub use structpath_derive::StructInfo;
+
+#[cfg(feature = "derive")]
+pub use structpath_derive::FromPaths;
+
+#[cfg(feature = "derive")]
+pub use structpath_derive::path;
@@ -0,0 +1,328 @@
+//! Map fields, such as the `HashMap`s generated by `prost-build` for protobuf
+//! `map` fields, navigated by key like the fields of a struct
+//!
+//! With `#[type_hint = "map"]`, `labels.team` is the value at key `team` of a
+//! `labels` map, and keys of other types than `String` are parsed from the
+//! path component, as in `scores.42`. Values are either leaves or nested
+//! structs navigated by the rest of the path.
+
+use crate::{
+    compiled::{compile_dynamic, CompilePath, PathFn},
+    error::StructPathError,
+    from_paths::{FromPaths, PathSource},
+    path::PathComponent,
+    traits::{Borrowed, StructInfo, StructPath},
+    value::Value,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::str::FromStr;
+use structpath_types::FieldsInfo;
+
+/// Trait for the values of map fields: nested structs, and the leaves
+/// listed below
+///
+/// Used by the implementations of the `structpath` traits for maps.
+#[doc(hidden)]
+pub trait MapValue {
+    /// Whether the values are leaves rather than nested structs
+    const LEAF: bool;
+
+    /// The value at the path made of `components` in `self`
+    fn get_components(&self, components: &[PathComponent]) -> Result<Value, StructPathError>;
+
+    /// Borrow what is at the path made of `components` in `self`
+    fn borrow_components(
+        &self,
+        components: &[PathComponent],
+    ) -> Result<Borrowed<'_>, StructPathError>;
+}
+
+impl<T: StructPath + Clone + Send + Sync + 'static> MapValue for T {
+    const LEAF: bool = false;
+
+    fn get_components(&self, components: &[PathComponent]) -> Result<Value, StructPathError> {
+        match components {
+            [] => Ok(Value::from_struct(self.clone())),
+            _ => self.get_value_by_components(components),
+        }
+    }
+
+    fn borrow_components(
+        &self,
+        components: &[PathComponent],
+    ) -> Result<Borrowed<'_>, StructPathError> {
+        self.borrow_by_components(components)
+    }
+}
+
+/// The error for a path going on past a leaf of type `T`
+fn past_leaf<T>() -> StructPathError {
+    StructPathError::TypeMismatch {
+        expected: "struct".to_string(),
+        actual: std::any::type_name::<T>().to_string(),
+    }
+}
+
+/// Map values converting with their `From` implementation of `Value`
+macro_rules! leaf_map_value {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl MapValue for $ty {
+                const LEAF: bool = true;
+
+                fn get_components(
+                    &self,
+                    components: &[PathComponent],
+                ) -> Result<Value, StructPathError> {
+                    match components {
+                        [] => Ok(Value::from(self.clone())),
+                        _ => Err(past_leaf::<$ty>()),
+                    }
+                }
+
+                fn borrow_components(
+                    &self,
+                    components: &[PathComponent],
+                ) -> Result<Borrowed<'_>, StructPathError> {
+                    match components {
+                        [] => Ok(Borrowed::Value),
+                        _ => Err(past_leaf::<$ty>()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+leaf_map_value! {
+    String,
+    i64,
+    i32,
+    u32,
+    u64,
+    f64,
+    f32,
+    bool,
+}
+
+/// Protobuf `bytes` values
+impl MapValue for Vec<u8> {
+    const LEAF: bool = true;
+
+    fn get_components(&self, components: &[PathComponent]) -> Result<Value, StructPathError> {
+        match components {
+            [] => Ok(Value::Bytes(self.clone())),
+            _ => Err(past_leaf::<Vec<u8>>()),
+        }
+    }
+
+    fn borrow_components(
+        &self,
+        components: &[PathComponent],
+    ) -> Result<Borrowed<'_>, StructPathError> {
+        match components {
+            [] => Ok(Borrowed::Value),
+            _ => Err(past_leaf::<Vec<u8>>()),
+        }
+    }
+}
+
+/// Protobuf `bytes` values, with the `bytes` option of `prost-build`
+#[cfg(feature = "bytes")]
+impl MapValue for bytes::Bytes {
+    const LEAF: bool = true;
+
+    fn get_components(&self, components: &[PathComponent]) -> Result<Value, StructPathError> {
+        match components {
+            [] => Ok(Value::Bytes(self.to_vec())),
+            _ => Err(past_leaf::<bytes::Bytes>()),
+        }
+    }
+
+    fn borrow_components(
+        &self,
+        components: &[PathComponent],
+    ) -> Result<Borrowed<'_>, StructPathError> {
+        match components {
+            [] => Ok(Borrowed::Value),
+            _ => Err(past_leaf::<bytes::Bytes>()),
+        }
+    }
+}
+
+/// The maps implementing the `structpath` traits
+trait Map {
+    type Key: FromStr + Display;
+    type Value: MapValue;
+
+    /// Name of the map type, as reported in `FieldNotFound` errors
+    const TYPE_NAME: &'static str;
+
+    fn lookup(&self, key: &Self::Key) -> Option<&Self::Value>;
+
+    fn keys(&self) -> impl Iterator<Item = &Self::Key>;
+}
+
+/// The keys of `map`, sorted
+fn key_names(map: &impl Map) -> Vec<String> {
+    let mut keys: Vec<String> = map.keys().map(ToString::to_string).collect();
+    keys.sort();
+    keys
+}
+
+/// The value at the key named by the first of `components`
+fn value_at<'a, M: Map>(
+    map: &'a M,
+    components: &[PathComponent],
+) -> Result<&'a M::Value, StructPathError> {
+    let not_found = |field: &str| {
+        let keys = key_names(map);
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+        StructPathError::field_not_found(field, components, M::TYPE_NAME, &keys)
+    };
+    match &components[0] {
+        PathComponent::Field(field) => field
+            .parse()
+            .ok()
+            .and_then(|key| map.lookup(&key))
+            .ok_or_else(|| not_found(field)),
+        // Protobuf map values are never lists
+        PathComponent::ArrayIndex(field, _) => Err(StructPathError::field_not_found(
+            field.clone(),
+            components,
+            M::TYPE_NAME,
+            &[],
+        )),
+    }
+}
+
+macro_rules! map_impls {
+    ($map:ident, $($bound:path),*) => {
+        impl<K, V> Map for $map<K, V>
+        where
+            K: FromStr + Display $(+ $bound)*,
+            V: MapValue,
+        {
+            type Key = K;
+            type Value = V;
+
+            const TYPE_NAME: &'static str = stringify!($map);
+
+            fn lookup(&self, key: &K) -> Option<&V> {
+                self.get(key)
+            }
+
+            fn keys(&self) -> impl Iterator<Item = &K> {
+                $map::keys(self)
+            }
+        }
+
+        impl<K, V> StructPath for $map<K, V>
+        where
+            K: FromStr + Display + Clone + Send + Sync + 'static $(+ $bound)*,
+            V: MapValue + Clone + Send + Sync + 'static,
+        {
+            fn get_value_by_components(
+                &self,
+                components: &[PathComponent],
+            ) -> Result<Value, StructPathError> {
+                if components.is_empty() {
+                    return Ok(Value::from_struct(self.clone()));
+                }
+                value_at(self, components)?
+                    .get_components(&components[1..])
+                    .map_err(|e| e.nested(components))
+            }
+
+            fn borrow_by_components(
+                &self,
+                components: &[PathComponent],
+            ) -> Result<Borrowed<'_>, StructPathError> {
+                if components.is_empty() {
+                    return Ok(Borrowed::Struct(self));
+                }
+                value_at(self, components)?
+                    .borrow_components(&components[1..])
+                    .map_err(|e| e.nested(components))
+            }
+
+            fn value_fields(&self) -> Vec<String> {
+                if V::LEAF {
+                    key_names(self)
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+
+        /// Keys are only matched when evaluated, as they vary between maps
+        impl<K, V> CompilePath for $map<K, V>
+        where
+            K: FromStr + Display + Clone + Send + Sync + 'static $(+ $bound)*,
+            V: MapValue + Clone + Send + Sync + 'static,
+        {
+            fn compile_components(
+                components: &[PathComponent],
+            ) -> Result<PathFn<Self>, StructPathError> {
+                Ok(compile_dynamic(components))
+            }
+        }
+
+        /// Maps have no static fields
+        impl<K, V> StructInfo for $map<K, V> {
+            fn get_fields_info() -> FieldsInfo {
+                FieldsInfo {
+                    name: stringify!($map).to_string(),
+                    fields: Vec::new(),
+                    proto_name: None,
+                }
+            }
+        }
+
+        /// Maps cannot be read back, as their keys are unknown: they read as
+        /// empty
+        impl<K, V> FromPaths for $map<K, V>
+        where
+            $map<K, V>: Default,
+        {
+            fn from_paths(
+                _source: &dyn PathSource,
+                _prefix: &[PathComponent],
+            ) -> Result<Self, StructPathError> {
+                Ok(Self::default())
+            }
+        }
+    };
+}
+
+map_impls!(HashMap, Eq, Hash);
+map_impls!(BTreeMap, Ord);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_paths() {
+        let mut scores = BTreeMap::new();
+        scores.insert(42i64, HashMap::from([("math".to_string(), 12i64)]));
+
+        assert_eq!(scores.get_value("42.math").unwrap(), Value::Integer(12));
+        let scores_42 = scores.get_value("42").unwrap();
+        assert_eq!(scores_42.as_unboxed::<HashMap<String, i64>>()["math"], 12);
+        assert_eq!(scores.value_fields(), Vec::<String>::new());
+        assert_eq!(scores[&42].value_fields(), vec!["math"]);
+
+        let err = scores.get_value("7.math").unwrap_err();
+        assert!(matches!(
+            err,
+            StructPathError::FieldNotFound { ref valid_fields, .. } if valid_fields == &["42"]
+        ));
+        let err = scores.get_value("42.math.value").unwrap_err();
+        assert!(matches!(err, StructPathError::TypeMismatch { .. }));
+        let err = scores.get_value("42[0]").unwrap_err();
+        assert!(matches!(err, StructPathError::FieldNotFound { .. }));
+    }
+}
@@ -1,7 +1,10 @@
+use std::ops::Range;
+
 /// Represents a single component in a path
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PathComponent {
-    /// A field name (e.g., "name", "father")
+    /// A field name (e.g., "name", "father"), or a protobuf field number
+    /// (e.g., "#5")
     Field(String),
     /// An array index (e.g., "pets[0]")
     ArrayIndex(String, usize),
@@ -34,9 +37,38 @@ impl Path {
     }
 }
 
-/// Error type for path parsing
-#[derive(Debug, thiserror::Error)]
-pub enum PathParseError {
+impl std::fmt::Display for PathComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathComponent::Field(field) => write!(f, "{field}"),
+            PathComponent::ArrayIndex(field, index) => write!(f, "{field}[{index}]"),
+        }
+    }
+}
+
+impl std::fmt::Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", components_to_string(&self.components))
+    }
+}
+
+/// Render path components with the same syntax accepted by `Path::from_str`
+pub(crate) fn components_to_string(components: &[PathComponent]) -> String {
+    components
+        .iter()
+        .map(|component| component.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// The protobuf field number of a `#5` component name
+pub(crate) fn field_number(name: &str) -> Option<u32> {
+    name.strip_prefix('#')?.parse().ok()
+}
+
+/// The kind of error encountered while parsing a path
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PathParseErrorKind {
     #[error("Empty path")]
     EmptyPath,
 
@@ -50,52 +82,127 @@ pub enum PathParseError {
     UnexpectedChar(char),
 }
 
+/// Error type for path parsing
+///
+/// Keeps the original input and the byte span of the offending token, so
+/// callers can point at the exact location of the problem.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{kind} at position {}", span.start)]
+pub struct PathParseError {
+    kind: PathParseErrorKind,
+    input: String,
+    span: Range<usize>,
+}
+
+impl PathParseError {
+    pub(crate) fn new(kind: PathParseErrorKind, input: &str, span: Range<usize>) -> Self {
+        Self {
+            kind,
+            input: input.to_string(),
+            span,
+        }
+    }
+
+    /// The kind of error
+    pub fn kind(&self) -> &PathParseErrorKind {
+        &self.kind
+    }
+
+    /// The path string that failed to parse
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Byte offset in the input where the error starts
+    pub fn position(&self) -> usize {
+        self.span.start
+    }
+
+    /// Byte range in the input covering the offending token
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Render the input with a caret line underneath the offending token
+    ///
+    /// ```text
+    /// pets[a].name
+    ///      ^ Invalid array index: a
+    /// ```
+    pub fn render(&self) -> String {
+        let offset = self.input[..self.span.start].chars().count();
+        let width = self.input[self.span.clone()].chars().count().max(1);
+        format!(
+            "{}\n{}{} {}",
+            self.input,
+            " ".repeat(offset),
+            "^".repeat(width),
+            self.kind
+        )
+    }
+}
+
 /// We don't implement the FromStr trait to avoid extra imports
 /// This prevent us from e.g. using `"pets[0].name".parse::<Path>()`
 impl Path {
+    /// Parse a path such as `pets[0].name`
+    ///
+    /// Fields of protobuf messages may also be named by their field number,
+    /// as in `#8[0].#1`, so that stored paths survive field renames. Other
+    /// names starting with `#`, such as the `#tag` key of a document, are
+    /// plain field names.
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<Self, PathParseError> {
+        use PathParseErrorKind::*;
+
+        let error = |kind, span| PathParseError::new(kind, s, span);
+
         if s.is_empty() {
-            return Err(PathParseError::EmptyPath);
+            return Err(error(EmptyPath, 0..0));
         }
 
         let mut components = Vec::new();
         let mut current = String::new();
-        let mut in_bracket = false;
+        // Byte offset of the opening bracket, while inside one
+        let mut bracket_start: Option<usize> = None;
 
-        for c in s.chars() {
+        for (pos, c) in s.char_indices() {
+            let char_span = pos..pos + c.len_utf8();
             match c {
-                '.' if !in_bracket => {
+                '.' if bracket_start.is_none() => {
                     if !current.is_empty() {
                         components.push(PathComponent::Field(current));
                         current = String::new();
                     }
                 }
-                '[' if !in_bracket => {
+                '[' if bracket_start.is_none() => {
                     if current.is_empty() {
-                        return Err(PathParseError::UnexpectedChar('['));
+                        return Err(error(UnexpectedChar('['), char_span));
                     }
                     components.push(PathComponent::Field(current));
                     current = String::new();
-                    in_bracket = true;
+                    bracket_start = Some(pos);
                 }
-                ']' if in_bracket => {
+                ']' => {
+                    let Some(start) = bracket_start else {
+                        return Err(error(UnexpectedChar(']'), char_span));
+                    };
                     let index = current
                         .parse::<usize>()
-                        .map_err(|_| PathParseError::InvalidArrayIndex(current.clone()))?;
+                        .map_err(|_| error(InvalidArrayIndex(current.clone()), start + 1..pos))?;
 
                     // Get the field name before the array index
                     if let Some(PathComponent::Field(field)) = components.pop() {
                         components.push(PathComponent::ArrayIndex(field, index));
                     } else {
-                        return Err(PathParseError::UnexpectedChar(']'));
+                        return Err(error(UnexpectedChar(']'), char_span));
                     }
 
                     current = String::new();
-                    in_bracket = false;
+                    bracket_start = None;
                 }
                 c if c.is_whitespace() => {
-                    return Err(PathParseError::UnexpectedChar(c));
+                    return Err(error(UnexpectedChar(c), char_span));
                 }
                 c => {
                     current.push(c);
@@ -103,8 +210,8 @@ impl Path {
             }
         }
 
-        if in_bracket {
-            return Err(PathParseError::UnclosedBracket);
+        if let Some(start) = bracket_start {
+            return Err(error(UnclosedBracket, start..s.len()));
         }
 
         if !current.is_empty() {
@@ -112,13 +219,120 @@ impl Path {
         }
 
         if components.is_empty() {
-            return Err(PathParseError::EmptyPath);
+            return Err(error(EmptyPath, 0..s.len()));
         }
 
         Ok(Path { components })
     }
 }
 
+/// A path whose list fields may be indexed with the `[*]` wildcard
+///
+/// For example `members[*].pets[*].name` stands for the name of every pet of
+/// every member. Only list fields can be wildcarded, one level at a time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathPattern {
+    input: String,
+    /// Components between wildcards: all segments but the last end with the
+    /// wildcarded list field, the last one may be empty.
+    segments: Vec<Vec<PathComponent>>,
+}
+
+impl PathPattern {
+    /// Parse a path possibly containing `[*]` wildcards
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Result<Self, PathParseError> {
+        const WILDCARD: &str = "[*]";
+
+        let mut segments = Vec::new();
+        let mut start = 0;
+        for (pos, _) in s.match_indices(WILDCARD) {
+            let segment = parse_segment(s, start..pos)?;
+            if !matches!(segment.last(), Some(PathComponent::Field(_))) {
+                return Err(PathParseError::new(
+                    PathParseErrorKind::UnexpectedChar('['),
+                    s,
+                    pos..pos + 1,
+                ));
+            }
+            segments.push(segment);
+            start = pos + WILDCARD.len();
+        }
+        if start == 0 || start < s.len() {
+            segments.push(parse_segment(s, start..s.len())?);
+        } else {
+            segments.push(Vec::new());
+        }
+
+        Ok(Self {
+            input: s.to_string(),
+            segments,
+        })
+    }
+
+    /// Number of `[*]` wildcards
+    pub fn wildcards(&self) -> usize {
+        self.segments.len() - 1
+    }
+
+    /// The components between wildcards
+    ///
+    /// All segments but the last end with the wildcarded list field, and the
+    /// last one is empty when the pattern ends with a wildcard.
+    pub fn segments(&self) -> &[Vec<PathComponent>] {
+        &self.segments
+    }
+
+    /// The concrete path with every wildcard replaced by `index`
+    pub fn with_index(&self, index: usize) -> Path {
+        let mut components = Vec::new();
+        for segment in &self.segments {
+            if let Some(PathComponent::Field(name)) = components.pop() {
+                components.push(PathComponent::ArrayIndex(name, index));
+            }
+            components.extend(segment.iter().cloned());
+        }
+        Path { components }
+    }
+}
+
+impl std::fmt::Display for PathPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.input)
+    }
+}
+
+/// Parse the part of `input` at `span`, which follows a wildcard unless it
+/// starts at 0
+fn parse_segment(input: &str, span: Range<usize>) -> Result<Vec<PathComponent>, PathParseError> {
+    let segment = &input[span.clone()];
+    if span.start > 0 {
+        match segment.chars().next() {
+            Some('.') => {}
+            Some(c) => {
+                return Err(PathParseError::new(
+                    PathParseErrorKind::UnexpectedChar(c),
+                    input,
+                    span.start..span.start + c.len_utf8(),
+                ))
+            }
+            None => {
+                return Err(PathParseError::new(
+                    PathParseErrorKind::EmptyPath,
+                    input,
+                    span,
+                ))
+            }
+        }
+    }
+    Path::from_str(segment)
+        .map(|path| path.components)
+        .map_err(|e| {
+            let shifted = e.span.start + span.start..e.span.end + span.start;
+            PathParseError::new(e.kind, input, shifted)
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,6 +366,32 @@ mod tests {
         assert_eq!(path.components[1], PathComponent::Field("name".to_string()));
     }
 
+    #[test]
+    fn test_field_numbers() {
+        let path = Path::from_str("#8[1].#2").unwrap();
+        assert_eq!(
+            path.components,
+            vec![
+                PathComponent::ArrayIndex("#8".to_string(), 1),
+                PathComponent::Field("#2".to_string()),
+            ]
+        );
+        assert_eq!(path.to_string(), "#8[1].#2");
+        assert_eq!(field_number("#8"), Some(8));
+        assert_eq!(field_number("name"), None);
+
+        let path = Path::from_str("name.#x1").unwrap();
+        assert_eq!(path.components[1], PathComponent::Field("#x1".to_string()));
+        assert_eq!(field_number("#x1"), None);
+        assert_eq!(field_number("#-1"), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let path = Path::from_str("members[0].pets[1].name").unwrap();
+        assert_eq!(path.to_string(), "members[0].pets[1].name");
+    }
+
     #[test]
     fn test_invalid_paths() {
         assert!(Path::from_str("").is_err());
@@ -160,5 +400,78 @@ mod tests {
         assert!(Path::from_str("pets[a]").is_err());
         assert!(Path::from_str("pets[0").is_err());
         assert!(Path::from_str("name ").is_err());
+        assert!(Path::from_str("name]").is_err());
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let err = Path::from_str("pets[a].name").unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &PathParseErrorKind::InvalidArrayIndex("a".to_string())
+        );
+        assert_eq!(err.input(), "pets[a].name");
+        assert_eq!(err.position(), 5);
+        assert_eq!(err.span(), 5..6);
+
+        let err = Path::from_str("father.na me").unwrap_err();
+        assert_eq!(err.kind(), &PathParseErrorKind::UnexpectedChar(' '));
+        assert_eq!(err.position(), 9);
+
+        let err = Path::from_str("name.pets[0").unwrap_err();
+        assert_eq!(err.kind(), &PathParseErrorKind::UnclosedBracket);
+        assert_eq!(err.span(), 9..11);
+
+        let err = Path::from_str("name.[0]").unwrap_err();
+        assert_eq!(err.kind(), &PathParseErrorKind::UnexpectedChar('['));
+        assert_eq!(err.position(), 5);
+    }
+
+    #[test]
+    fn test_error_render() {
+        let err = Path::from_str("pets[12a].name").unwrap_err();
+        assert_eq!(
+            err.render(),
+            "pets[12a].name\n     ^^^ Invalid array index: 12a"
+        );
+        assert_eq!(err.to_string(), "Invalid array index: 12a at position 5");
+
+        let err = Path::from_str("").unwrap_err();
+        assert_eq!(err.render(), "\n^ Empty path");
+    }
+
+    #[test]
+    fn test_path_pattern() {
+        let pattern = PathPattern::from_str("members[*].pets[*].name").unwrap();
+        assert_eq!(pattern.wildcards(), 2);
+        assert_eq!(pattern.to_string(), "members[*].pets[*].name");
+        assert_eq!(
+            pattern.segments()[1],
+            vec![PathComponent::Field("pets".to_string())]
+        );
+        assert_eq!(pattern.with_index(0).to_string(), "members[0].pets[0].name");
+
+        let pattern = PathPattern::from_str("admin.tags[*]").unwrap();
+        assert_eq!(pattern.segments()[1], vec![]);
+        assert_eq!(pattern.with_index(1).to_string(), "admin.tags[1]");
+
+        let pattern = PathPattern::from_str("name").unwrap();
+        assert_eq!(pattern.wildcards(), 0);
+    }
+
+    #[test]
+    fn test_invalid_path_patterns() {
+        let err = PathPattern::from_str("members[*]pets").unwrap_err();
+        assert_eq!(err.kind(), &PathParseErrorKind::UnexpectedChar('p'));
+        assert_eq!(err.position(), 10);
+
+        let err = PathPattern::from_str("members[*].pets[a]").unwrap_err();
+        assert_eq!(err.input(), "members[*].pets[a]");
+        assert_eq!(err.span(), 16..17);
+
+        let err = PathPattern::from_str("members[0][*]").unwrap_err();
+        assert_eq!(err.position(), 10);
+        assert!(PathPattern::from_str("[*]").is_err());
+        assert!(PathPattern::from_str("members[*][*]").is_err());
     }
 }
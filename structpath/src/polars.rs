@@ -0,0 +1,889 @@
+use crate::{
+    batch::PathBatch,
+    error::StructPathError,
+    from_paths::{FromPaths, PathSource},
+    path::{components_to_string, Path, PathComponent, PathPattern},
+    traits::{StructInfo, StructPath},
+    value::{to_scale, Value},
+};
+use polars::prelude::{
+    AnyValue, Column, DataFrame, Int128Chunked, Int32Chunked, Int64Chunked, IntoColumn, IntoSeries,
+    NewChunkedArray, PolarsError, TimeUnit,
+};
+#[cfg(feature = "prost")]
+use polars::prelude::{DataType, Field, StructChunked};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use structpath_types::FieldType;
+
+/// Builds a Polars `DataFrame` out of records, one column per path
+///
+/// By default each record gives one row. In explode mode, paths may index
+/// list fields with the `[*]` wildcard and each list element gives a row, as
+/// Polars' `explode` would: with `name`, `members[*].name` and
+/// `members[*].pets[*].name` there is one row per pet, repeating the name of
+/// its member and group. Empty lists still give a row, with nulls in the
+/// columns below them.
+///
+/// ```ignore
+/// let df = DataFrameBuilder::new(&["name", "members[*].name", "members[*].pets[*].name"])?
+///     .explode()
+///     .build(&groups)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct DataFrameBuilder {
+    patterns: Vec<PathPattern>,
+    explode: bool,
+    decimal_scale: Option<u32>,
+}
+
+impl DataFrameBuilder {
+    /// Prepare a `DataFrame` with one column per path, named after it
+    pub fn new(paths: &[&str]) -> Result<Self, StructPathError> {
+        let patterns = paths
+            .iter()
+            .map(|path| PathPattern::from_str(path))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            patterns,
+            explode: false,
+            decimal_scale: None,
+        })
+    }
+
+    /// Give a row to each element of the lists indexed with `[*]`
+    pub fn explode(mut self) -> Self {
+        self.explode = true;
+        self
+    }
+
+    /// Give `Decimal` columns the scale `scale` instead of the largest one
+    /// among their values, failing for values with more digits
+    pub fn decimal_scale(mut self, scale: u32) -> Self {
+        self.decimal_scale = Some(scale);
+        self
+    }
+
+    /// Extract the columns from `records`
+    ///
+    /// Columns must be strings, integers, floats, booleans, decimals, big
+    /// integers, date-times, dates, durations or bytes, possibly optional.
+    /// Decimals become `Decimal` columns of the largest scale among their
+    /// values, or of the one set with [`decimal_scale`](Self::decimal_scale),
+    /// big integers `Decimal` ones of scale 0, date-times `Datetime`
+    /// columns of nanoseconds in UTC, without time zone, dates `Date` ones,
+    /// durations `Duration` ones and bytes `Binary` ones.
+    pub fn build<T: StructPath + StructInfo>(
+        &self,
+        records: &[T],
+    ) -> Result<DataFrame, StructPathError> {
+        let mut builder = self.chunked::<T>(usize::MAX)?;
+        for record in records {
+            builder.push(record)?;
+        }
+        builder.flush()
+    }
+
+    /// Extract the columns like [`build`](Self::build), building the rows of
+    /// `chunk_size` records in parallel before concatenating them
+    #[cfg(feature = "rayon")]
+    pub fn par_build<T: StructPath + StructInfo + Sync>(
+        &self,
+        records: &[T],
+        chunk_size: usize,
+    ) -> Result<DataFrame, StructPathError> {
+        use rayon::prelude::*;
+
+        let chunks = records
+            .par_chunks(chunk_size.max(1))
+            .map(|chunk| self.build(chunk))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut chunks = unify_decimal_scales(chunks)?.into_iter();
+        let Some(mut df) = chunks.next() else {
+            return self.build(records);
+        };
+        for chunk in chunks {
+            df.vstack_mut(&chunk).map_err(polars_error)?;
+        }
+        df.as_single_chunk_par();
+        Ok(df)
+    }
+
+    /// Build the `DataFrame` incrementally, in chunks of `chunk_size` rows
+    ///
+    /// Records are appended one by one to typed column buffers, so only the
+    /// rows of the current chunk are held in memory. For the chunks to stack,
+    /// `Decimal` columns keep the scale of the first chunk, unless set with
+    /// [`decimal_scale`](Self::decimal_scale), and values of later chunks
+    /// with more digits fail.
+    pub fn chunked<T: StructPath + StructInfo>(
+        &self,
+        chunk_size: usize,
+    ) -> Result<ChunkedDataFrameBuilder<T>, StructPathError> {
+        let levels = self.levels()?.to_vec();
+        let columns = self
+            .patterns
+            .iter()
+            .map(|pattern| {
+                let mut values = ColumnValues::for_path::<T>(&pattern.with_index(0), pattern)?;
+                if let ColumnValues::Decimal { scale, .. } = &mut values {
+                    *scale = self.decimal_scale;
+                }
+                Ok(values)
+            })
+            .collect::<Result<_, StructPathError>>()?;
+        // Without wildcards, the paths are the patterns themselves
+        let batch = levels.is_empty().then(|| {
+            PathBatch::new(
+                self.patterns
+                    .iter()
+                    .map(|pattern| pattern.with_index(0))
+                    .collect(),
+            )
+        });
+        Ok(ChunkedDataFrameBuilder {
+            patterns: self.patterns.clone(),
+            levels,
+            batch,
+            columns,
+            rows: 0,
+            chunk_size: chunk_size.max(1),
+            record: PhantomData,
+        })
+    }
+
+    /// The lists to explode, outermost first, each relative to the previous
+    ///
+    /// Columns exploding the same number of levels must go through the same
+    /// lists: sibling lists would give unrelated rows.
+    fn levels(&self) -> Result<&[Vec<PathComponent>], StructPathError> {
+        let Some(deepest) = self.patterns.iter().max_by_key(|p| p.wildcards()) else {
+            return Ok(&[]);
+        };
+        let levels = &deepest.segments()[..deepest.wildcards()];
+        for pattern in &self.patterns {
+            let depth = pattern.wildcards();
+            if depth > 0 && !self.explode {
+                return Err(StructPathError::Polars(format!(
+                    "`{pattern}` has wildcards, which need explode mode"
+                )));
+            }
+            if pattern.segments()[..depth] != levels[..depth] {
+                return Err(StructPathError::Polars(format!(
+                    "`{pattern}` and `{deepest}` explode different lists"
+                )));
+            }
+        }
+        Ok(levels)
+    }
+}
+
+/// Incremental [`DataFrameBuilder`], giving `DataFrame` chunks as records
+/// are pushed
+///
+/// ```ignore
+/// let mut builder = DataFrameBuilder::new(&["name", "age"])?.chunked::<User>(10_000)?;
+/// for user in users {
+///     if let Some(chunk) = builder.push(&user)? {
+///         write(chunk)?;
+///     }
+/// }
+/// write(builder.flush()?)?;
+/// ```
+pub struct ChunkedDataFrameBuilder<T> {
+    patterns: Vec<PathPattern>,
+    levels: Vec<Vec<PathComponent>>,
+    /// The paths of the columns, outside of explode mode
+    batch: Option<PathBatch>,
+    columns: Vec<ColumnValues>,
+    rows: usize,
+    chunk_size: usize,
+    record: PhantomData<fn(&T)>,
+}
+
+impl<T: StructPath + StructInfo> ChunkedDataFrameBuilder<T> {
+    /// Append the rows of `record`, giving a chunk once `chunk_size` rows are
+    /// buffered
+    ///
+    /// Chunks hold whole records: in explode mode, a chunk can exceed
+    /// `chunk_size` by the rows of its last record. A record failing to
+    /// convert adds no rows.
+    pub fn push(&mut self, record: &T) -> Result<Option<DataFrame>, StructPathError> {
+        let rows = match &self.batch {
+            Some(batch) => vec![record
+                .get_values_batch(batch)
+                .into_iter()
+                .map(|value| Ok(value?.flatten()))
+                .collect::<Result<Vec<_>, StructPathError>>()?],
+            None => self.exploded_rows(record)?,
+        };
+
+        let len = rows.len();
+        let appended = rows.into_iter().try_for_each(|row| {
+            self.columns
+                .iter_mut()
+                .zip(row)
+                .try_for_each(|(column, value)| column.push(value))
+        });
+        if let Err(e) = appended {
+            for column in &mut self.columns {
+                column.truncate(self.rows);
+            }
+            return Err(e);
+        }
+
+        self.rows += len;
+        if self.rows >= self.chunk_size {
+            self.flush().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The values of the rows of `record` in explode mode, `None` below
+    /// empty lists
+    ///
+    /// The rows below a list element share the values of the columns below
+    /// it, so each path is resolved once, all in a single traversal.
+    fn exploded_rows(&self, record: &T) -> Result<Vec<Vec<Option<Value>>>, StructPathError> {
+        let mut rows = Vec::new();
+        explode_record(record, &self.levels, &mut vec![Some(Vec::new())], &mut rows)?;
+
+        let mut paths = Vec::new();
+        // The index in `paths` of each column below each list element
+        let mut resolved = HashMap::new();
+        let slots: Vec<Vec<Option<usize>>> = rows
+            .iter()
+            .map(|prefixes| {
+                self.patterns
+                    .iter()
+                    .enumerate()
+                    .map(|(column, pattern)| {
+                        let prefix = prefixes[pattern.wildcards()].as_deref()?;
+                        let slot = resolved.entry((column, prefix)).or_insert_with(|| {
+                            let tail = pattern.segments().last().map(Vec::as_slice);
+                            paths.push(Path {
+                                components: [prefix, tail.unwrap_or_default()].concat(),
+                            });
+                            paths.len() - 1
+                        });
+                        Some(*slot)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let values = record
+            .get_values(&paths)
+            .into_iter()
+            .map(|value| Ok(value?.flatten()))
+            .collect::<Result<Vec<_>, StructPathError>>()?;
+        Ok(slots
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|slot| slot.and_then(|slot| values[slot].clone()))
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Number of rows buffered since the last chunk
+    pub fn buffered_rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Take the buffered rows as a `DataFrame`, possibly empty
+    pub fn flush(&mut self) -> Result<DataFrame, StructPathError> {
+        self.rows = 0;
+        let columns = self
+            .patterns
+            .iter()
+            .zip(&mut self.columns)
+            .map(|(pattern, values)| values.take(pattern.to_string()))
+            .collect::<Result<_, _>>()?;
+        DataFrame::new(columns).map_err(polars_error)
+    }
+
+    /// Iterate over the chunks built from `records`, the last one holding
+    /// the remaining rows
+    pub fn chunks<I>(self, records: I) -> Chunks<T, I::IntoIter>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        Chunks {
+            builder: self,
+            records: records.into_iter(),
+            done: false,
+        }
+    }
+}
+
+/// Iterator over the `DataFrame` chunks of an iterator of records, see
+/// [`ChunkedDataFrameBuilder::chunks`]
+pub struct Chunks<T, I> {
+    builder: ChunkedDataFrameBuilder<T>,
+    records: I,
+    done: bool,
+}
+
+impl<T, I> Iterator for Chunks<T, I>
+where
+    T: StructPath + StructInfo,
+    I: Iterator,
+    I::Item: Borrow<T>,
+{
+    type Item = Result<DataFrame, StructPathError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        for record in self.records.by_ref() {
+            match self.builder.push(record.borrow()) {
+                Ok(None) => {}
+                Ok(Some(chunk)) => return Some(Ok(chunk)),
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.done = true;
+        (self.builder.buffered_rows() > 0).then(|| self.builder.flush())
+    }
+}
+
+fn polars_error(error: PolarsError) -> StructPathError {
+    StructPathError::Polars(error.to_string())
+}
+
+/// For `?` in the functions of Polars expressions
+impl From<StructPathError> for PolarsError {
+    fn from(error: StructPathError) -> Self {
+        PolarsError::ComputeError(error.to_string().into())
+    }
+}
+
+/// Cast the `Decimal` columns of `chunks` to the largest scale among them,
+/// so that the chunks can be stacked
+#[cfg(feature = "rayon")]
+fn unify_decimal_scales(mut chunks: Vec<DataFrame>) -> Result<Vec<DataFrame>, StructPathError> {
+    use polars::prelude::DataType;
+
+    let Some(first) = chunks.first() else {
+        return Ok(chunks);
+    };
+    for index in 0..first.width() {
+        let scales = chunks
+            .iter()
+            .map(|chunk| match chunk.get_columns()[index].dtype() {
+                DataType::Decimal(_, scale) => *scale,
+                _ => None,
+            });
+        let Some(scale) = scales.max().flatten() else {
+            continue;
+        };
+        let dtype = DataType::Decimal(Some(38), Some(scale));
+        for chunk in &mut chunks {
+            let column = chunk.get_columns()[index]
+                .strict_cast(&dtype)
+                .map_err(polars_error)?;
+            chunk.replace_column(index, column).map_err(polars_error)?;
+        }
+    }
+    Ok(chunks)
+}
+
+/// For each row of `record` below the element at the last of `prefixes`,
+/// push the path to the list element it comes from at each exploded level,
+/// `None` below an empty list
+fn explode_record<T: StructPath>(
+    record: &T,
+    levels: &[Vec<PathComponent>],
+    prefixes: &mut Vec<Option<Vec<PathComponent>>>,
+    rows: &mut Vec<Vec<Option<Vec<PathComponent>>>>,
+) -> Result<(), StructPathError> {
+    let depth = prefixes.len() - 1;
+    let Some(level) = levels.get(depth) else {
+        rows.push(prefixes.clone());
+        return Ok(());
+    };
+
+    let list = prefixes[depth]
+        .as_ref()
+        .map(|prefix| [prefix.as_slice(), level].concat());
+    let len = match &list {
+        Some(list) => match record.get_value_by_components(list)?.flatten() {
+            Some(value) => value.try_list_len()?,
+            None => 0,
+        },
+        None => 0,
+    };
+
+    if len == 0 {
+        prefixes.push(None);
+        explode_record(record, levels, prefixes, rows)?;
+        prefixes.pop();
+    }
+    if let Some(list) = list {
+        for i in 0..len {
+            let mut element = list.clone();
+            if let Some(PathComponent::Field(name)) = element.pop() {
+                element.push(PathComponent::ArrayIndex(name, i));
+            }
+            prefixes.push(Some(element));
+            explode_record(record, levels, prefixes, rows)?;
+            prefixes.pop();
+        }
+    }
+    Ok(())
+}
+
+/// Buffered values of a column, typed by `StructInfo`
+enum ColumnValues {
+    String(Vec<Option<String>>),
+    Integer(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Boolean(Vec<Option<bool>>),
+    /// Mantissas and scales, brought to `scale` when taken, which is the
+    /// largest of the first values taken unless set
+    Decimal {
+        values: Vec<Option<(i128, u32)>>,
+        scale: Option<u32>,
+    },
+    BigInt(Vec<Option<i128>>),
+    /// Nanoseconds since the Unix epoch
+    DateTime(Vec<Option<i64>>),
+    /// Days since the Unix epoch
+    Date(Vec<Option<i32>>),
+    /// Nanoseconds
+    Duration(Vec<Option<i64>>),
+    Bytes(Vec<Option<Vec<u8>>>),
+}
+
+impl ColumnValues {
+    /// Buffer for the values at `path`, which must be a string, integer,
+    /// float, boolean, decimal, big integer, date-time, date, duration or
+    /// bytes field, possibly optional
+    ///
+    /// `name` is how the path is reported in errors.
+    fn for_path<T: StructInfo>(
+        path: &Path,
+        name: &dyn std::fmt::Display,
+    ) -> Result<Self, StructPathError> {
+        let field_type = match T::get_field_info(path)?.r#type {
+            FieldType::Option(inner) => *inner,
+            field_type => field_type,
+        };
+        match field_type {
+            FieldType::String => Ok(ColumnValues::String(Vec::new())),
+            FieldType::Integer => Ok(ColumnValues::Integer(Vec::new())),
+            FieldType::Float => Ok(ColumnValues::Float(Vec::new())),
+            FieldType::Boolean => Ok(ColumnValues::Boolean(Vec::new())),
+            FieldType::Decimal => Ok(ColumnValues::Decimal {
+                values: Vec::new(),
+                scale: None,
+            }),
+            FieldType::BigInt => Ok(ColumnValues::BigInt(Vec::new())),
+            FieldType::DateTime => Ok(ColumnValues::DateTime(Vec::new())),
+            FieldType::Date => Ok(ColumnValues::Date(Vec::new())),
+            FieldType::Duration => Ok(ColumnValues::Duration(Vec::new())),
+            FieldType::Bytes => Ok(ColumnValues::Bytes(Vec::new())),
+            _ => Err(StructPathError::Polars(format!(
+                "`{name}` is not a string, integer, float, boolean, decimal, big integer, date-time, date, duration or bytes field"
+            ))),
+        }
+    }
+
+    fn push(&mut self, value: Option<Value>) -> Result<(), StructPathError> {
+        match self {
+            ColumnValues::String(values) => values.push(value.map(String::try_from).transpose()?),
+            ColumnValues::Integer(values) => values.push(value.map(i64::try_from).transpose()?),
+            ColumnValues::Float(values) => values.push(value.map(f64::try_from).transpose()?),
+            ColumnValues::Boolean(values) => values.push(value.map(bool::try_from).transpose()?),
+            ColumnValues::Bytes(values) => values.push(value.map(Vec::<u8>::try_from).transpose()?),
+            ColumnValues::Decimal { values, .. } => {
+                values.push(value.map(|value| value.try_as_decimal()).transpose()?)
+            }
+            ColumnValues::BigInt(values) => values.push(
+                value
+                    .map(|value| match value {
+                        Value::BigUint(unsigned) => i128::try_from(unsigned).map_err(|_| {
+                            StructPathError::Polars(format!("{unsigned} is out of the i128 range"))
+                        }),
+                        value => value.try_as_i128(),
+                    })
+                    .transpose()?,
+            ),
+            ColumnValues::Date(values) => {
+                values.push(value.map(|value| value.try_as_date()).transpose()?)
+            }
+            ColumnValues::DateTime(values) | ColumnValues::Duration(values) => {
+                let nanos = value.map(|value| value.total_nanos()).transpose()?;
+                values.push(
+                    nanos
+                        .map(|nanos| {
+                            nanos.ok_or_else(|| {
+                                StructPathError::Polars(
+                                    "value out of the nanosecond range".to_string(),
+                                )
+                            })
+                        })
+                        .transpose()?,
+                )
+            }
+        }
+        Ok(())
+    }
+
+    /// The type of the column of the values, without the scale of decimals
+    #[cfg(feature = "prost")]
+    fn dtype(&self) -> DataType {
+        match self {
+            ColumnValues::String(_) => DataType::String,
+            ColumnValues::Integer(_) => DataType::Int64,
+            ColumnValues::Float(_) => DataType::Float64,
+            ColumnValues::Boolean(_) => DataType::Boolean,
+            ColumnValues::Decimal { .. } => DataType::Decimal(Some(38), None),
+            ColumnValues::BigInt(_) => DataType::Decimal(Some(38), Some(0)),
+            ColumnValues::DateTime(_) => DataType::Datetime(TimeUnit::Nanoseconds, None),
+            ColumnValues::Date(_) => DataType::Date,
+            ColumnValues::Duration(_) => DataType::Duration(TimeUnit::Nanoseconds),
+            ColumnValues::Bytes(_) => DataType::Binary,
+        }
+    }
+
+    /// Drop the values buffered after the first `len`
+    fn truncate(&mut self, len: usize) {
+        match self {
+            ColumnValues::String(values) => values.truncate(len),
+            ColumnValues::Integer(values) => values.truncate(len),
+            ColumnValues::Float(values) => values.truncate(len),
+            ColumnValues::Boolean(values) => values.truncate(len),
+            ColumnValues::Decimal { values, .. } => values.truncate(len),
+            ColumnValues::BigInt(values) => values.truncate(len),
+            ColumnValues::DateTime(values) | ColumnValues::Duration(values) => values.truncate(len),
+            ColumnValues::Date(values) => values.truncate(len),
+            ColumnValues::Bytes(values) => values.truncate(len),
+        }
+    }
+
+    /// Take the buffered values as a column named `name`
+    ///
+    /// Decimals are rescaled to the scale of the column, which fails if a
+    /// value then needs more than 38 digits or loses digits.
+    fn take(&mut self, name: String) -> Result<Column, StructPathError> {
+        let name = name.into();
+        let column = match self {
+            ColumnValues::String(values) => Column::new(name, std::mem::take(values)),
+            ColumnValues::Integer(values) => Column::new(name, std::mem::take(values)),
+            ColumnValues::Float(values) => Column::new(name, std::mem::take(values)),
+            ColumnValues::Boolean(values) => Column::new(name, std::mem::take(values)),
+            ColumnValues::Bytes(values) => Column::new(name, std::mem::take(values)),
+            ColumnValues::Decimal { values, scale } => {
+                let values = std::mem::take(values);
+                let largest = values.iter().flatten().map(|(_, scale)| *scale).max();
+                let scale = *scale.get_or_insert(largest.unwrap_or_default());
+                let mantissas = values
+                    .into_iter()
+                    .map(|value| {
+                        value
+                            .map(|(mantissa, from)| {
+                                to_scale(mantissa, from, scale).ok_or_else(|| {
+                                    StructPathError::Polars(format!(
+                                        "decimal {mantissa}e-{from} is out of range at scale {scale}"
+                                    ))
+                                })
+                            })
+                            .transpose()
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Int128Chunked::from_iter_options(name, mantissas.into_iter())
+                    .into_decimal(Some(38), scale as usize)
+                    .map_err(polars_error)?
+                    .into_series()
+                    .into_column()
+            }
+            ColumnValues::BigInt(values) => {
+                Int128Chunked::from_iter_options(name, std::mem::take(values).into_iter())
+                    .into_decimal(Some(38), 0)
+                    .map_err(polars_error)?
+                    .into_series()
+                    .into_column()
+            }
+            ColumnValues::DateTime(values) => {
+                Int64Chunked::from_iter_options(name, std::mem::take(values).into_iter())
+                    .into_datetime(TimeUnit::Nanoseconds, None)
+                    .into_series()
+                    .into_column()
+            }
+            ColumnValues::Date(values) => {
+                Int32Chunked::from_iter_options(name, std::mem::take(values).into_iter())
+                    .into_date()
+                    .into_series()
+                    .into_column()
+            }
+            ColumnValues::Duration(values) => {
+                Int64Chunked::from_iter_options(name, std::mem::take(values).into_iter())
+                    .into_duration(TimeUnit::Nanoseconds)
+                    .into_series()
+                    .into_column()
+            }
+        };
+        Ok(column)
+    }
+}
+
+/// Evaluate `paths` on the protobuf messages encoded in the binary `column`
+///
+/// Each row is decoded as a `T` and dropped once its values are extracted,
+/// so messages are never collected. Gives a struct column named after
+/// `column`, with one field per path, named after it, and null fields for
+/// null rows. Fields must be strings, integers, floats, booleans, decimals,
+/// big integers, date-times, dates, durations or bytes, possibly optional.
+///
+/// In a lazy query, it backs a `map` expression whose output type is given
+/// by [`decode_paths_dtype`], and the struct can be unnested:
+///
+/// ```ignore
+/// let paths = ["name", "admin.name"];
+/// let dtype = decode_paths_dtype::<Group>(&paths)?;
+/// let decode = move |column: Column| Ok(Some(decode_paths::<Group>(&column, &paths)?));
+/// let df = df
+///     .lazy()
+///     .with_column(col("payload").map(decode, GetOutput::from_type(dtype)))
+///     .unnest(["payload"])
+///     .collect()?;
+/// ```
+#[cfg(feature = "prost")]
+pub fn decode_paths<T>(column: &Column, paths: &[&str]) -> Result<Column, StructPathError>
+where
+    T: prost::Message + Default + StructPath + StructInfo,
+{
+    let (paths, mut columns) = decoded_columns::<T>(paths)?;
+    let batch = PathBatch::new(paths);
+
+    let bytes = column.binary().map_err(polars_error)?;
+    for (row, bytes) in bytes.into_iter().enumerate() {
+        let Some(bytes) = bytes else {
+            for values in &mut columns {
+                values.push(None)?;
+            }
+            continue;
+        };
+        let message = T::decode(bytes).map_err(|e| StructPathError::Decode {
+            row,
+            message: e.to_string(),
+        })?;
+        for (values, value) in columns.iter_mut().zip(message.get_values_batch(&batch)) {
+            values.push(value?.flatten())?;
+        }
+    }
+
+    let columns = batch
+        .paths()
+        .iter()
+        .zip(&mut columns)
+        .map(|(path, values)| values.take(path.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    StructChunked::from_columns(column.name().clone(), column.len(), &columns)
+        .map(IntoColumn::into_column)
+        .map_err(polars_error)
+}
+
+/// The type of the column given by [`decode_paths`], as the output type of
+/// lazy `map` expressions
+///
+/// Decimal fields have no scale, which depends on their values.
+#[cfg(feature = "prost")]
+pub fn decode_paths_dtype<T: StructInfo>(paths: &[&str]) -> Result<DataType, StructPathError> {
+    let (paths, columns) = decoded_columns::<T>(paths)?;
+    let fields = paths
+        .iter()
+        .zip(&columns)
+        .map(|(path, values)| Field::new(path.to_string().into(), values.dtype()))
+        .collect();
+    Ok(DataType::Struct(fields))
+}
+
+/// The paths of [`decode_paths`] and the buffers of their values
+#[cfg(feature = "prost")]
+fn decoded_columns<T: StructInfo>(
+    paths: &[&str],
+) -> Result<(Vec<Path>, Vec<ColumnValues>), StructPathError> {
+    let paths = paths
+        .iter()
+        .map(|path| Path::from_str(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    let columns = paths
+        .iter()
+        .map(|path| ColumnValues::for_path::<T>(path, path))
+        .collect::<Result<_, _>>()?;
+    Ok((paths, columns))
+}
+
+/// Build one `T` per row of `df`, the reverse of [`DataFrameBuilder`]
+///
+/// Columns are named after the paths of the fields they hold, as in `name`
+/// or `members[0].name`. See [`FromPaths`] for how fields are read; a
+/// required field without column gives `MissingPath`, and a value of the
+/// wrong type gives `InvalidValue`.
+pub fn from_dataframe<T: FromPaths>(df: &DataFrame) -> Result<Vec<T>, StructPathError> {
+    let columns = DataFrameColumns::new(df)?;
+    (0..df.height())
+        .map(|row| {
+            let source = DataFrameRow {
+                columns: &columns,
+                row,
+            };
+            T::from_paths(&source, &[])
+        })
+        .collect()
+}
+
+fn nanos_per(unit: TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Nanoseconds => 1,
+        TimeUnit::Microseconds => 1_000,
+        TimeUnit::Milliseconds => 1_000_000,
+    }
+}
+
+/// The columns of a `DataFrame`, indexed by the paths they are named after
+struct DataFrameColumns<'a> {
+    columns: Vec<&'a Column>,
+    /// The column named after each path
+    by_path: HashMap<Vec<PathComponent>, usize>,
+    /// The columns at or below each path
+    below: HashMap<Vec<PathComponent>, Vec<usize>>,
+    /// Length of each list, one past the largest index of its columns
+    list_lens: HashMap<Vec<PathComponent>, usize>,
+}
+
+impl<'a> DataFrameColumns<'a> {
+    fn new(df: &'a DataFrame) -> Result<Self, StructPathError> {
+        let mut columns = DataFrameColumns {
+            columns: Vec::new(),
+            by_path: HashMap::new(),
+            below: HashMap::new(),
+            list_lens: HashMap::new(),
+        };
+        for (index, column) in df.get_columns().iter().enumerate() {
+            let components = Path::from_str(column.name())?.components;
+            for end in 1..=components.len() {
+                let prefix = &components[..end];
+                columns
+                    .below
+                    .entry(prefix.to_vec())
+                    .or_default()
+                    .push(index);
+                if let Some((PathComponent::ArrayIndex(field, i), parent)) = prefix.split_last() {
+                    let mut list = parent.to_vec();
+                    list.push(PathComponent::Field(field.clone()));
+                    let len = columns.list_lens.entry(list).or_default();
+                    *len = (*len).max(i + 1);
+                }
+            }
+            columns.by_path.insert(components, index);
+            columns.columns.push(column);
+        }
+        Ok(columns)
+    }
+}
+
+/// A row of a `DataFrame`
+struct DataFrameRow<'a> {
+    columns: &'a DataFrameColumns<'a>,
+    row: usize,
+}
+
+impl PathSource for DataFrameRow<'_> {
+    fn value_at(&self, path: &[PathComponent]) -> Result<Value, StructPathError> {
+        let column = self
+            .columns
+            .by_path
+            .get(path)
+            .map(|&index| self.columns.columns[index])
+            .ok_or_else(|| StructPathError::MissingPath(components_to_string(path)))?;
+        let value = column.get(self.row).map_err(polars_error)?;
+        let invalid = |source| StructPathError::InvalidValue {
+            path: components_to_string(path),
+            source: Box::new(source),
+        };
+        // Date-times and durations in `unit`, which must fit in nanoseconds
+        let nanos = |value: i64, unit| {
+            value.checked_mul(nanos_per(unit)).ok_or_else(|| {
+                invalid(StructPathError::Polars(format!(
+                    "{value} {unit} is out of the nanosecond range"
+                )))
+            })
+        };
+        let value = match value {
+            AnyValue::Null => Value::Option(None),
+            AnyValue::String(value) => Value::String(value.to_string()),
+            AnyValue::StringOwned(value) => Value::String(value.to_string()),
+            AnyValue::Boolean(value) => Value::Boolean(value),
+            AnyValue::Int8(value) => Value::Integer(value.into()),
+            AnyValue::Int16(value) => Value::Integer(value.into()),
+            AnyValue::Int32(value) => Value::Integer(value.into()),
+            AnyValue::Int64(value) => Value::Integer(value),
+            AnyValue::UInt8(value) => Value::Integer(value.into()),
+            AnyValue::UInt16(value) => Value::Integer(value.into()),
+            AnyValue::UInt32(value) => Value::Integer(value.into()),
+            AnyValue::UInt64(value) => match i64::try_from(value) {
+                Ok(value) => Value::Integer(value),
+                Err(_) => Value::BigUint(value.into()),
+            },
+            AnyValue::Float32(value) => Value::Float(value.into()),
+            AnyValue::Float64(value) => Value::Float(value),
+            AnyValue::Int128(value) => Value::BigInt(value),
+            AnyValue::Decimal(mantissa, scale) => Value::Decimal {
+                mantissa,
+                scale: scale as u32,
+            },
+            AnyValue::Datetime(value, unit, _) | AnyValue::DatetimeOwned(value, unit, _) => {
+                Value::from_nanos(nanos(value, unit)?, true)
+            }
+            AnyValue::Date(days) => Value::Date(days),
+            AnyValue::Duration(value, unit) => Value::from_nanos(nanos(value, unit)?, false),
+            AnyValue::Binary(value) => Value::Bytes(value.to_vec()),
+            AnyValue::BinaryOwned(value) => Value::Bytes(value),
+            value => return Err(invalid(StructPathError::TypeMismatch {
+                expected:
+                    "String, Integer, Float, Boolean, Decimal, Datetime, Date, Duration or Binary"
+                        .to_string(),
+                actual: value.dtype().to_string(),
+            })),
+        };
+        Ok(value)
+    }
+
+    fn is_present(&self, path: &[PathComponent]) -> bool {
+        self.columns.below.get(path).is_some_and(|below| {
+            below
+                .iter()
+                .any(|&index| self.is_valid(self.columns.columns[index]))
+        })
+    }
+
+    /// Null elements count, as in the `HashMap` source: the builder fails on
+    /// indices beyond the lists of a record, so each row has every indexed
+    /// element
+    fn list_len(&self, path: &[PathComponent]) -> usize {
+        self.columns
+            .list_lens
+            .get(path)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+impl DataFrameRow<'_> {
+    /// Whether `column` has a non-null value in the row
+    fn is_valid(&self, column: &Column) -> bool {
+        column.get(self.row).is_ok_and(|value| !value.is_null())
+    }
+}
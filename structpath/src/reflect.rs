@@ -0,0 +1,435 @@
+use crate::{
+    document,
+    error::StructPathError,
+    path::{field_number, Path, PathComponent},
+    schema::{field_info_in, Schema},
+    traits::StructPath,
+    value::Value,
+};
+use prost_reflect::{
+    Cardinality, DescriptorPool, DynamicMessage, FieldDescriptor, Kind, MessageDescriptor,
+    ReflectMessage, Value as ReflectValue,
+};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use structpath_types::{FieldInfo, FieldType, FieldsInfo, ProtoField, ProtoLabel};
+
+/// Load the message descriptors of the file at `path`
+///
+/// `.proto` files are compiled with `protoc`, taken from the `PROTOC`
+/// environment variable or the `PATH`, with the directory of the file as
+/// include path. Other files must hold a `FileDescriptorSet`, as written by
+/// `protoc --descriptor_set_out`.
+///
+/// ```ignore
+/// let pool = structpath::load_descriptor_pool("schemas/user.proto")?;
+/// let descriptor = pool.get_message_by_name("sample.User").unwrap();
+/// let user = DynamicMessage::decode(descriptor, payload)?;
+/// let name = user.get_as::<String>("name")?;
+/// ```
+pub fn load_descriptor_pool(
+    path: impl AsRef<std::path::Path>,
+) -> Result<DescriptorPool, StructPathError> {
+    let path = path.as_ref();
+    let bytes = if path
+        .extension()
+        .is_some_and(|extension| extension == "proto")
+    {
+        compile_proto(path)?
+    } else {
+        std::fs::read(path).map_err(|e| descriptor_error(path, e))?
+    };
+    DescriptorPool::decode(bytes.as_slice()).map_err(|e| descriptor_error(path, e))
+}
+
+fn descriptor_error(path: &std::path::Path, error: impl std::fmt::Display) -> StructPathError {
+    StructPathError::Descriptor(format!("{}: {error}", path.display()))
+}
+
+/// Run `protoc` on `path`, giving the encoded `FileDescriptorSet`
+fn compile_proto(path: &std::path::Path) -> Result<Vec<u8>, StructPathError> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let output = std::env::temp_dir().join(format!(
+        "structpath-{}-{}.bin",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let include = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => std::path::Path::new("."),
+    };
+    let protoc = std::env::var_os("PROTOC").unwrap_or_else(|| "protoc".into());
+    let result = Command::new(protoc)
+        .arg("--include_imports")
+        .arg(format!("--descriptor_set_out={}", output.display()))
+        .arg(format!("--proto_path={}", include.display()))
+        .arg(path)
+        .output()
+        .map_err(|e| descriptor_error(path, format!("cannot run protoc: {e}")))
+        .and_then(|run| {
+            if run.status.success() {
+                std::fs::read(&output).map_err(|e| descriptor_error(path, e))
+            } else {
+                Err(descriptor_error(
+                    path,
+                    String::from_utf8_lossy(&run.stderr).trim(),
+                ))
+            }
+        });
+    let _ = std::fs::remove_file(&output);
+    result
+}
+
+/// Describe the fields of the messages of `descriptor`, as
+/// [`StructInfo::get_fields_info`](crate::StructInfo::get_fields_info) does
+/// for Rust types
+///
+/// Integers and enums are `Integer`s, messages with presence and proto3
+/// `optional` fields are `Option`s and repeated fields are `Vec`s. Maps are
+/// `Unknown`. As the schema is only known at runtime, `nested`
+/// is always `None`: use [`message_field_info`] to describe nested fields.
+/// Unlike prost-generated messages, the members of a oneof are listed as
+/// separate fields.
+pub fn message_fields_info(descriptor: &MessageDescriptor) -> FieldsInfo {
+    FieldsInfo {
+        name: descriptor.full_name().to_string(),
+        fields: descriptor
+            .fields()
+            .map(|field| FieldInfo {
+                name: field.name().to_string(),
+                r#type: field_type(&field),
+                nested: None,
+                proto: Some(proto_field(&field)),
+            })
+            .collect(),
+        proto_name: Some(descriptor.full_name().to_string()),
+    }
+}
+
+/// Describe the field at `path` in the messages of `descriptor`, as
+/// [`StructInfo::get_field_info`](crate::StructInfo::get_field_info) does for
+/// Rust types
+pub fn message_field_info(
+    descriptor: &MessageDescriptor,
+    path: &Path,
+) -> Result<FieldInfo, StructPathError> {
+    field_info_in(descriptor, &path.components)
+}
+
+impl Schema for MessageDescriptor {
+    fn info(&self) -> FieldsInfo {
+        message_fields_info(self)
+    }
+
+    fn nested(&self, field: &FieldInfo) -> Option<Self> {
+        self.get_field_by_name(&field.name)?
+            .kind()
+            .as_message()
+            .cloned()
+    }
+
+    fn describe(&self) -> FieldInfo {
+        FieldInfo {
+            name: self.full_name().to_string(),
+            r#type: FieldType::StructPath,
+            nested: None,
+            proto: None,
+        }
+    }
+}
+
+fn field_type(field: &FieldDescriptor) -> FieldType {
+    if field.is_map() {
+        return FieldType::Unknown;
+    }
+    let field_type = match field.kind() {
+        Kind::Double | Kind::Float => FieldType::Float,
+        Kind::Int32
+        | Kind::Int64
+        | Kind::Uint32
+        | Kind::Uint64
+        | Kind::Sint32
+        | Kind::Sint64
+        | Kind::Fixed32
+        | Kind::Fixed64
+        | Kind::Sfixed32
+        | Kind::Sfixed64
+        | Kind::Enum(_) => FieldType::Integer,
+        Kind::Bool => FieldType::Boolean,
+        Kind::String => FieldType::String,
+        Kind::Bytes => FieldType::Bytes,
+        Kind::Message(message) => match well_known(&message) {
+            Some(WellKnown::Timestamp) => FieldType::DateTime,
+            Some(WellKnown::Duration) => FieldType::Duration,
+            Some(WellKnown::Wrapper(field_type)) => field_type,
+            Some(WellKnown::List) => FieldType::Unknown,
+            Some(WellKnown::Struct | WellKnown::Value | WellKnown::Any) | None => {
+                FieldType::StructPath
+            }
+        },
+    };
+    if field.is_list() {
+        FieldType::Vec(Box::new(field_type))
+    } else if field.supports_presence() {
+        FieldType::Option(Box::new(field_type))
+    } else {
+        field_type
+    }
+}
+
+fn proto_field(field: &FieldDescriptor) -> ProtoField {
+    let r#type = match field.kind() {
+        _ if field.is_map() => "map",
+        _ if field.is_group() => "group",
+        Kind::Double => "double",
+        Kind::Float => "float",
+        Kind::Int32 => "int32",
+        Kind::Int64 => "int64",
+        Kind::Uint32 => "uint32",
+        Kind::Uint64 => "uint64",
+        Kind::Sint32 => "sint32",
+        Kind::Sint64 => "sint64",
+        Kind::Fixed32 => "fixed32",
+        Kind::Fixed64 => "fixed64",
+        Kind::Sfixed32 => "sfixed32",
+        Kind::Sfixed64 => "sfixed64",
+        Kind::Bool => "bool",
+        Kind::String => "string",
+        Kind::Bytes => "bytes",
+        Kind::Message(_) => "message",
+        Kind::Enum(_) => "enum",
+    };
+    let label = if field.is_list() || field.is_map() {
+        ProtoLabel::Repeated
+    } else if field.cardinality() == Cardinality::Required {
+        ProtoLabel::Required
+    } else if field.supports_presence() {
+        ProtoLabel::Optional
+    } else {
+        ProtoLabel::Singular
+    };
+    ProtoField {
+        number: field.number(),
+        r#type: r#type.to_string(),
+        label,
+    }
+}
+
+/// The protobuf well-known types, represented as native values
+enum WellKnown {
+    Timestamp,
+    Duration,
+    /// Wrappers such as `StringValue`, holding a value of this type
+    Wrapper(FieldType),
+    /// `Struct`, navigated like a JSON object
+    Struct,
+    /// `Value`, navigated like a JSON value
+    Value,
+    /// `ListValue`, a list of `Value`s
+    List,
+    /// `Any`, navigated as the message it packs
+    Any,
+}
+
+fn well_known(message: &MessageDescriptor) -> Option<WellKnown> {
+    let wrapped = match message.full_name() {
+        "google.protobuf.Timestamp" => return Some(WellKnown::Timestamp),
+        "google.protobuf.Duration" => return Some(WellKnown::Duration),
+        "google.protobuf.Struct" => return Some(WellKnown::Struct),
+        "google.protobuf.Value" => return Some(WellKnown::Value),
+        "google.protobuf.ListValue" => return Some(WellKnown::List),
+        "google.protobuf.Any" => return Some(WellKnown::Any),
+        "google.protobuf.DoubleValue" | "google.protobuf.FloatValue" => FieldType::Float,
+        "google.protobuf.Int64Value"
+        | "google.protobuf.UInt64Value"
+        | "google.protobuf.Int32Value"
+        | "google.protobuf.UInt32Value" => FieldType::Integer,
+        "google.protobuf.BoolValue" => FieldType::Boolean,
+        "google.protobuf.StringValue" => FieldType::String,
+        "google.protobuf.BytesValue" => FieldType::Bytes,
+        _ => return None,
+    };
+    Some(WellKnown::Wrapper(wrapped))
+}
+
+/// Convert a message, mapping the well-known types to native values:
+/// timestamps and durations, the scalar of wrappers, `Struct`s and `Value`s
+/// as documents and `Any`s as the message they pack when its type is in the
+/// descriptor pool
+fn message_value(message: &DynamicMessage) -> Value {
+    let descriptor = message.descriptor();
+    let converted = match well_known(&descriptor) {
+        None => None,
+        Some(WellKnown::Timestamp) => message
+            .transcode_to::<prost_types::Timestamp>()
+            .ok()
+            .map(Value::from),
+        Some(WellKnown::Duration) => message
+            .transcode_to::<prost_types::Duration>()
+            .ok()
+            .map(Value::from),
+        Some(WellKnown::Wrapper(_)) => message.get_field_by_number(1).map(|value| to_value(&value)),
+        Some(WellKnown::Struct) => message
+            .transcode_to::<prost_types::Struct>()
+            .ok()
+            .map(Value::from_struct),
+        Some(WellKnown::Value) => message
+            .transcode_to::<prost_types::Value>()
+            .ok()
+            .map(|value| document::to_value(&value)),
+        Some(WellKnown::List) => message
+            .transcode_to::<prost_types::ListValue>()
+            .ok()
+            .map(|list| Value::list(list.values, document::to_value)),
+        Some(WellKnown::Any) => message.transcode_to::<prost_types::Any>().ok().map(|any| {
+            let type_name = any.type_url.rsplit('/').next().unwrap_or_default();
+            descriptor
+                .parent_pool()
+                .get_message_by_name(type_name)
+                .and_then(|packed| DynamicMessage::decode(packed, any.value.as_slice()).ok())
+                .map_or_else(|| Value::from_struct(any), Value::from_struct)
+        }),
+    };
+    converted.unwrap_or_else(|| Value::from_struct(message.clone()))
+}
+
+/// Convert a field value: integers widen to `i64` (`u64` beyond its range
+/// become `BigUint`), enums are their number, maps stay boxed and messages
+/// are converted by [`message_value`]
+fn to_value(value: &ReflectValue) -> Value {
+    match value {
+        ReflectValue::Bool(value) => Value::Boolean(*value),
+        ReflectValue::I32(value) => Value::Integer((*value).into()),
+        ReflectValue::I64(value) => Value::Integer(*value),
+        ReflectValue::U32(value) => Value::Integer((*value).into()),
+        ReflectValue::U64(value) => Value::from(*value),
+        ReflectValue::F32(value) => Value::Float((*value).into()),
+        ReflectValue::F64(value) => Value::Float(*value),
+        ReflectValue::String(value) => Value::String(value.clone()),
+        ReflectValue::Bytes(value) => Value::Bytes(value.to_vec()),
+        ReflectValue::EnumNumber(value) => Value::Integer((*value).into()),
+        ReflectValue::Message(message) => message_value(message),
+        ReflectValue::List(items) => Value::list(items.clone(), to_value),
+        ReflectValue::Map(map) => Value::Boxed(Box::new(map.clone())),
+    }
+}
+
+/// Whether `field` can be accessed with a component that is `indexed`, and
+/// followed by more components when `nested`
+fn accepts(field: &FieldDescriptor, indexed: bool, nested: bool) -> bool {
+    if indexed && !field.is_list() {
+        return false;
+    }
+    let navigable = field.kind().as_message().is_some_and(|message| {
+        matches!(
+            well_known(message),
+            None | Some(WellKnown::Struct | WellKnown::Value | WellKnown::Any)
+        )
+    });
+    !nested || (navigable && !field.is_map() && (indexed || !field.is_list()))
+}
+
+/// Fields are resolved by name or `#5` field number through the message
+/// descriptor, with the same values as for a prost-generated struct: fields
+/// with presence are `Value::Option`s, repeated fields are lists and messages
+/// are boxed `DynamicMessage`s, but for the well-known types
+/// (see [`message_value`])
+impl StructPath for DynamicMessage {
+    fn get_value_by_components(
+        &self,
+        components: &[PathComponent],
+    ) -> Result<Value, StructPathError> {
+        let Some(component) = components.first() else {
+            return Ok(Value::from_struct(self.clone()));
+        };
+        let (name, index) = match component {
+            PathComponent::Field(name) => (name, None),
+            PathComponent::ArrayIndex(name, index) => (name, Some(*index)),
+        };
+        let nested = components.len() > 1;
+
+        let descriptor = self.descriptor();
+        let field = match field_number(name) {
+            Some(number) => descriptor.get_field(number),
+            None => descriptor.get_field_by_name(name),
+        };
+        let field = field
+            .filter(|field| accepts(field, index.is_some(), nested))
+            .ok_or_else(|| {
+                let valid_fields: Vec<String> = descriptor
+                    .fields()
+                    .filter(|field| accepts(field, index.is_some(), nested))
+                    .map(|field| field.name().to_string())
+                    .collect();
+                let valid_fields: Vec<&str> = valid_fields.iter().map(String::as_str).collect();
+                StructPathError::field_not_found(
+                    name.clone(),
+                    components,
+                    descriptor.full_name(),
+                    &valid_fields,
+                )
+            })?;
+
+        if field.supports_presence() && !self.has_field(&field) {
+            return Ok(Value::Option(None));
+        }
+        let value = self.get_field(&field);
+        let value = match index {
+            None => value.as_ref(),
+            Some(index) => {
+                let items = value.as_list().unwrap_or_default();
+                items.get(index).ok_or_else(|| {
+                    StructPathError::index_out_of_bounds(
+                        name.clone(),
+                        index,
+                        items.len(),
+                        components,
+                        descriptor.full_name(),
+                    )
+                })?
+            }
+        };
+
+        if !nested {
+            let value = to_value(value);
+            return Ok(if field.supports_presence() {
+                Value::Option(Some(Box::new(value)))
+            } else {
+                value
+            });
+        }
+        let ReflectValue::Message(message) = value else {
+            unreachable!("nested components only go through message fields")
+        };
+        if well_known(&message.descriptor()).is_none() {
+            return message
+                .get_value_by_components(&components[1..])
+                .map_err(|e| e.nested(components));
+        }
+        match message_value(message) {
+            Value::Option(None) => Ok(Value::Option(None)),
+            Value::Boxed(boxed) => match (*boxed).as_struct_path() {
+                Some(record) => record
+                    .get_value_by_components(&components[1..])
+                    .map_err(|e| e.nested(components)),
+                None => Err(StructPathError::TypeMismatch {
+                    expected: "struct".to_string(),
+                    actual: boxed.type_name().to_string(),
+                }),
+            },
+            value => Err(StructPathError::TypeMismatch {
+                expected: "struct".to_string(),
+                actual: value.variant_name().to_string(),
+            }),
+        }
+    }
+
+    fn value_fields(&self) -> Vec<String> {
+        self.descriptor()
+            .fields()
+            .filter(|field| field_type(field) != FieldType::Unknown)
+            .map(|field| field.name().to_string())
+            .collect()
+    }
+}
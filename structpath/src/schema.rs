@@ -0,0 +1,246 @@
+use crate::{
+    error::StructPathError,
+    path::{field_number, PathComponent},
+};
+use structpath_types::{FieldInfo, FieldType, FieldsInfo};
+
+/// The type reached by accessing a field of type `field_type` with a single
+/// path component
+///
+/// Returns `None` when the component cannot be applied: indexing a field that
+/// is not a `Vec`, or going through a field that is not a nested struct when
+/// the component is not the `last` one. The flag tells whether an `Option`
+/// was crossed on the way.
+fn step(field_type: &FieldType, indexed: bool, last: bool) -> Option<(&FieldType, bool)> {
+    let mut optional = false;
+    let mut field_type = field_type;
+    if let FieldType::Option(inner) = field_type {
+        optional = true;
+        field_type = inner;
+    }
+    if indexed {
+        match field_type {
+            FieldType::Vec(elem) => field_type = elem,
+            _ => return None,
+        }
+        if let FieldType::Option(inner) = field_type {
+            optional = true;
+            field_type = inner;
+        }
+    }
+    if !last && *field_type != FieldType::StructPath {
+        return None;
+    }
+    Some((field_type, optional))
+}
+
+/// Field descriptions that can be walked down nested structs, whether known at
+/// compile time through [`StructInfo`](crate::StructInfo) or at runtime
+pub(crate) trait Schema: Clone {
+    fn info(&self) -> FieldsInfo;
+
+    /// The schema of the struct nested in `field`, one of the fields of `info`
+    fn nested(&self, field: &FieldInfo) -> Option<Self>;
+
+    /// Describe the struct itself, as the field at the empty path
+    fn describe(&self) -> FieldInfo;
+}
+
+impl Schema for fn() -> FieldsInfo {
+    fn info(&self) -> FieldsInfo {
+        self()
+    }
+
+    fn nested(&self, field: &FieldInfo) -> Option<Self> {
+        field.nested
+    }
+
+    fn describe(&self) -> FieldInfo {
+        FieldInfo {
+            name: self().name,
+            r#type: FieldType::StructPath,
+            nested: Some(*self),
+            proto: None,
+        }
+    }
+}
+
+/// Whether `name`, from a path component, is the name of `field` or its
+/// `#5` protobuf field number
+fn is_named(field: &FieldInfo, name: &str) -> bool {
+    match (field_number(name), &field.proto) {
+        (Some(number), Some(proto)) => proto.number == number,
+        _ => field.name == name,
+    }
+}
+
+/// Describe the field at `components` in the struct described by `root`
+///
+/// The returned type is the one of the value at the path: indexing a `Vec`
+/// gives its element type, and anything reached through an `Option` is
+/// optional. The empty path describes the root struct itself.
+pub(crate) fn field_info_at(
+    root: fn() -> FieldsInfo,
+    components: &[PathComponent],
+) -> Result<FieldInfo, StructPathError> {
+    field_info_in(&root, components)
+}
+
+/// Same as [`field_info_at`], in any [`Schema`]
+pub(crate) fn field_info_in<S: Schema>(
+    root: &S,
+    components: &[PathComponent],
+) -> Result<FieldInfo, StructPathError> {
+    let mut schema = root.clone();
+    let mut optional = false;
+    for (depth, component) in components.iter().enumerate() {
+        let info = schema.info();
+        let (name, indexed) = match component {
+            PathComponent::Field(name) => (name, false),
+            PathComponent::ArrayIndex(name, _) => (name, true),
+        };
+        let last = depth + 1 == components.len();
+
+        let found = info.fields.iter().find(|field| is_named(field, name));
+        let Some((field, (field_type, crossed_option))) = found
+            .and_then(|field| step(&field.r#type, indexed, last).map(|reached| (field, reached)))
+        else {
+            let valid_fields: Vec<&str> = info
+                .fields
+                .iter()
+                .filter(|field| step(&field.r#type, indexed, last).is_some())
+                .map(|field| field.name.as_str())
+                .collect();
+            return Err(StructPathError::field_not_found(
+                name.clone(),
+                &components[depth..],
+                &info.name,
+                &valid_fields,
+            )
+            .nested_at(components, depth));
+        };
+        optional |= crossed_option;
+
+        if last {
+            let r#type = if optional {
+                FieldType::Option(Box::new(field_type.clone()))
+            } else {
+                field_type.clone()
+            };
+            return Ok(FieldInfo {
+                name: field.name.clone(),
+                r#type,
+                nested: field.nested,
+                proto: field.proto.clone(),
+            });
+        }
+        schema = schema
+            .nested(field)
+            .ok_or_else(|| StructPathError::TypeMismatch {
+                expected: "StructInfo".to_string(),
+                actual: field.name.clone(),
+            })?;
+    }
+
+    Ok(root.describe())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::path::Path;
+
+    fn pet_info() -> FieldsInfo {
+        FieldsInfo {
+            name: "Pet".to_string(),
+            fields: vec![
+                FieldInfo {
+                    name: "name".to_string(),
+                    r#type: FieldType::String,
+                    nested: None,
+                    proto: None,
+                },
+                FieldInfo {
+                    name: "tags".to_string(),
+                    r#type: FieldType::Vec(Box::new(FieldType::String)),
+                    nested: None,
+                    proto: None,
+                },
+            ],
+            proto_name: None,
+        }
+    }
+
+    fn user_info() -> FieldsInfo {
+        FieldsInfo {
+            name: "User".to_string(),
+            fields: vec![
+                FieldInfo {
+                    name: "age".to_string(),
+                    r#type: FieldType::Integer,
+                    nested: None,
+                    proto: None,
+                },
+                FieldInfo {
+                    name: "parent".to_string(),
+                    r#type: FieldType::Option(Box::new(FieldType::StructPath)),
+                    nested: Some(user_info),
+                    proto: None,
+                },
+                FieldInfo {
+                    name: "pets".to_string(),
+                    r#type: FieldType::Vec(Box::new(FieldType::StructPath)),
+                    nested: Some(pet_info),
+                    proto: None,
+                },
+            ],
+            proto_name: None,
+        }
+    }
+
+    fn resolve(path: &str) -> Result<FieldInfo, StructPathError> {
+        field_info_at(user_info, &Path::from_str(path).unwrap().components)
+    }
+
+    #[test]
+    fn test_field_info_at() {
+        assert_eq!(resolve("age").unwrap().r#type, FieldType::Integer);
+        assert_eq!(
+            resolve("parent.parent.age").unwrap().r#type,
+            FieldType::Option(Box::new(FieldType::Integer))
+        );
+        assert_eq!(
+            resolve("pets[0].tags").unwrap().r#type,
+            FieldType::Vec(Box::new(FieldType::String))
+        );
+        assert_eq!(
+            resolve("pets[0].tags[1]").unwrap().r#type,
+            FieldType::String
+        );
+
+        let pets = resolve("pets").unwrap();
+        assert_eq!(pets.r#type, FieldType::Vec(Box::new(FieldType::StructPath)));
+        assert_eq!(pets.nested.unwrap()().name, "Pet");
+
+        let root = field_info_at(user_info, &[]).unwrap();
+        assert_eq!(root.name, "User");
+        assert_eq!(root.r#type, FieldType::StructPath);
+    }
+
+    #[test]
+    fn test_field_info_at_not_found() {
+        let err = resolve("pets[0].nmae").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Field not found: nmae in Pet at component 1 of `pets[0].nmae`, did you mean `name`?"
+        );
+
+        match resolve("age[0]").unwrap_err() {
+            StructPathError::FieldNotFound { valid_fields, .. } => {
+                assert_eq!(valid_fields, vec!["pets"]);
+            }
+            other => panic!("Expected FieldNotFound, got {other:?}"),
+        }
+        assert!(resolve("age.name").is_err());
+    }
+}
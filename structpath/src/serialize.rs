@@ -0,0 +1,188 @@
+use crate::{
+    error::StructPathError,
+    path::PathComponent,
+    traits::{StructInfo, StructPath},
+    value::Value,
+};
+use serde::ser::{Error, SerializeMap, SerializeSeq};
+use serde::{Serialize, Serializer};
+use structpath_types::FieldType;
+
+/// Values serialize as the data they hold: options as `null` or their
+/// content, lists as sequences and nested structs as maps of their
+/// [`value_fields`](StructPath::value_fields). Date-times and durations are
+/// strings, as in the protobuf JSON mapping: `2024-05-01T12:00:00.500Z` and
+/// `-1.500s`, and dates are `2024-05-01`. Decimals are strings too, such as
+/// `-0.005`, to keep their precision. Bytes go through `serialize_bytes`,
+/// which `serde_json` renders as an array of numbers.
+///
+/// Boxed values of other types, and lists whose elements cannot be converted
+/// to `Value`, fail to serialize.
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::String(value) => serializer.serialize_str(value),
+            Value::Integer(value) => serializer.serialize_i64(*value),
+            Value::Float(value) => serializer.serialize_f64(*value),
+            Value::Boolean(value) => serializer.serialize_bool(*value),
+            Value::Decimal { mantissa, scale } => {
+                serializer.serialize_str(&format_decimal(*mantissa, *scale))
+            }
+            Value::BigInt(value) => serializer.serialize_i128(*value),
+            Value::BigUint(value) => serializer.serialize_u128(*value),
+            Value::DateTime { seconds, nanos } => {
+                serializer.serialize_str(&format_datetime(*seconds, *nanos))
+            }
+            Value::Date(days) => serializer.serialize_str(&format_date((*days).into())),
+            Value::Duration { seconds, nanos } => {
+                serializer.serialize_str(&format_duration(*seconds, *nanos))
+            }
+            Value::Bytes(value) => serializer.serialize_bytes(value),
+            Value::Option(None) => serializer.serialize_none(),
+            Value::Option(Some(value)) => serializer.serialize_some(value.as_ref()),
+            Value::Vec(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for index in 0..list.len() {
+                    let element = list.element(index).ok_or_else(|| {
+                        S::Error::custom(format!(
+                            "cannot serialize the elements of {}",
+                            list.type_name()
+                        ))
+                    })?;
+                    seq.serialize_element(&element)?;
+                }
+                seq.end()
+            }
+            // `Box<dyn BoxedValue>` is itself a `BoxedValue`, go through the box
+            Value::Boxed(boxed) => match (**boxed).as_struct_path() {
+                Some(record) => serialize_map(record, &record.value_fields(), serializer),
+                None => Err(S::Error::custom(format!(
+                    "cannot serialize {}",
+                    boxed.type_name()
+                ))),
+            },
+        }
+    }
+}
+
+/// Plain rendering of `mantissa` × 10^-`scale`, keeping trailing zeros
+fn format_decimal(mantissa: i128, scale: u32) -> String {
+    let sign = if mantissa < 0 { "-" } else { "" };
+    let digits = mantissa.unsigned_abs().to_string();
+    let scale = scale as usize;
+    if scale == 0 {
+        return format!("{sign}{digits}");
+    }
+    let digits = format!("{digits:0>width$}", width = scale + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - scale);
+    format!("{sign}{whole}.{fraction}")
+}
+
+/// Fractional seconds with 3, 6 or 9 digits, or none for whole seconds
+fn fraction(nanos: u32) -> String {
+    match nanos {
+        0 => String::new(),
+        _ if nanos % 1_000_000 == 0 => format!(".{:03}", nanos / 1_000_000),
+        _ if nanos % 1_000 == 0 => format!(".{:06}", nanos / 1_000),
+        _ => format!(".{nanos:09}"),
+    }
+}
+
+/// RFC 3339 rendering of an instant in UTC
+fn format_datetime(seconds: i64, nanos: u32) -> String {
+    let (days, time) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    format!(
+        "{}T{:02}:{:02}:{:02}{}Z",
+        format_date(days),
+        time / 3_600,
+        time / 60 % 60,
+        time % 60,
+        fraction(nanos)
+    )
+}
+
+/// ISO 8601 rendering of a date, given as days since the Unix epoch
+fn format_date(days: i64) -> String {
+    // Civil date of a day count since the epoch, from Howard Hinnant's
+    // `civil_from_days`
+    let days = days + 719_468;
+    let (era, day_of_era) = (days.div_euclid(146_097), days.rem_euclid(146_097));
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Seconds rendering of a duration, as in `-1.500s`
+fn format_duration(seconds: i64, nanos: i32) -> String {
+    let sign = if seconds < 0 || nanos < 0 { "-" } else { "" };
+    format!(
+        "{sign}{}{}s",
+        seconds.unsigned_abs(),
+        fraction(nanos.unsigned_abs())
+    )
+}
+
+fn serialize_map<S: Serializer>(
+    record: &dyn StructPath,
+    fields: &[impl AsRef<str>],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(fields.len()))?;
+    for field in fields {
+        let field = field.as_ref();
+        let value = record
+            .get_value_by_components(&[PathComponent::Field(field.to_string())])
+            .map_err(S::Error::custom)?;
+        map.serialize_entry(field, &value)?;
+    }
+    map.end()
+}
+
+fn is_serializable(field_type: &FieldType) -> bool {
+    match field_type {
+        FieldType::Option(inner) | FieldType::Vec(inner) => is_serializable(inner),
+        FieldType::Unknown => false,
+        _ => true,
+    }
+}
+
+/// Serialize `record` as a map of the fields enumerated by [`StructInfo`]
+///
+/// Fields of types `Value` cannot represent are left out. Usable with
+/// `#[serde(serialize_with = "structpath::serialize_fields")]`.
+pub fn serialize_fields<T, S>(record: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: StructPath + StructInfo,
+    S: Serializer,
+{
+    let info = T::get_fields_info();
+    let fields: Vec<&str> = info
+        .fields
+        .iter()
+        .filter(|field| is_serializable(&field.r#type))
+        .map(|field| field.name.as_str())
+        .collect();
+    serialize_map(record, &fields, serializer)
+}
+
+/// Render `record` as JSON, see [`serialize_fields`]
+///
+/// ```ignore
+/// let json = structpath::to_json(&user)?;
+/// println!("{json}");
+/// ```
+pub fn to_json<T: StructPath + StructInfo>(
+    record: &T,
+) -> Result<serde_json::Value, StructPathError> {
+    serialize_fields(record, serde_json::value::Serializer)
+        .map_err(|e| StructPathError::Serialize(e.to_string()))
+}
@@ -0,0 +1,281 @@
+//! Date and time types of the `chrono` and `time` crates
+//!
+//! Instants become `Value::DateTime`s, in UTC, calendar dates `Value::Date`s
+//! and spans of time `Value::Duration`s. Date-times without offset, such as
+//! `chrono::NaiveDateTime`, are taken to be in UTC.
+
+use crate::{error::StructPathError, value::Value};
+
+/// A `DateTime` from seconds and nanoseconds that may include a leap second
+#[cfg(feature = "chrono")]
+fn datetime(seconds: i64, nanos: u32) -> Value {
+    Value::DateTime {
+        seconds: seconds + i64::from(nanos / 1_000_000_000),
+        nanos: nanos % 1_000_000_000,
+    }
+}
+
+/// The error of a value beyond the range of `target`
+fn out_of_range(target: &str, value: &Value) -> StructPathError {
+    StructPathError::TypeMismatch {
+        expected: target.to_string(),
+        actual: format!("{value:?}"),
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono {
+    use super::{datetime, out_of_range};
+    use crate::{
+        error::StructPathError,
+        leaf::{path_leaf, PathLeaf},
+        value::{FromValue, Value},
+    };
+    use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc};
+    use structpath_types::FieldType;
+
+    impl<Tz: TimeZone> PathLeaf for DateTime<Tz> {
+        fn field_type() -> FieldType {
+            FieldType::DateTime
+        }
+
+        fn to_value(&self) -> Value {
+            self.clone().into()
+        }
+    }
+
+    path_leaf! {
+        NaiveDateTime => DateTime,
+        NaiveDate => Date,
+        TimeDelta => Duration,
+    }
+
+    impl<Tz: TimeZone> From<DateTime<Tz>> for Value {
+        fn from(value: DateTime<Tz>) -> Self {
+            datetime(value.timestamp(), value.timestamp_subsec_nanos())
+        }
+    }
+
+    impl From<NaiveDateTime> for Value {
+        fn from(value: NaiveDateTime) -> Self {
+            value.and_utc().into()
+        }
+    }
+
+    impl From<NaiveDate> for Value {
+        fn from(value: NaiveDate) -> Self {
+            Value::Date(value.to_epoch_days())
+        }
+    }
+
+    impl From<TimeDelta> for Value {
+        fn from(value: TimeDelta) -> Self {
+            Value::Duration {
+                seconds: value.num_seconds(),
+                nanos: value.subsec_nanos(),
+            }
+        }
+    }
+
+    impl FromValue for DateTime<Utc> {
+        fn from_value(value: Value) -> Result<Self, StructPathError> {
+            let value = value.flatten().ok_or(StructPathError::NullValue)?;
+            let (seconds, nanos) = value.try_as_datetime()?;
+            DateTime::from_timestamp(seconds, nanos)
+                .ok_or_else(|| out_of_range("chrono::DateTime", &value))
+        }
+    }
+
+    /// Read with a zero offset, as date-times are in UTC
+    impl FromValue for DateTime<FixedOffset> {
+        fn from_value(value: Value) -> Result<Self, StructPathError> {
+            DateTime::<Utc>::from_value(value).map(|value| value.fixed_offset())
+        }
+    }
+
+    impl FromValue for NaiveDateTime {
+        fn from_value(value: Value) -> Result<Self, StructPathError> {
+            DateTime::<Utc>::from_value(value).map(|value| value.naive_utc())
+        }
+    }
+
+    impl FromValue for NaiveDate {
+        fn from_value(value: Value) -> Result<Self, StructPathError> {
+            let value = value.flatten().ok_or(StructPathError::NullValue)?;
+            NaiveDate::from_epoch_days(value.try_as_date()?)
+                .ok_or_else(|| out_of_range("chrono::NaiveDate", &value))
+        }
+    }
+
+    impl FromValue for TimeDelta {
+        fn from_value(value: Value) -> Result<Self, StructPathError> {
+            let value = value.flatten().ok_or(StructPathError::NullValue)?;
+            let (seconds, nanos) = value.try_as_duration()?;
+            TimeDelta::try_seconds(seconds)
+                .and_then(|delta| delta.checked_add(&TimeDelta::nanoseconds(nanos.into())))
+                .ok_or_else(|| out_of_range("chrono::TimeDelta", &value))
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time {
+    use super::out_of_range;
+    use crate::{
+        error::StructPathError,
+        leaf::path_leaf,
+        value::{FromValue, Value},
+    };
+    use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime};
+
+    path_leaf! {
+        OffsetDateTime => DateTime,
+        PrimitiveDateTime => DateTime,
+        Date => Date,
+        Duration => Duration,
+    }
+
+    /// Julian day number of the Unix epoch
+    const UNIX_EPOCH_JULIAN_DAY: i32 = 2_440_588;
+
+    impl From<OffsetDateTime> for Value {
+        fn from(value: OffsetDateTime) -> Self {
+            Value::DateTime {
+                seconds: value.unix_timestamp(),
+                nanos: value.nanosecond(),
+            }
+        }
+    }
+
+    impl From<PrimitiveDateTime> for Value {
+        fn from(value: PrimitiveDateTime) -> Self {
+            value.assume_utc().into()
+        }
+    }
+
+    impl From<Date> for Value {
+        fn from(value: Date) -> Self {
+            Value::Date(value.to_julian_day() - UNIX_EPOCH_JULIAN_DAY)
+        }
+    }
+
+    impl From<Duration> for Value {
+        fn from(value: Duration) -> Self {
+            Value::Duration {
+                seconds: value.whole_seconds(),
+                nanos: value.subsec_nanoseconds(),
+            }
+        }
+    }
+
+    /// Read in UTC
+    impl FromValue for OffsetDateTime {
+        fn from_value(value: Value) -> Result<Self, StructPathError> {
+            let value = value.flatten().ok_or(StructPathError::NullValue)?;
+            let (seconds, nanos) = value.try_as_datetime()?;
+            let total = i128::from(seconds) * 1_000_000_000 + i128::from(nanos);
+            OffsetDateTime::from_unix_timestamp_nanos(total)
+                .map_err(|_| out_of_range("time::OffsetDateTime", &value))
+        }
+    }
+
+    impl FromValue for PrimitiveDateTime {
+        fn from_value(value: Value) -> Result<Self, StructPathError> {
+            OffsetDateTime::from_value(value)
+                .map(|value| PrimitiveDateTime::new(value.date(), value.time()))
+        }
+    }
+
+    impl FromValue for Date {
+        fn from_value(value: Value) -> Result<Self, StructPathError> {
+            let value = value.flatten().ok_or(StructPathError::NullValue)?;
+            value
+                .try_as_date()?
+                .checked_add(UNIX_EPOCH_JULIAN_DAY)
+                .and_then(|day| Date::from_julian_day(day).ok())
+                .ok_or_else(|| out_of_range("time::Date", &value))
+        }
+    }
+
+    impl FromValue for Duration {
+        fn from_value(value: Value) -> Result<Self, StructPathError> {
+            let (seconds, nanos) = value
+                .flatten()
+                .ok_or(StructPathError::NullValue)?
+                .try_as_duration()?;
+            Ok(Duration::new(seconds, nanos))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::value::{FromValue, Value};
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() {
+        use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+        let instant = DateTime::<Utc>::from_timestamp(-1, 500_000_000).unwrap();
+        let value = Value::from(instant);
+        assert_eq!(
+            value,
+            Value::DateTime {
+                seconds: -1,
+                nanos: 500_000_000
+            }
+        );
+        assert_eq!(DateTime::<Utc>::from_value(value.clone()).unwrap(), instant);
+        assert_eq!(Value::from(instant.naive_utc()), value);
+
+        let date = NaiveDate::from_ymd_opt(1969, 12, 31).unwrap();
+        assert_eq!(Value::from(date), Value::Date(-1));
+        assert_eq!(NaiveDate::from_value(Value::Date(-1)).unwrap(), date);
+        assert!(NaiveDate::from_value(Value::Date(i32::MAX)).is_err());
+
+        let delta = TimeDelta::milliseconds(-1_500);
+        let value = Value::from(delta);
+        assert_eq!(
+            value,
+            Value::Duration {
+                seconds: -1,
+                nanos: -500_000_000
+            }
+        );
+        assert_eq!(TimeDelta::from_value(value).unwrap(), delta);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_time() {
+        use time::{Date, Duration, Month, OffsetDateTime};
+
+        let instant = OffsetDateTime::from_unix_timestamp_nanos(-500_000_000).unwrap();
+        let value = Value::from(instant);
+        assert_eq!(
+            value,
+            Value::DateTime {
+                seconds: -1,
+                nanos: 500_000_000
+            }
+        );
+        assert_eq!(OffsetDateTime::from_value(value).unwrap(), instant);
+
+        let date = Date::from_calendar_date(2024, Month::May, 1).unwrap();
+        assert_eq!(Value::from(date), Value::Date(19_844));
+        assert_eq!(Date::from_value(Value::Date(19_844)).unwrap(), date);
+        assert!(Date::from_value(Value::Date(i32::MAX)).is_err());
+
+        let duration = Duration::milliseconds(-1_500);
+        let value = Value::from(duration);
+        assert_eq!(
+            value,
+            Value::Duration {
+                seconds: -1,
+                nanos: -500_000_000
+            }
+        );
+        assert_eq!(Duration::from_value(value).unwrap(), duration);
+    }
+}
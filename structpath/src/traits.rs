@@ -1,11 +1,223 @@
-use crate::{error::StructPathError, path::Path, value::Value};
-use structpath_types::FieldsInfo;
+use crate::{
+    batch::{assign, resolve_each, BatchResults, PathBatch, PathTrie},
+    error::StructPathError,
+    path::{Path, PathComponent},
+    schema::field_info_at,
+    value::{FromValue, Value},
+};
+use std::marker::PhantomData;
+use structpath_types::{FieldInfo, FieldsInfo};
+
+/// What [`StructPath::borrow_by_components`] finds at a path
+#[doc(hidden)]
+pub enum Borrowed<'a> {
+    /// A missing `Option`
+    None,
+    Struct(&'a dyn StructPath),
+    /// A list of the given length
+    List(usize),
+    /// Any other value
+    Value,
+}
 
 pub trait StructPath {
-    fn get_value_by_path(&self, path: &Path) -> Result<Value, StructPathError>;
-    fn get_value(&self, path: &str) -> Result<Value, StructPathError>;
+    /// Get the value at the path made of `components`
+    ///
+    /// Nested structs are traversed by passing them the remaining sub-slice,
+    /// so resolving a path does not allocate besides the resulting value.
+    fn get_value_by_components(
+        &self,
+        components: &[PathComponent],
+    ) -> Result<Value, StructPathError>;
+
+    /// Borrow what is at the path made of `components`, telling nested
+    /// structs, the lengths of lists and missing options apart without
+    /// cloning them
+    ///
+    /// Used by the Arrow conversion. The default resolves the value, and
+    /// cannot lend nested structs.
+    #[doc(hidden)]
+    fn borrow_by_components(
+        &self,
+        components: &[PathComponent],
+    ) -> Result<Borrowed<'_>, StructPathError> {
+        match self.get_value_by_components(components)?.flatten() {
+            None => Ok(Borrowed::None),
+            Some(Value::Vec(list)) => Ok(Borrowed::List(list.len())),
+            Some(Value::Boxed(boxed)) if boxed.as_struct_path().is_some() => {
+                Err(StructPathError::NotImplemented)
+            }
+            Some(_) => Ok(Borrowed::Value),
+        }
+    }
+
+    fn get_value_by_path(&self, path: &Path) -> Result<Value, StructPathError> {
+        self.get_value_by_components(&path.components)
+    }
+
+    fn get_value(&self, path: &str) -> Result<Value, StructPathError> {
+        self.get_value_by_path(&Path::from_str(path)?)
+    }
+
+    /// Get the values at many paths in a single traversal
+    ///
+    /// Paths are merged into a prefix tree, so shared prefixes such as
+    /// `admin.*` are resolved once and each nested struct is visited once.
+    /// Results are returned in the order of `paths`.
+    fn get_values(&self, paths: &[Path]) -> Vec<Result<Value, StructPathError>> {
+        self.get_values_from_trie(&PathTrie::new(paths), paths)
+    }
+
+    /// Same as [`StructPath::get_values`], reusing a prepared [`PathBatch`]
+    fn get_values_batch(&self, batch: &PathBatch) -> Vec<Result<Value, StructPathError>> {
+        self.get_values_from_trie(batch.trie(), batch.paths())
+    }
+
+    #[doc(hidden)]
+    fn get_values_from_trie(
+        &self,
+        trie: &PathTrie,
+        paths: &[Path],
+    ) -> Vec<Result<Value, StructPathError>> {
+        let mut out: Vec<_> = paths.iter().map(|_| None).collect();
+        if !trie.leaves().is_empty() {
+            assign(trie, paths, 0, &mut out, self.get_value_by_components(&[]));
+        }
+        self.get_values_by_trie(trie, paths, 0, &mut out);
+        out.into_iter()
+            .map(|result| result.expect("every path is a leaf of the trie"))
+            .collect()
+    }
+
+    /// Visit the children of `trie`, whose components are at `depth` in `paths`
+    ///
+    /// The default resolves each path on its own; the derive overrides it to
+    /// descend into each nested struct once.
+    #[doc(hidden)]
+    fn get_values_by_trie(
+        &self,
+        trie: &PathTrie,
+        paths: &[Path],
+        depth: usize,
+        out: &mut BatchResults,
+    ) {
+        for (_, node) in trie.children() {
+            resolve_each(self, node, paths, depth, out);
+        }
+    }
+
+    /// Names of the fields whose values a [`Value`] can represent: strings,
+    /// numbers, booleans, bytes, nested structs, [`PathLeaf`](crate::PathLeaf)
+    /// types, and options and lists of those
+    ///
+    /// The derive lists them in declaration order, leaving out fields of other
+    /// types, and documents such as JSON objects list their keys. They are the
+    /// fields serialized for nested structs.
+    fn value_fields(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Get the value at `path` converted to `T`
+    ///
+    /// See [`FromValue`] for the conversions applied.
+    fn get_as<T: FromValue>(&self, path: &str) -> Result<T, StructPathError>
+    where
+        Self: Sized,
+    {
+        T::from_value(self.get_value(path)?)
+    }
 }
 
 pub trait StructInfo {
     fn get_fields_info() -> FieldsInfo;
+
+    /// Describe the field at `path`
+    ///
+    /// The type is the one of the value found at `path`: indexing a `Vec`
+    /// gives its element type and fields reached through an `Option` are
+    /// optional.
+    fn get_field_info(path: &Path) -> Result<FieldInfo, StructPathError> {
+        field_info_at(Self::get_fields_info, &path.components)
+    }
+}
+
+/// Pin down the signature of the closure generated by the `path!` macro
+///
+/// Closures returning references to their argument need an explicit
+/// higher-ranked bound to borrow-check; this function provides it.
+#[doc(hidden)]
+pub fn typed_accessor<T, R, F>(accessor: F) -> F
+where
+    R: ?Sized,
+    F: for<'a> Fn(&'a T) -> Result<&'a R, StructPathError>,
+{
+    accessor
+}
+
+/// Choice of the `path!` macro between returning `Copy` values, such as
+/// numbers, and references to the others
+///
+/// As with [`Leaf`](crate::Leaf), `output` called on a `&Accessor<T, R>`
+/// resolves to [`CopyAccessor`] when `R` is `Copy`, and otherwise, through
+/// auto-referencing, to [`RefAccessor`].
+#[doc(hidden)]
+pub struct Accessor<T, R: ?Sized>(PhantomData<fn(&T) -> &R>);
+
+impl<T, R: ?Sized> Accessor<T, R> {
+    /// The choice for the accessor built by [`typed_accessor`]
+    pub fn of<F>(_accessor: &F) -> Self
+    where
+        F: for<'a> Fn(&'a T) -> Result<&'a R, StructPathError>,
+    {
+        Accessor(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait CopyAccessor {
+    fn output(&self) -> ByValue;
+}
+
+impl<T, R: Copy> CopyAccessor for Accessor<T, R> {
+    fn output(&self) -> ByValue {
+        ByValue
+    }
+}
+
+#[doc(hidden)]
+pub trait RefAccessor {
+    fn output(&self) -> ByReference;
+}
+
+impl<T, R: ?Sized> RefAccessor for &Accessor<T, R> {
+    fn output(&self) -> ByReference {
+        ByReference
+    }
+}
+
+#[doc(hidden)]
+pub struct ByValue;
+
+impl ByValue {
+    /// Copy the value `accessor` returns a reference to
+    pub fn finish<T, R, F>(self, accessor: F) -> impl Fn(&T) -> Result<R, StructPathError>
+    where
+        R: Copy,
+        F: for<'a> Fn(&'a T) -> Result<&'a R, StructPathError>,
+    {
+        move |value| accessor(value).copied()
+    }
+}
+
+#[doc(hidden)]
+pub struct ByReference;
+
+impl ByReference {
+    pub fn finish<T, R, F>(self, accessor: F) -> F
+    where
+        R: ?Sized,
+        F: for<'a> Fn(&'a T) -> Result<&'a R, StructPathError>,
+    {
+        accessor
+    }
 }
@@ -1,7 +1,17 @@
+use crate::{error::StructPathError, traits::StructPath};
+use std::cmp::Ordering;
+
 /// Trait for types that are kept boxed inside a `Value`
 pub trait BoxedValue: Send + Sync + 'static {
     fn as_any(&self) -> &dyn std::any::Any;
     fn clone_box(&self) -> Box<dyn BoxedValue>;
+    fn type_name(&self) -> &'static str;
+
+    /// The boxed value as a struct that can be navigated by path, for values
+    /// built with [`Value::from_struct`]
+    fn as_struct_path(&self) -> Option<&dyn StructPath> {
+        None
+    }
 }
 
 impl<T: Clone + Send + Sync + 'static> BoxedValue for T {
@@ -9,6 +19,10 @@ impl<T: Clone + Send + Sync + 'static> BoxedValue for T {
         self
     }
 
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
     fn clone_box(&self) -> Box<dyn BoxedValue> {
         Box::new(self.clone())
     }
@@ -20,15 +34,125 @@ impl std::fmt::Debug for Box<dyn BoxedValue> {
     }
 }
 
+// Calls go through `**self`: `Box<dyn BoxedValue>` is itself a `BoxedValue`
+// through the blanket impl, which would otherwise be picked.
 impl Clone for Box<dyn BoxedValue> {
     fn clone(&self) -> Self {
-        self.clone_box()
+        (**self).clone_box()
     }
 }
 
 impl PartialEq for Box<dyn BoxedValue> {
     fn eq(&self, other: &Self) -> bool {
-        self.as_any().type_id() == other.as_any().type_id()
+        (**self).as_any().type_id() == (**other).as_any().type_id()
+    }
+}
+
+/// A struct boxed by [`Value::from_struct`]
+///
+/// It downcasts to the struct itself, so it is indistinguishable from a
+/// plainly boxed struct besides [`BoxedValue::as_struct_path`].
+struct StructValue<T>(T);
+
+impl<T: StructPath + Clone + Send + Sync + 'static> BoxedValue for StructValue<T> {
+    fn as_any(&self) -> &dyn std::any::Any {
+        &self.0
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    fn clone_box(&self) -> Box<dyn BoxedValue> {
+        Box::new(StructValue(self.0.clone()))
+    }
+
+    fn as_struct_path(&self) -> Option<&dyn StructPath> {
+        Some(&self.0)
+    }
+}
+
+/// Trait for the lists kept boxed inside a `Value::Vec`
+pub trait ListValue: BoxedValue {
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn clone_list(&self) -> Box<dyn ListValue>;
+
+    /// The element at `index` as a `Value`, for lists built with
+    /// [`Value::list`]
+    fn element(&self, _index: usize) -> Option<Value> {
+        None
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> ListValue for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn clone_list(&self) -> Box<dyn ListValue> {
+        Box::new(self.clone())
+    }
+}
+
+/// A list boxed by [`Value::list`], along with the conversion of its elements
+///
+/// It downcasts to the `Vec` itself, like a plainly boxed `Vec`.
+struct ValueList<T> {
+    items: Vec<T>,
+    element: fn(&T) -> Value,
+}
+
+impl<T: Clone + Send + Sync + 'static> BoxedValue for ValueList<T> {
+    fn as_any(&self) -> &dyn std::any::Any {
+        &self.items
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Vec<T>>()
+    }
+
+    fn clone_box(&self) -> Box<dyn BoxedValue> {
+        self.clone_list()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> ListValue for ValueList<T> {
+    fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    fn clone_list(&self) -> Box<dyn ListValue> {
+        Box::new(ValueList {
+            items: self.items.clone(),
+            element: self.element,
+        })
+    }
+
+    fn element(&self, index: usize) -> Option<Value> {
+        self.items.get(index).map(self.element)
+    }
+}
+
+impl std::fmt::Debug for Box<dyn ListValue> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ListValue")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl Clone for Box<dyn ListValue> {
+    fn clone(&self) -> Self {
+        (**self).clone_list()
+    }
+}
+
+impl PartialEq for Box<dyn ListValue> {
+    fn eq(&self, other: &Self) -> bool {
+        (**self).as_any().type_id() == (**other).as_any().type_id()
     }
 }
 
@@ -38,73 +162,1111 @@ pub enum Value {
     Integer(i64),
     Float(f64),
     Boolean(bool),
+    /// Exact decimal number, `mantissa` × 10^-`scale`
+    Decimal {
+        mantissa: i128,
+        scale: u32,
+    },
+    /// Integer beyond `i64`, from `i128` fields
+    BigInt(i128),
+    /// Unsigned integer beyond `i64`, from `u128` fields
+    BigUint(u128),
+    /// Instant in UTC, as seconds and nanoseconds since the Unix epoch
+    DateTime {
+        seconds: i64,
+        nanos: u32,
+    },
+    /// Calendar date, as days since the Unix epoch
+    Date(i32),
+    /// Signed span of time, `nanos` having the sign of `seconds`
+    Duration {
+        seconds: i64,
+        nanos: i32,
+    },
+    /// Binary data, from `Vec<u8>`, `[u8; N]` or `bytes::Bytes` fields
+    Bytes(Vec<u8>),
     Boxed(Box<dyn BoxedValue>),
-    Vec(Box<dyn BoxedValue>),
+    Vec(Box<dyn ListValue>),
     Option(Option<Box<Value>>),
 }
 
+/// A number of any kind, as compared by [`Value::compare`]
+#[derive(Clone, Copy)]
+enum Number {
+    /// `mantissa` × 10^-`scale`, for integers, big integers and decimals
+    Exact(i128, u32),
+    /// Unsigned integer beyond `i128`
+    Huge(u128),
+    Float(f64),
+}
+
+impl Number {
+    fn to_f64(self) -> f64 {
+        match self {
+            Number::Exact(mantissa, scale) => mantissa as f64 / 10f64.powi(scale as i32),
+            Number::Huge(value) => value as f64,
+            Number::Float(value) => value,
+        }
+    }
+
+    /// Exact unless one side is a float, which compares as `f64`
+    fn compare(a: Number, b: Number) -> Option<Ordering> {
+        match (a, b) {
+            (Number::Float(_), _) | (_, Number::Float(_)) => a.to_f64().partial_cmp(&b.to_f64()),
+            (Number::Huge(a), Number::Huge(b)) => Some(a.cmp(&b)),
+            (Number::Huge(_), _) => Some(Ordering::Greater),
+            (_, Number::Huge(_)) => Some(Ordering::Less),
+            (Number::Exact(a, a_scale), Number::Exact(b, b_scale)) => Some(if a_scale <= b_scale {
+                compare_rescaled(a, b_scale - a_scale, b)
+            } else {
+                compare_rescaled(b, a_scale - b_scale, a).reverse()
+            }),
+        }
+    }
+}
+
+/// Order `a` × 10^`shift` against `b`
+///
+/// A rescaled `a` beyond `i128` is larger in magnitude than `b`, so its sign
+/// decides.
+fn compare_rescaled(a: i128, shift: u32, b: i128) -> Ordering {
+    match rescale(a, shift) {
+        Some(a) => a.cmp(&b),
+        None => a.cmp(&0),
+    }
+}
+
+/// The mantissa of a decimal given `shift` more digits of scale, `None` when
+/// out of the `i128` range
+pub(crate) fn rescale(mantissa: i128, shift: u32) -> Option<i128> {
+    10i128
+        .checked_pow(shift)
+        .and_then(|factor| mantissa.checked_mul(factor))
+}
+
+/// The mantissa of the decimal `mantissa`e-`from` at scale `scale`, `None`
+/// when out of the `i128` range or when digits would be lost
+#[cfg(any(feature = "arrow", feature = "polars"))]
+pub(crate) fn to_scale(mantissa: i128, from: u32, scale: u32) -> Option<i128> {
+    match scale.checked_sub(from) {
+        Some(shift) => rescale(mantissa, shift),
+        None => {
+            let factor = 10i128.checked_pow(from - scale)?;
+            (mantissa % factor == 0).then(|| mantissa / factor)
+        }
+    }
+}
+
 impl Value {
-    pub fn unwrap(&self) -> &Value {
+    /// Box a struct, keeping it navigable by path through
+    /// [`BoxedValue::as_struct_path`]
+    ///
+    /// Used by the derive for nested structs.
+    pub fn from_struct<T: StructPath + Clone + Send + Sync + 'static>(value: T) -> Value {
+        Value::Boxed(Box::new(StructValue(value)))
+    }
+
+    /// Box a list, converting its elements with `element` when accessed
+    /// through [`ListValue::element`]
+    ///
+    /// Used by the derive for `Vec` fields.
+    pub fn list<T: Clone + Send + Sync + 'static>(
+        items: Vec<T>,
+        element: fn(&T) -> Value,
+    ) -> Value {
+        Value::Vec(Box::new(ValueList { items, element }))
+    }
+
+    /// Name of the variant, as reported in `TypeMismatch` errors
+    pub fn variant_name(&self) -> &'static str {
         match self {
-            Value::Option(Some(value)) => value,
-            _ => panic!("Value is not an optional"),
+            Value::String(_) => "String",
+            Value::Integer(_) => "Integer",
+            Value::Float(_) => "Float",
+            Value::Boolean(_) => "Boolean",
+            Value::Decimal { .. } => "Decimal",
+            Value::BigInt(_) => "BigInt",
+            Value::BigUint(_) => "BigUint",
+            Value::DateTime { .. } => "DateTime",
+            Value::Date(_) => "Date",
+            Value::Duration { .. } => "Duration",
+            Value::Bytes(_) => "Bytes",
+            Value::Boxed(_) => "Boxed",
+            Value::Vec(_) => "Vec",
+            Value::Option(_) => "Option",
         }
     }
 
-    pub fn as_str(&self) -> &str {
+    fn mismatch(&self, expected: &str) -> StructPathError {
+        StructPathError::TypeMismatch {
+            expected: expected.to_string(),
+            actual: self.variant_name().to_string(),
+        }
+    }
+
+    fn downcast<T: BoxedValue + 'static>(boxed: &dyn BoxedValue) -> Result<&T, StructPathError> {
+        boxed
+            .as_any()
+            .downcast_ref::<T>()
+            .ok_or_else(|| StructPathError::TypeMismatch {
+                expected: std::any::type_name::<T>().to_string(),
+                actual: boxed.type_name().to_string(),
+            })
+    }
+
+    pub fn try_unwrap(&self) -> Result<&Value, StructPathError> {
         match self {
-            Value::String(value) => value,
-            _ => panic!("Value is not a string"),
+            Value::Option(Some(value)) => Ok(value),
+            Value::Option(None) => Err(StructPathError::NullValue),
+            _ => Err(self.mismatch("Option")),
         }
     }
 
-    pub fn as_string(&self) -> String {
+    pub fn try_as_str(&self) -> Result<&str, StructPathError> {
         match self {
-            Value::String(value) => value.clone(),
-            _ => panic!("Value is not a string"),
+            Value::String(value) => Ok(value),
+            _ => Err(self.mismatch("String")),
         }
     }
 
-    pub fn as_i64(&self) -> i64 {
+    pub fn try_as_string(&self) -> Result<String, StructPathError> {
+        self.try_as_str().map(str::to_string)
+    }
+
+    pub fn try_as_i64(&self) -> Result<i64, StructPathError> {
         match self {
-            Value::Integer(value) => *value,
-            _ => panic!("Value is not an integer"),
+            Value::Integer(value) => Ok(*value),
+            _ => Err(self.mismatch("Integer")),
         }
     }
 
-    pub fn as_f64(&self) -> f64 {
+    pub fn try_as_f64(&self) -> Result<f64, StructPathError> {
         match self {
-            Value::Float(value) => *value,
-            _ => panic!("Value is not a float"),
+            Value::Float(value) => Ok(*value),
+            _ => Err(self.mismatch("Float")),
         }
     }
 
-    pub fn as_bool(&self) -> bool {
+    pub fn try_as_bool(&self) -> Result<bool, StructPathError> {
         match self {
-            Value::Boolean(value) => *value,
-            _ => panic!("Value is not a boolean"),
+            Value::Boolean(value) => Ok(*value),
+            _ => Err(self.mismatch("Boolean")),
         }
     }
 
-    pub fn as_unboxed<T: BoxedValue + 'static>(&self) -> &T {
+    /// Mantissa and scale of a `Decimal`
+    pub fn try_as_decimal(&self) -> Result<(i128, u32), StructPathError> {
         match self {
-            Value::Boxed(boxed) => boxed.as_ref().as_any().downcast_ref::<T>().unwrap(),
-            _ => panic!("Value is not a boxable"),
+            Value::Decimal { mantissa, scale } => Ok((*mantissa, *scale)),
+            _ => Err(self.mismatch("Decimal")),
         }
     }
 
-    pub fn as_array<T: BoxedValue + 'static>(&self) -> &T {
+    pub fn try_as_i128(&self) -> Result<i128, StructPathError> {
         match self {
-            Value::Vec(boxed) => boxed.as_ref().as_any().downcast_ref::<T>().unwrap(),
-            _ => panic!("Value is not an array"),
+            Value::BigInt(value) => Ok(*value),
+            _ => Err(self.mismatch("BigInt")),
         }
     }
 
-    pub fn as_option(self) -> Option<Value> {
+    pub fn try_as_u128(&self) -> Result<u128, StructPathError> {
+        match self {
+            Value::BigUint(value) => Ok(*value),
+            _ => Err(self.mismatch("BigUint")),
+        }
+    }
+
+    /// Seconds and nanoseconds since the Unix epoch of a `DateTime`
+    pub fn try_as_datetime(&self) -> Result<(i64, u32), StructPathError> {
+        match self {
+            Value::DateTime { seconds, nanos } => Ok((*seconds, *nanos)),
+            _ => Err(self.mismatch("DateTime")),
+        }
+    }
+
+    /// Days since the Unix epoch of a `Date`
+    pub fn try_as_date(&self) -> Result<i32, StructPathError> {
+        match self {
+            Value::Date(days) => Ok(*days),
+            _ => Err(self.mismatch("Date")),
+        }
+    }
+
+    /// Seconds and nanoseconds of a `Duration`
+    pub fn try_as_duration(&self) -> Result<(i64, i32), StructPathError> {
+        match self {
+            Value::Duration { seconds, nanos } => Ok((*seconds, *nanos)),
+            _ => Err(self.mismatch("Duration")),
+        }
+    }
+
+    pub fn try_as_bytes(&self) -> Result<&[u8], StructPathError> {
+        match self {
+            Value::Bytes(value) => Ok(value),
+            _ => Err(self.mismatch("Bytes")),
+        }
+    }
+
+    /// Nanoseconds since the Unix epoch of a `DateTime`, or of a `Duration`,
+    /// `None` when out of the `i64` range
+    #[cfg_attr(not(any(feature = "arrow", feature = "polars")), allow(dead_code))]
+    pub(crate) fn total_nanos(&self) -> Result<Option<i64>, StructPathError> {
+        let (seconds, nanos) = match self {
+            Value::DateTime { seconds, nanos } => (*seconds, i64::from(*nanos)),
+            Value::Duration { seconds, nanos } => (*seconds, i64::from(*nanos)),
+            _ => return Err(self.mismatch("DateTime or Duration")),
+        };
+        Ok(seconds
+            .checked_mul(1_000_000_000)
+            .and_then(|total| total.checked_add(nanos)))
+    }
+
+    /// The `DateTime` or `Duration` `nanos` nanoseconds after the Unix epoch
+    #[cfg_attr(not(feature = "polars"), allow(dead_code))]
+    pub(crate) fn from_nanos(nanos: i64, datetime: bool) -> Value {
+        let (seconds, nanos) = (
+            nanos.div_euclid(1_000_000_000),
+            nanos.rem_euclid(1_000_000_000),
+        );
+        if datetime {
+            Value::DateTime {
+                seconds,
+                nanos: nanos as u32,
+            }
+        } else if seconds < 0 && nanos > 0 {
+            Value::Duration {
+                seconds: seconds + 1,
+                nanos: (nanos - 1_000_000_000) as i32,
+            }
+        } else {
+            Value::Duration {
+                seconds,
+                nanos: nanos as i32,
+            }
+        }
+    }
+
+    pub fn try_as_unboxed<T: BoxedValue + 'static>(&self) -> Result<&T, StructPathError> {
+        match self {
+            Value::Boxed(boxed) => Self::downcast(boxed.as_ref()),
+            _ => Err(self.mismatch("Boxed")),
+        }
+    }
+
+    pub fn try_as_array<T: BoxedValue + 'static>(&self) -> Result<&T, StructPathError> {
+        match self {
+            Value::Vec(boxed) => Self::downcast(boxed.as_ref()),
+            _ => Err(self.mismatch("Vec")),
+        }
+    }
+
+    /// Length of a `Vec` value, whatever its element type
+    pub fn try_list_len(&self) -> Result<usize, StructPathError> {
         match self {
-            Value::Option(Some(value)) => Some(*value),
+            Value::Vec(boxed) => Ok(boxed.len()),
+            _ => Err(self.mismatch("Vec")),
+        }
+    }
+
+    pub fn try_as_option(self) -> Result<Option<Value>, StructPathError> {
+        match self {
+            Value::Option(value) => Ok(value.map(|value| *value)),
+            _ => Err(self.mismatch("Option")),
+        }
+    }
+
+    /// Order two values of the same kind: numbers of any kind alike,
+    /// strings, booleans, date-times, dates, durations or bytes
+    ///
+    /// `Option` layers are stripped, `None` coming first. Values of different
+    /// kinds, boxed values and lists are not comparable and give `None`.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        let (value, other) = match (self.unwrapped(), other.unwrapped()) {
+            (None, None) => return Some(Ordering::Equal),
+            (None, Some(_)) => return Some(Ordering::Less),
+            (Some(_), None) => return Some(Ordering::Greater),
+            (Some(value), Some(other)) => (value, other),
+        };
+        if let (Some(a), Some(b)) = (value.number(), other.number()) {
+            return Number::compare(a, b);
+        }
+        match (value, other) {
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            (Value::Boolean(a), Value::Boolean(b)) => Some(a.cmp(b)),
+            (
+                Value::DateTime { seconds, nanos },
+                Value::DateTime {
+                    seconds: other_seconds,
+                    nanos: other_nanos,
+                },
+            ) => Some((seconds, nanos).cmp(&(other_seconds, other_nanos))),
+            (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+            // `nanos` having the sign of `seconds`, durations order like pairs
+            (
+                Value::Duration { seconds, nanos },
+                Value::Duration {
+                    seconds: other_seconds,
+                    nanos: other_nanos,
+                },
+            ) => Some((seconds, nanos).cmp(&(other_seconds, other_nanos))),
+            (Value::Bytes(a), Value::Bytes(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+
+    /// The value as a number, `None` for other kinds
+    fn number(&self) -> Option<Number> {
+        match self {
+            Value::Integer(value) => Some(Number::Exact(i128::from(*value), 0)),
+            Value::BigInt(value) => Some(Number::Exact(*value, 0)),
+            Value::BigUint(value) => Some(match i128::try_from(*value) {
+                Ok(value) => Number::Exact(value, 0),
+                Err(_) => Number::Huge(*value),
+            }),
+            Value::Decimal { mantissa, scale } => Some(Number::Exact(*mantissa, *scale)),
+            Value::Float(value) => Some(Number::Float(*value)),
+            _ => None,
+        }
+    }
+
+    /// The value inside any `Option` layers, `None` if one of them is empty
+    fn unwrapped(&self) -> Option<&Value> {
+        match self {
+            Value::Option(Some(value)) => value.unwrapped(),
             Value::Option(None) => None,
-            _ => panic!("Value is not an optional"),
+            value => Some(value),
         }
     }
+
+    /// Strip any `Option` layers, giving `None` if one of them is empty
+    pub fn flatten(self) -> Option<Value> {
+        match self {
+            Value::Option(Some(value)) => value.flatten(),
+            Value::Option(None) => None,
+            value => Some(value),
+        }
+    }
+
+    /// Panicking counterpart of [`Value::try_unwrap`]
+    pub fn unwrap(&self) -> &Value {
+        self.try_unwrap().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_str`]
+    pub fn as_str(&self) -> &str {
+        self.try_as_str().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_string`]
+    pub fn as_string(&self) -> String {
+        self.try_as_string().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_i64`]
+    pub fn as_i64(&self) -> i64 {
+        self.try_as_i64().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_f64`]
+    pub fn as_f64(&self) -> f64 {
+        self.try_as_f64().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_bool`]
+    pub fn as_bool(&self) -> bool {
+        self.try_as_bool().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_decimal`]
+    pub fn as_decimal(&self) -> (i128, u32) {
+        self.try_as_decimal().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_i128`]
+    pub fn as_i128(&self) -> i128 {
+        self.try_as_i128().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_u128`]
+    pub fn as_u128(&self) -> u128 {
+        self.try_as_u128().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_bytes`]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.try_as_bytes().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_unboxed`]
+    pub fn as_unboxed<T: BoxedValue + 'static>(&self) -> &T {
+        self.try_as_unboxed().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_array`]
+    pub fn as_array<T: BoxedValue + 'static>(&self) -> &T {
+        self.try_as_array().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Panicking counterpart of [`Value::try_as_option`]
+    pub fn as_option(self) -> Option<Value> {
+        self.try_as_option().unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = StructPathError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(value) => Ok(value),
+            _ => Err(value.mismatch("String")),
+        }
+    }
+}
+
+impl TryFrom<Value> for i64 {
+    type Error = StructPathError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.try_as_i64()
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = StructPathError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.try_as_f64()
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = StructPathError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.try_as_bool()
+    }
+}
+
+impl TryFrom<Value> for Vec<u8> {
+    type Error = StructPathError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bytes(value) => Ok(value),
+            _ => Err(value.mismatch("Bytes")),
+        }
+    }
+}
+
+impl<T> TryFrom<Value> for Option<T>
+where
+    T: TryFrom<Value, Error = StructPathError>,
+{
+    type Error = StructPathError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.try_as_option()?.map(T::try_from).transpose()
+    }
+}
+
+/// Conversion used by [`StructPath::get_as`](crate::StructPath::get_as)
+///
+/// Unlike `TryFrom<Value>`, conversions are lenient: optional values are
+/// unwrapped (`None` gives `NullValue`), integers widen to floats and boxed
+/// values are downcast and cloned.
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Result<Self, StructPathError>;
+}
+
+/// Strip any `Option` layers, failing on `None`
+fn unwrap_optional(value: Value) -> Result<Value, StructPathError> {
+    value.flatten().ok_or(StructPathError::NullValue)
+}
+
+impl Value {
+    /// Downcast and clone a boxed value, unwrapping optionals
+    ///
+    /// Used by the derived `FromValue` implementations of nested structs.
+    pub fn try_into_unboxed<T: BoxedValue + Clone>(self) -> Result<T, StructPathError> {
+        unwrap_optional(self)?.try_as_unboxed::<T>().cloned()
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        String::try_from(unwrap_optional(value)?)
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        unwrap_optional(value)?.try_as_i64()
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        match unwrap_optional(value)? {
+            Value::Integer(value) => Ok(value as f64),
+            value => value.try_as_f64(),
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        unwrap_optional(value)?.try_as_bool()
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Integer(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Boolean(value)
+    }
+}
+
+impl From<i128> for Value {
+    fn from(value: i128) -> Self {
+        Value::BigInt(value)
+    }
+}
+
+/// `BigInt` when it fits in an `i128`, as the `BigInt` field type declares,
+/// and `BigUint` beyond
+impl From<u128> for Value {
+    fn from(value: u128) -> Self {
+        i128::try_from(value).map_or(Value::BigUint(value), Value::BigInt)
+    }
+}
+
+/// The error of an integer beyond the range of `target`
+fn integer_out_of_range(target: &str, value: &Value) -> StructPathError {
+    StructPathError::TypeMismatch {
+        expected: target.to_string(),
+        actual: format!("{value:?}"),
+    }
+}
+
+/// Also reads from `Integer`, from `BigUint` within range and from
+/// `Decimal`s of scale 0, as Polars columns hold big integers
+impl FromValue for i128 {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        match unwrap_optional(value)? {
+            Value::Integer(value) => Ok(value.into()),
+            Value::Decimal { mantissa, scale: 0 } => Ok(mantissa),
+            Value::BigUint(unsigned) => i128::try_from(unsigned)
+                .map_err(|_| integer_out_of_range("i128", &Value::BigUint(unsigned))),
+            value => value.try_as_i128(),
+        }
+    }
+}
+
+/// Also reads from non-negative `Integer`, `BigInt` and `Decimal`s of scale 0
+impl FromValue for u128 {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        let value = unwrap_optional(value)?;
+        let signed = match value {
+            Value::Integer(signed) => i128::from(signed),
+            Value::BigInt(signed) => signed,
+            Value::Decimal { mantissa, scale: 0 } => mantissa,
+            value => return value.try_as_u128(),
+        };
+        u128::try_from(signed).map_err(|_| integer_out_of_range("u128", &value))
+    }
+}
+
+/// Integers narrower than `i64`, such as protobuf `int32` and `uint32` fields
+/// and the numbers of enums, widen to `Integer`s, and read back within range
+macro_rules! narrow_integer {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Value::Integer(value.into())
+                }
+            }
+
+            impl FromValue for $ty {
+                fn from_value(value: Value) -> Result<Self, StructPathError> {
+                    let value = unwrap_optional(value)?;
+                    let signed = value.try_as_i64()?;
+                    <$ty>::try_from(signed)
+                        .map_err(|_| integer_out_of_range(stringify!($ty), &value))
+                }
+            }
+        )*
+    };
+}
+
+narrow_integer!(i8, i16, i32, u8, u16, u32);
+
+/// `Integer` when it fits in an `i64`, as for protobuf `uint64` fields read
+/// by reflection, and `BigUint` beyond
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or(Value::BigUint(value.into()), Value::Integer)
+    }
+}
+
+/// Reads from the same values as `u128`, within range
+impl FromValue for u64 {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        let value = unwrap_optional(value)?;
+        let unsigned = u128::from_value(value.clone())?;
+        u64::try_from(unsigned).map_err(|_| integer_out_of_range("u64", &value))
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float(value.into())
+    }
+}
+
+/// Also reads from `Integer`, as `f64` does, rounding to the nearest `f32`
+impl FromValue for f32 {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        f64::from_value(value).map(|value| value as f32)
+    }
+}
+
+impl From<std::time::SystemTime> for Value {
+    fn from(value: std::time::SystemTime) -> Self {
+        match value.duration_since(std::time::UNIX_EPOCH) {
+            Ok(since) => Value::DateTime {
+                seconds: since.as_secs() as i64,
+                nanos: since.subsec_nanos(),
+            },
+            Err(e) => {
+                let before = e.duration();
+                match before.subsec_nanos() {
+                    0 => Value::DateTime {
+                        seconds: -(before.as_secs() as i64),
+                        nanos: 0,
+                    },
+                    nanos => Value::DateTime {
+                        seconds: -(before.as_secs() as i64) - 1,
+                        nanos: 1_000_000_000 - nanos,
+                    },
+                }
+            }
+        }
+    }
+}
+
+impl From<std::time::Duration> for Value {
+    fn from(value: std::time::Duration) -> Self {
+        Value::Duration {
+            seconds: value.as_secs() as i64,
+            nanos: value.subsec_nanos() as i32,
+        }
+    }
+}
+
+/// Instants before the Unix epoch are supported
+impl FromValue for std::time::SystemTime {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        let (seconds, nanos) = unwrap_optional(value)?.try_as_datetime()?;
+        let epoch = std::time::UNIX_EPOCH;
+        let instant = match u64::try_from(seconds) {
+            Ok(seconds) => epoch.checked_add(std::time::Duration::from_secs(seconds)),
+            Err(_) => epoch.checked_sub(std::time::Duration::from_secs(seconds.unsigned_abs())),
+        };
+        instant
+            .and_then(|instant| instant.checked_add(std::time::Duration::from_nanos(nanos.into())))
+            .ok_or_else(|| StructPathError::TypeMismatch {
+                expected: "SystemTime".to_string(),
+                actual: format!("DateTime of {seconds}s"),
+            })
+    }
+}
+
+/// Negative durations cannot be represented and give a `TypeMismatch`
+impl FromValue for std::time::Duration {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        let (seconds, nanos) = unwrap_optional(value)?.try_as_duration()?;
+        match (u64::try_from(seconds), u32::try_from(nanos)) {
+            (Ok(seconds), Ok(nanos)) => Ok(std::time::Duration::new(seconds, nanos)),
+            _ => Err(StructPathError::TypeMismatch {
+                expected: "non-negative Duration".to_string(),
+                actual: format!("Duration of {seconds}s {nanos}ns"),
+            }),
+        }
+    }
+}
+
+/// `Vec<u8>` also reads from `Bytes`
+impl<T: BoxedValue + Clone> FromValue for Vec<T> {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        match unwrap_optional(value)? {
+            Value::Bytes(bytes) => {
+                let boxed: Box<dyn std::any::Any> = Box::new(bytes);
+                boxed.downcast::<Vec<T>>().map(|bytes| *bytes).map_err(|_| {
+                    StructPathError::TypeMismatch {
+                        expected: std::any::type_name::<Vec<T>>().to_string(),
+                        actual: "Bytes".to_string(),
+                    }
+                })
+            }
+            value => value.try_as_array::<Vec<T>>().cloned(),
+        }
+    }
+}
+
+/// Bytes of another length give a `TypeMismatch`
+impl<const N: usize> FromValue for [u8; N] {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        let value = unwrap_optional(value)?;
+        let bytes = value.try_as_bytes()?;
+        bytes.try_into().map_err(|_| StructPathError::TypeMismatch {
+            expected: format!("{N} bytes"),
+            actual: format!("{} bytes", bytes.len()),
+        })
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl FromValue for bytes::Bytes {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        Vec::<u8>::try_from(unwrap_optional(value)?).map(bytes::Bytes::from)
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        match value {
+            Value::Option(None) => Ok(None),
+            Value::Option(Some(value)) => T::from_value(*value).map(Some),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_mismatch<T: std::fmt::Debug>(
+        result: Result<T, StructPathError>,
+        expected: &str,
+        actual: &str,
+    ) {
+        match result {
+            Err(StructPathError::TypeMismatch {
+                expected: e,
+                actual: a,
+            }) => {
+                assert_eq!(e, expected);
+                assert_eq!(a, actual);
+            }
+            other => panic!("Expected TypeMismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_try_as_scalars() {
+        assert_eq!(Value::String("a".to_string()).try_as_str().unwrap(), "a");
+        assert_eq!(Value::Integer(3).try_as_i64().unwrap(), 3);
+        assert_eq!(Value::Float(1.5).try_as_f64().unwrap(), 1.5);
+        assert!(Value::Boolean(true).try_as_bool().unwrap());
+
+        assert_mismatch(Value::Integer(3).try_as_str(), "String", "Integer");
+        assert_mismatch(Value::Float(1.5).try_as_i64(), "Integer", "Float");
+        assert_mismatch(Value::Boolean(true).try_as_f64(), "Float", "Boolean");
+        assert_mismatch(Value::Option(None).try_as_bool(), "Boolean", "Option");
+    }
+
+    #[test]
+    fn test_try_unwrap_and_option() {
+        let some = Value::Option(Some(Box::new(Value::Integer(1))));
+        assert_eq!(some.try_unwrap().unwrap(), &Value::Integer(1));
+        assert!(matches!(
+            Value::Option(None).try_unwrap(),
+            Err(StructPathError::NullValue)
+        ));
+        assert_mismatch(Value::Integer(1).try_unwrap(), "Option", "Integer");
+
+        assert_eq!(some.clone().flatten(), Some(Value::Integer(1)));
+        assert_eq!(
+            Value::Option(Some(Box::new(Value::Option(None)))).flatten(),
+            None
+        );
+        assert_eq!(some.try_as_option().unwrap(), Some(Value::Integer(1)));
+        assert_mismatch(Value::Integer(1).try_as_option(), "Option", "Integer");
+    }
+
+    #[test]
+    fn test_try_as_boxed() {
+        let boxed = Value::Boxed(Box::new(42u8));
+        assert_eq!(boxed.try_as_unboxed::<u8>().unwrap(), &42);
+        assert_mismatch(boxed.try_as_unboxed::<u16>(), "u16", "u8");
+        assert_mismatch(boxed.try_as_array::<Vec<u8>>(), "Vec", "Boxed");
+
+        let array = Value::Vec(Box::new(vec![1i64, 2]));
+        assert_eq!(array.try_as_array::<Vec<i64>>().unwrap(), &vec![1, 2]);
+        assert_eq!(array.try_list_len().unwrap(), 2);
+        assert_eq!(array.clone(), array);
+        assert_mismatch(boxed.try_list_len(), "Vec", "Boxed");
+        assert_mismatch(
+            array.try_as_array::<Vec<u8>>(),
+            "alloc::vec::Vec<u8>",
+            "alloc::vec::Vec<i64>",
+        );
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(
+            String::try_from(Value::String("a".to_string())).unwrap(),
+            "a"
+        );
+        assert_eq!(i64::try_from(Value::Integer(3)).unwrap(), 3);
+        assert_eq!(f64::try_from(Value::Float(0.5)).unwrap(), 0.5);
+        assert!(!bool::try_from(Value::Boolean(false)).unwrap());
+        assert_mismatch(i64::try_from(Value::Float(0.5)), "Integer", "Float");
+
+        let some = Value::Option(Some(Box::new(Value::Integer(1))));
+        assert_eq!(Option::<i64>::try_from(some).unwrap(), Some(1));
+        assert_eq!(Option::<i64>::try_from(Value::Option(None)).unwrap(), None);
+        assert_mismatch(
+            Option::<String>::try_from(Value::Integer(1)),
+            "Option",
+            "Integer",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Type mismatch: expected String, got Integer")]
+    fn test_as_str_panics() {
+        Value::Integer(1).as_str();
+    }
+
+    #[test]
+    fn test_from_value() {
+        let some = |v| Value::Option(Some(Box::new(v)));
+
+        assert_eq!(i64::from_value(some(Value::Integer(1))).unwrap(), 1);
+        assert_eq!(f64::from_value(Value::Integer(2)).unwrap(), 2.0);
+        assert_eq!(f64::from_value(some(Value::Float(0.5))).unwrap(), 0.5);
+        assert!(matches!(
+            String::from_value(Value::Option(None)),
+            Err(StructPathError::NullValue)
+        ));
+        assert_mismatch(bool::from_value(Value::Integer(1)), "Boolean", "Integer");
+
+        assert_eq!(
+            Option::<i64>::from_value(Value::Integer(3)).unwrap(),
+            Some(3)
+        );
+        assert_eq!(
+            Option::<i64>::from_value(Value::Option(None)).unwrap(),
+            None
+        );
+
+        let array = some(Value::Vec(Box::new(vec![1u8, 2])));
+        assert_eq!(Vec::<u8>::from_value(array).unwrap(), vec![1, 2]);
+        assert_eq!(
+            some(Value::Boxed(Box::new(7u8)))
+                .try_into_unboxed::<u8>()
+                .unwrap(),
+            7
+        );
+    }
+
+    #[test]
+    fn test_clone_boxed() {
+        let boxed = Value::Boxed(Box::new(7u8));
+        let cloned = boxed.clone();
+        assert_eq!(cloned.as_unboxed::<u8>(), &7);
+        assert_eq!(boxed, cloned);
+        assert_ne!(boxed, Value::Boxed(Box::new(7u16)));
+    }
+
+    #[test]
+    fn test_time_values() {
+        let before_epoch = Value::from_nanos(-1_500_000_000, true);
+        assert_eq!(
+            before_epoch,
+            Value::DateTime {
+                seconds: -2,
+                nanos: 500_000_000
+            }
+        );
+        assert_eq!(before_epoch.total_nanos().unwrap(), Some(-1_500_000_000));
+        let instant = std::time::UNIX_EPOCH - std::time::Duration::from_millis(1500);
+        assert_eq!(Value::from(instant), before_epoch);
+        assert_eq!(
+            std::time::SystemTime::from_value(before_epoch).unwrap(),
+            instant
+        );
+
+        let negative = Value::from_nanos(-1_500_000_000, false);
+        assert_eq!(
+            negative,
+            Value::Duration {
+                seconds: -1,
+                nanos: -500_000_000
+            }
+        );
+        assert_eq!(negative.total_nanos().unwrap(), Some(-1_500_000_000));
+        assert_mismatch(
+            std::time::Duration::from_value(negative),
+            "non-negative Duration",
+            "Duration of -1s -500000000ns",
+        );
+        assert_eq!(
+            std::time::Duration::from_value(Value::Option(Some(Box::new(Value::from_nanos(
+                2_000_000_001,
+                false,
+            )))))
+            .unwrap(),
+            std::time::Duration::new(2, 1)
+        );
+        assert_mismatch(Value::Integer(1).try_as_datetime(), "DateTime", "Integer");
+    }
+
+    #[test]
+    fn test_bytes() {
+        let bytes = Value::Option(Some(Box::new(Value::Bytes(vec![1, 2]))));
+        assert_eq!(bytes.unwrap().as_bytes(), &[1, 2]);
+        assert_eq!(Vec::<u8>::from_value(bytes.clone()).unwrap(), vec![1, 2]);
+        assert_eq!(<[u8; 2]>::from_value(bytes.clone()).unwrap(), [1, 2]);
+        assert_mismatch(<[u8; 3]>::from_value(bytes.clone()), "3 bytes", "2 bytes");
+        assert_mismatch(
+            Vec::<u16>::from_value(bytes),
+            "alloc::vec::Vec<u16>",
+            "Bytes",
+        );
+        assert_mismatch(Vec::<u8>::try_from(Value::Integer(1)), "Bytes", "Integer");
+    }
+
+    #[test]
+    fn test_compare() {
+        let some = |v| Value::Option(Some(Box::new(v)));
+
+        assert_eq!(
+            Value::Integer(2).compare(&Value::Float(1.5)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            some(Value::String("a".into())).compare(&Value::String("b".into())),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            Value::Option(None).compare(&some(Value::Date(0))),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            Value::Date(-1).compare(&Value::Date(0)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            Value::from_nanos(-1_500_000_000, true).compare(&Value::from_nanos(-1, true)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            Value::from_nanos(-1_500_000_000, false)
+                .compare(&Value::from_nanos(-500_000_000, false)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(Value::Date(0).compare(&Value::Integer(0)), None);
+        assert_eq!(
+            Value::Boxed(Box::new(1u8)).compare(&Value::Boxed(Box::new(1u8))),
+            None
+        );
+        assert_eq!(Value::Float(f64::NAN).compare(&Value::Float(0.0)), None);
+        assert_mismatch(Value::Integer(1).try_as_date(), "Date", "Integer");
+    }
+
+    #[test]
+    fn test_big_numbers() {
+        let some = |v| Value::Option(Some(Box::new(v)));
+
+        assert_eq!(Value::from(-1i128).as_i128(), -1);
+        assert_eq!(Value::from(u128::MAX).as_u128(), u128::MAX);
+        assert_eq!(i128::from_value(some(Value::Integer(-3))).unwrap(), -3);
+        assert_eq!(i128::from_value(Value::BigUint(5)).unwrap(), 5);
+        assert_eq!(
+            u128::from_value(Value::Decimal {
+                mantissa: 9,
+                scale: 0
+            })
+            .unwrap(),
+            9
+        );
+        assert_mismatch(u128::from_value(Value::BigInt(-1)), "u128", "BigInt(-1)");
+        assert_mismatch(
+            i128::from_value(Value::BigUint(u128::MAX)),
+            "i128",
+            &format!("BigUint({})", u128::MAX),
+        );
+        assert_mismatch(
+            i128::from_value(Value::Decimal {
+                mantissa: 15,
+                scale: 1,
+            }),
+            "BigInt",
+            "Decimal",
+        );
+        assert_mismatch(Value::Integer(1).try_as_decimal(), "Decimal", "Integer");
+    }
+
+    #[test]
+    fn test_compare_numbers() {
+        let decimal = |mantissa, scale| Value::Decimal { mantissa, scale };
+
+        assert_eq!(
+            decimal(150, 2).compare(&decimal(15, 1)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            decimal(-5, 3).compare(&Value::Integer(0)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            Value::BigInt(i128::MAX).compare(&decimal(1, 0)),
+            Some(Ordering::Greater)
+        );
+        // Rescaling `i128::MAX` overflows, its sign decides
+        assert_eq!(
+            Value::BigInt(i128::MAX).compare(&decimal(i128::MAX, 1)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            Value::BigInt(i128::MIN).compare(&decimal(-1, 30)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            Value::BigUint(u128::MAX).compare(&Value::BigInt(i128::MAX)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            Value::BigUint(1).compare(&Value::Integer(1)),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(
+            decimal(25, 1).compare(&Value::Float(2.4)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(decimal(1, 0).compare(&Value::Date(1)), None);
+    }
 }
@@ -0,0 +1,391 @@
+//! The protobuf well-known types of `prost_types`, as used by the messages
+//! generated by `prost-build`
+//!
+//! `Timestamp`s and `Duration`s convert to `Value::DateTime` and
+//! `Value::Duration`. `Struct`s and `Value`s are dynamic documents navigated
+//! by key, like JSON objects, and `Any`s are structs of their `type_url` and
+//! encoded `value`. `prost-build` generates the wrapper types as optional
+//! scalars, which are leaves: `Int32Value`s, `UInt32Value`s and `UInt64Value`s
+//! are `Integer`s, like `u64` fields only `BigUint`s beyond the `i64` range,
+//! and `FloatValue`s `Float`s.
+
+use crate::{
+    compiled::{compile_dynamic, CompilePath, PathFn},
+    document::{get_components, get_field_components, Document, Node, Object},
+    error::StructPathError,
+    from_paths::{child_path, read_value, FromPaths, PathSource},
+    leaf::path_leaf,
+    path::{field_number, PathComponent},
+    traits::{StructInfo, StructPath},
+    value::{FromValue, Value},
+};
+use prost_types::{value::Kind, Any, Duration, Struct, Timestamp};
+use std::borrow::Cow;
+use structpath_types::{FieldInfo, FieldType, FieldsInfo, ProtoField, ProtoLabel};
+
+impl From<Timestamp> for Value {
+    fn from(mut value: Timestamp) -> Self {
+        value.normalize();
+        Value::DateTime {
+            seconds: value.seconds,
+            nanos: value.nanos as u32,
+        }
+    }
+}
+
+impl From<Duration> for Value {
+    fn from(mut value: Duration) -> Self {
+        value.normalize();
+        Value::Duration {
+            seconds: value.seconds,
+            nanos: value.nanos,
+        }
+    }
+}
+
+path_leaf! {
+    Timestamp => DateTime,
+    Duration => Duration,
+}
+
+impl FromValue for Timestamp {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        let (seconds, nanos) = value
+            .flatten()
+            .ok_or(StructPathError::NullValue)?
+            .try_as_datetime()?;
+        Ok(Timestamp {
+            seconds,
+            nanos: nanos as i32,
+        })
+    }
+}
+
+impl FromValue for Duration {
+    fn from_value(value: Value) -> Result<Self, StructPathError> {
+        let (seconds, nanos) = value
+            .flatten()
+            .ok_or(StructPathError::NullValue)?
+            .try_as_duration()?;
+        Ok(Duration { seconds, nanos })
+    }
+}
+
+/// Numbers are always floats, as protobuf `Value`s hold doubles
+impl Document for prost_types::Value {
+    const TYPE_NAME: &'static str = "protobuf Struct";
+
+    fn node(&self) -> Node<'_, Self> {
+        match &self.kind {
+            None | Some(Kind::NullValue(_)) => Node::Null,
+            Some(Kind::NumberValue(value)) => Node::Float(*value),
+            Some(Kind::StringValue(value)) => Node::String(Cow::Borrowed(value)),
+            Some(Kind::BoolValue(value)) => Node::Boolean(*value),
+            Some(Kind::StructValue(_)) => Node::Object,
+            Some(Kind::ListValue(list)) => Node::Array(&list.values),
+        }
+    }
+}
+
+impl Object<prost_types::Value> for prost_types::Value {
+    fn field(&self, name: &str) -> Option<&Self> {
+        match &self.kind {
+            Some(Kind::StructValue(object)) => object.field(name),
+            _ => None,
+        }
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        match &self.kind {
+            Some(Kind::StructValue(object)) => object.keys(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Object<prost_types::Value> for Struct {
+    fn field(&self, name: &str) -> Option<&prost_types::Value> {
+        self.fields.get(name)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.fields.keys().map(String::as_str).collect()
+    }
+}
+
+impl StructPath for prost_types::Value {
+    fn get_value_by_components(
+        &self,
+        components: &[PathComponent],
+    ) -> Result<Value, StructPathError> {
+        get_components(self, components)
+    }
+
+    fn value_fields(&self) -> Vec<String> {
+        self.keys().into_iter().map(str::to_string).collect()
+    }
+}
+
+impl StructPath for Struct {
+    fn get_value_by_components(
+        &self,
+        components: &[PathComponent],
+    ) -> Result<Value, StructPathError> {
+        if components.is_empty() {
+            return Ok(Value::from_struct(self.clone()));
+        }
+        get_field_components::<prost_types::Value>(self, components, 0)
+    }
+
+    fn value_fields(&self) -> Vec<String> {
+        self.keys().into_iter().map(str::to_string).collect()
+    }
+}
+
+/// Keys are only matched when evaluated, as they vary between documents
+impl CompilePath for Struct {
+    fn compile_components(components: &[PathComponent]) -> Result<PathFn<Self>, StructPathError> {
+        Ok(compile_dynamic(components))
+    }
+}
+
+/// See the `CompilePath` implementation of `Struct`
+impl CompilePath for prost_types::Value {
+    fn compile_components(components: &[PathComponent]) -> Result<PathFn<Self>, StructPathError> {
+        Ok(compile_dynamic(components))
+    }
+}
+
+/// Documents have no static fields
+fn document_info(name: &str) -> FieldsInfo {
+    FieldsInfo {
+        name: name.to_string(),
+        fields: Vec::new(),
+        proto_name: Some(format!("google.protobuf.{name}")),
+    }
+}
+
+impl StructInfo for Struct {
+    fn get_fields_info() -> FieldsInfo {
+        document_info("Struct")
+    }
+}
+
+impl StructInfo for prost_types::Value {
+    fn get_fields_info() -> FieldsInfo {
+        document_info("Value")
+    }
+}
+
+/// Documents cannot be read back, as their keys are unknown: they read as
+/// empty
+impl FromPaths for Struct {
+    fn from_paths(
+        _source: &dyn PathSource,
+        _prefix: &[PathComponent],
+    ) -> Result<Self, StructPathError> {
+        Ok(Struct::default())
+    }
+}
+
+/// See the `FromPaths` implementation of `Struct`
+impl FromPaths for prost_types::Value {
+    fn from_paths(
+        _source: &dyn PathSource,
+        _prefix: &[PathComponent],
+    ) -> Result<Self, StructPathError> {
+        Ok(prost_types::Value::default())
+    }
+}
+
+impl StructPath for Any {
+    fn get_value_by_components(
+        &self,
+        components: &[PathComponent],
+    ) -> Result<Value, StructPathError> {
+        let (name, valid_fields): (_, &[&str]) = match components {
+            [] => return Ok(Value::from_struct(self.clone())),
+            [PathComponent::Field(name)] => (name, &["type_url", "value"]),
+            [PathComponent::Field(name) | PathComponent::ArrayIndex(name, _), ..] => (name, &[]),
+        };
+        let field = match field_number(name) {
+            Some(1) => "type_url",
+            Some(2) => "value",
+            _ => name.as_str(),
+        };
+        match field {
+            "type_url" if !valid_fields.is_empty() => Ok(Value::String(self.type_url.clone())),
+            "value" if !valid_fields.is_empty() => Ok(Value::Bytes(self.value.clone())),
+            _ => Err(StructPathError::field_not_found(
+                name.clone(),
+                components,
+                "Any",
+                valid_fields,
+            )),
+        }
+    }
+
+    fn value_fields(&self) -> Vec<String> {
+        vec!["type_url".to_string(), "value".to_string()]
+    }
+}
+
+impl StructInfo for Any {
+    fn get_fields_info() -> FieldsInfo {
+        let field = |name: &str, number, r#type, proto_type: &str| FieldInfo {
+            name: name.to_string(),
+            r#type,
+            nested: None,
+            proto: Some(ProtoField {
+                number,
+                r#type: proto_type.to_string(),
+                label: ProtoLabel::Singular,
+            }),
+        };
+        FieldsInfo {
+            name: "Any".to_string(),
+            fields: vec![
+                field("type_url", 1, FieldType::String, "string"),
+                field("value", 2, FieldType::Bytes, "bytes"),
+            ],
+            proto_name: Some("google.protobuf.Any".to_string()),
+        }
+    }
+}
+
+/// The fields of `Any` are fixed, so they are checked when compiling
+impl CompilePath for Any {
+    fn compile_components(components: &[PathComponent]) -> Result<PathFn<Self>, StructPathError> {
+        Any::default().get_value_by_components(components)?;
+        Ok(compile_dynamic(components))
+    }
+}
+
+impl FromPaths for Any {
+    fn from_paths(
+        source: &dyn PathSource,
+        prefix: &[PathComponent],
+    ) -> Result<Self, StructPathError> {
+        Ok(Any {
+            type_url: read_value(source, &child_path(prefix, "type_url"), false)?,
+            value: read_value(source, &child_path(prefix, "value"), false)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compiled::CompiledPath, path::Path};
+
+    fn value(kind: Kind) -> prost_types::Value {
+        prost_types::Value { kind: Some(kind) }
+    }
+
+    fn get(record: &impl StructPath, path: &str) -> Result<Value, StructPathError> {
+        record.get_value_by_path(&Path::from_str(path).unwrap())
+    }
+
+    #[test]
+    fn test_times() {
+        let timestamp = Timestamp {
+            seconds: 10,
+            nanos: -1,
+        };
+        let datetime = Value::from(timestamp);
+        assert_eq!(
+            datetime,
+            Value::DateTime {
+                seconds: 9,
+                nanos: 999_999_999
+            }
+        );
+        assert_eq!(
+            Timestamp::from_value(Value::Option(Some(Box::new(datetime)))).unwrap(),
+            Timestamp {
+                seconds: 9,
+                nanos: 999_999_999
+            }
+        );
+
+        let duration = Duration {
+            seconds: -1,
+            nanos: -500_000_000,
+        };
+        assert_eq!(
+            Duration::from_value(Value::from(duration)).unwrap(),
+            duration
+        );
+        assert!(matches!(
+            Duration::from_value(Value::Integer(1)),
+            Err(StructPathError::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_struct() {
+        let mut pet = Struct::default();
+        pet.fields.insert(
+            "name".to_string(),
+            value(Kind::StringValue("Max".to_string())),
+        );
+        let mut record = Struct::default();
+        record
+            .fields
+            .insert("score".to_string(), value(Kind::NumberValue(1.5)));
+        record
+            .fields
+            .insert("missing".to_string(), value(Kind::NullValue(0)));
+        record.fields.insert(
+            "pets".to_string(),
+            value(Kind::ListValue(prost_types::ListValue {
+                values: vec![value(Kind::StructValue(pet))],
+            })),
+        );
+
+        assert_eq!(get(&record, "score").unwrap(), Value::Float(1.5));
+        assert_eq!(get(&record, "pets[0].name").unwrap().as_str(), "Max");
+        assert_eq!(get(&record, "missing.name").unwrap(), Value::Option(None));
+        assert_eq!(get(&record, "pets").unwrap().try_list_len().unwrap(), 1);
+        assert_eq!(record.value_fields(), vec!["missing", "pets", "score"]);
+        assert!(matches!(
+            get(&record, "pets[0].nmae"),
+            Err(StructPathError::FieldNotFound { .. })
+        ));
+        assert!(matches!(
+            get(&record, "pets[1]"),
+            Err(StructPathError::IndexOutOfBounds { index: 1, .. })
+        ));
+
+        let compiled = CompiledPath::<Struct>::from_str("pets[0].name").unwrap();
+        assert_eq!(compiled.get_value(&record).unwrap().as_str(), "Max");
+    }
+
+    #[test]
+    fn test_any() {
+        let any = Any {
+            type_url: "type.googleapis.com/sample.User".to_string(),
+            value: vec![1, 2],
+        };
+        assert_eq!(
+            get(&any, "type_url").unwrap().as_str(),
+            "type.googleapis.com/sample.User"
+        );
+        assert_eq!(get(&any, "#2").unwrap().as_bytes(), &[1, 2]);
+        assert!(matches!(
+            get(&any, "type_url.name"),
+            Err(StructPathError::FieldNotFound { .. })
+        ));
+        assert!(matches!(
+            get(&any, "name"),
+            Err(StructPathError::FieldNotFound { .. })
+        ));
+
+        let compiled = CompiledPath::<Any>::from_str("#1").unwrap();
+        assert_eq!(
+            compiled.get_value(&any).unwrap(),
+            get(&any, "type_url").unwrap()
+        );
+        assert!(CompiledPath::<Any>::from_str("name").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+#![cfg(feature = "derive")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use structpath::{Path, StructPath};
+
+/// Allocator counting the allocations made on this test binary
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+#[derive(StructPath, Debug, Clone)]
+struct Pet {
+    name: String,
+    birth_year: i64,
+}
+
+#[derive(StructPath, Debug, Clone)]
+struct User {
+    #[type_hint = "struct"]
+    pets: Vec<Pet>,
+}
+
+#[derive(StructPath, Debug, Clone)]
+struct Group {
+    #[type_hint = "struct"]
+    members: Vec<User>,
+    #[type_hint = "struct"]
+    admin: Option<User>,
+}
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.load(Ordering::SeqCst);
+    let result = f();
+    (result, ALLOCATIONS.load(Ordering::SeqCst) - before)
+}
+
+#[test]
+fn test_get_value_by_path_does_not_allocate() {
+    let user = User {
+        pets: vec![Pet {
+            name: "Buddy".to_string(),
+            birth_year: 2020,
+        }],
+    };
+    let group = Group {
+        members: vec![user.clone()],
+        admin: Some(user),
+    };
+
+    let path = Path::from_str("members[0].pets[0].birth_year").unwrap();
+    let (value, allocations) = count_allocations(|| group.get_value_by_path(&path));
+    assert_eq!(value.unwrap().as_i64(), 2020);
+    assert_eq!(allocations, 0);
+
+    let path = Path::from_str("admin.pets[0].birth_year").unwrap();
+    let (value, allocations) = count_allocations(|| group.get_value_by_path(&path));
+    assert_eq!(value.unwrap().as_i64(), 2020);
+    assert_eq!(allocations, 0);
+
+    // Only the resulting string is allocated
+    let path = Path::from_str("members[0].pets[0].name").unwrap();
+    let (value, allocations) = count_allocations(|| group.get_value_by_path(&path));
+    assert_eq!(value.unwrap().as_str(), "Buddy");
+    assert_eq!(allocations, 1);
+}
@@ -0,0 +1,277 @@
+#![cfg(all(feature = "arrow", feature = "derive"))]
+
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::{DataType, Int64Type};
+use structpath::{to_record_batch, Path, StructInfo, StructPath, StructPathError};
+
+#[derive(StructInfo, StructPath, Debug, Clone, PartialEq)]
+struct Pet {
+    name: String,
+    birth_year: Option<i64>,
+    tags: Vec<String>,
+}
+
+#[derive(StructInfo, StructPath, Debug, Clone, PartialEq)]
+struct User {
+    name: String,
+    age: i64,
+    #[type_hint = "struct"]
+    favourite_pet: Option<Pet>,
+    #[type_hint = "struct"]
+    pets: Vec<Pet>,
+    #[type_hint = "struct"]
+    children: Vec<User>,
+}
+
+fn pet(name: &str, birth_year: Option<i64>, tags: &[&str]) -> Pet {
+    Pet {
+        name: name.to_string(),
+        birth_year,
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+    }
+}
+
+fn build_users() -> Vec<User> {
+    vec![
+        User {
+            name: "John".to_string(),
+            age: 32,
+            favourite_pet: Some(pet("Buddy", Some(2020), &["dog"])),
+            pets: vec![
+                pet("Max", None, &[]),
+                pet("Kitty", Some(2022), &["cat", "old"]),
+            ],
+            children: vec![],
+        },
+        User {
+            name: "Jane".to_string(),
+            age: 28,
+            favourite_pet: None,
+            pets: vec![],
+            children: vec![],
+        },
+    ]
+}
+
+fn paths(paths: &[&str]) -> Vec<Path> {
+    paths.iter().map(|p| Path::from_str(p).unwrap()).collect()
+}
+
+#[test]
+fn test_scalar_columns() {
+    let batch = to_record_batch(
+        &build_users(),
+        &paths(&["name", "age", "favourite_pet.birth_year"]),
+    )
+    .unwrap();
+
+    assert_eq!(batch.num_rows(), 2);
+    let schema = batch.schema();
+    assert_eq!(schema.field(0).name(), "name");
+    assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+    assert!(!schema.field(1).is_nullable());
+    assert!(schema.field(2).is_nullable());
+
+    let names = batch.column(0).as_string::<i32>();
+    assert_eq!(names.value(1), "Jane");
+    let birth_years = batch.column(2).as_primitive::<Int64Type>();
+    assert_eq!(birth_years.value(0), 2020);
+    assert!(birth_years.is_null(1));
+}
+
+#[test]
+fn test_struct_column() {
+    let batch = to_record_batch(&build_users(), &paths(&["favourite_pet"])).unwrap();
+
+    let pets = batch.column(0).as_struct();
+    assert_eq!(pets.column_names(), vec!["name", "birth_year", "tags"]);
+    assert!(pets.is_valid(0));
+    assert!(pets.is_null(1));
+    assert_eq!(pets.column(0).as_string::<i32>().value(0), "Buddy");
+
+    let tags = pets.column(2).as_list::<i32>();
+    assert_eq!(tags.value(0).as_string::<i32>().value(0), "dog");
+}
+
+#[test]
+fn test_list_column() {
+    let batch = to_record_batch(&build_users(), &paths(&["pets"])).unwrap();
+
+    let pets = batch.column(0).as_list::<i32>();
+    assert!(!batch.schema().field(0).is_nullable());
+    assert_eq!(pets.value_length(0), 2);
+    assert_eq!(pets.value_length(1), 0);
+
+    let values = pets.values().as_struct();
+    let birth_years = values.column(1).as_primitive::<Int64Type>();
+    assert!(birth_years.is_null(0));
+    assert_eq!(birth_years.value(1), 2022);
+    let tags = values.column(2).as_list::<i32>();
+    assert_eq!(tags.value_length(1), 2);
+}
+
+#[test]
+fn test_errors() {
+    let err = to_record_batch(&build_users(), &paths(&["pets[0].nmae"])).unwrap_err();
+    assert!(matches!(
+        err,
+        StructPathError::FieldNotFound { index: 1, .. }
+    ));
+
+    let err = to_record_batch(&build_users(), &paths(&["children"])).unwrap_err();
+    assert!(matches!(err, StructPathError::Arrow(_)));
+}
+
+#[test]
+fn test_indexed_columns() {
+    let batch = to_record_batch(
+        &build_users()[..1],
+        &paths(&["pets[1]", "pets[1].tags[0]", "favourite_pet.tags"]),
+    )
+    .unwrap();
+    let pet = batch.column(0).as_struct();
+    assert_eq!(pet.column(0).as_string::<i32>().value(0), "Kitty");
+    assert_eq!(batch.column(1).as_string::<i32>().value(0), "cat");
+    let tags = batch.column(2).as_list::<i32>();
+    assert_eq!(tags.value(0).as_string::<i32>().value(0), "dog");
+
+    // Jane has no pets
+    for path in ["pets[1].name", "pets[1]"] {
+        let err = to_record_batch(&build_users(), &paths(&[path])).unwrap_err();
+        assert!(matches!(
+            err,
+            StructPathError::IndexOutOfBounds {
+                index: 1,
+                len: 0,
+                component: 0,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains(path), "{err}");
+    }
+    let err = to_record_batch(&build_users()[..1], &paths(&["pets[0].tags[0]"])).unwrap_err();
+    assert!(matches!(
+        err,
+        StructPathError::IndexOutOfBounds {
+            index: 0,
+            len: 0,
+            component: 1,
+            ..
+        }
+    ));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_to_record_batch() {
+    let users: Vec<User> = (0..50).flat_map(|_| build_users()).collect();
+    let paths = paths(&["name", "favourite_pet", "pets"]);
+    let batch = structpath::par_to_record_batch(&users, &paths, 7).unwrap();
+    assert_eq!(batch, to_record_batch(&users, &paths).unwrap());
+
+    let empty = structpath::par_to_record_batch::<User>(&[], &paths, 7).unwrap();
+    assert_eq!(empty.num_rows(), 0);
+    assert_eq!(empty.num_columns(), 3);
+}
+
+#[derive(StructInfo, StructPath, Debug, Clone, PartialEq)]
+struct Visit {
+    #[type_hint = "timestamp"]
+    started_at: std::time::SystemTime,
+    #[type_hint = "duration"]
+    length: Option<std::time::Duration>,
+}
+
+#[test]
+fn test_time_columns() {
+    use arrow::datatypes::{DurationNanosecondType, TimeUnit, TimestampNanosecondType};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let visits = [Visit {
+        started_at: UNIX_EPOCH + Duration::from_millis(1_500),
+        length: None,
+    }];
+    let batch = to_record_batch(&visits, &paths(&["started_at", "length"])).unwrap();
+
+    let started_at = batch.column(0).as_primitive::<TimestampNanosecondType>();
+    assert_eq!(
+        started_at.data_type(),
+        &DataType::Timestamp(TimeUnit::Nanosecond, None)
+    );
+    assert_eq!(started_at.value(0), 1_500_000_000);
+    let length = batch.column(1).as_primitive::<DurationNanosecondType>();
+    assert!(length.is_null(0));
+}
+
+#[derive(StructInfo, StructPath, Debug, Clone, PartialEq)]
+struct Account {
+    id: u128,
+    balance: Option<i128>,
+}
+
+#[test]
+fn test_big_integer_columns() {
+    use arrow::datatypes::Decimal128Type;
+
+    let accounts = [
+        Account {
+            id: 10u128.pow(30),
+            balance: None,
+        },
+        Account {
+            id: u128::MAX,
+            balance: Some(-1),
+        },
+    ];
+    let batch = to_record_batch(&accounts[..1], &paths(&["id", "balance"])).unwrap();
+    let id = batch.column(0).as_primitive::<Decimal128Type>();
+    assert_eq!(id.data_type(), &DataType::Decimal128(38, 0));
+    assert_eq!(id.value(0), 10i128.pow(30));
+    assert!(batch.column(1).is_null(0));
+
+    let err = to_record_batch(&accounts, &paths(&["id"])).unwrap_err();
+    assert!(matches!(err, StructPathError::Arrow(_)));
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn test_decimal_columns() {
+    use arrow::datatypes::Decimal128Type;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[derive(StructInfo, StructPath, Debug, Clone, PartialEq)]
+    struct Line {
+        price: Decimal,
+    }
+
+    #[derive(StructInfo, StructPath, Debug, Clone, PartialEq)]
+    struct Invoice {
+        total: Option<Decimal>,
+        #[type_hint = "struct"]
+        lines: Vec<Line>,
+    }
+
+    let invoice = |total: &str| Invoice {
+        total: Some(Decimal::from_str(total).unwrap()),
+        lines: vec![Line {
+            price: Decimal::from_str(total).unwrap(),
+        }],
+    };
+    let invoices = [invoice("12.5"), invoice("-3"), invoice("0.005")];
+
+    // Values are brought to the largest scale of the column
+    let batch = to_record_batch(&invoices, &paths(&["total", "lines"])).unwrap();
+    let total = batch.column(0).as_primitive::<Decimal128Type>();
+    assert_eq!(total.data_type(), &DataType::Decimal128(38, 3));
+    assert_eq!(total.value(0), 12_500);
+    let lines = batch.column(1).as_list::<i32>().values().as_struct();
+    assert_eq!(lines.column(0).data_type(), &DataType::Decimal128(38, 3));
+
+    // Batches of different scales are cast to the largest one
+    #[cfg(feature = "rayon")]
+    assert_eq!(
+        structpath::par_to_record_batch(&invoices, &paths(&["total", "lines"]), 1).unwrap(),
+        batch
+    );
+}
@@ -0,0 +1,187 @@
+#![cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+
+use structpath::{StructPath, StructPathError, Value};
+
+fn assert_document<D: StructPath + Clone + Send + Sync + 'static>(document: &D) {
+    assert_eq!(
+        document.get_value("name").unwrap(),
+        Value::String("Admins".into())
+    );
+    assert_eq!(document.get_value("size").unwrap(), Value::Integer(2));
+    assert_eq!(document.get_value("score").unwrap(), Value::Float(1.5));
+    assert_eq!(document.get_value("active").unwrap(), Value::Boolean(true));
+    assert_eq!(
+        document.get_value("members[1].name").unwrap(),
+        Value::String("Jane".into())
+    );
+    assert_eq!(
+        document
+            .get_value("members")
+            .unwrap()
+            .try_list_len()
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        document
+            .get_value("members[0]")
+            .unwrap()
+            .as_unboxed::<D>()
+            .value_fields(),
+        vec!["name", "tags"]
+    );
+    assert_eq!(
+        document.get_as::<String>("members[0].tags[1]").unwrap(),
+        "dev"
+    );
+
+    let err = document.get_value("members[0].nmae").unwrap_err();
+    assert!(matches!(
+        err,
+        StructPathError::FieldNotFound { index: 1, .. }
+    ));
+    assert_eq!(err.suggestion(), Some("name"));
+    assert!(matches!(
+        document.get_value("members[2]"),
+        Err(StructPathError::IndexOutOfBounds {
+            index: 2,
+            len: 2,
+            component: 0,
+            ..
+        })
+    ));
+    assert!(matches!(
+        document.get_value("members[0].tags[5]"),
+        Err(StructPathError::IndexOutOfBounds {
+            index: 5,
+            component: 1,
+            ..
+        })
+    ));
+    assert!(matches!(
+        document.get_value("name.first"),
+        Err(StructPathError::TypeMismatch { .. })
+    ));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json() {
+    let document = serde_json::json!({
+        "name": "Admins",
+        "size": 2,
+        "score": 1.5,
+        "active": true,
+        "admin": null,
+        "members": [
+            {"name": "John", "tags": ["admin", "dev"]},
+            {"name": "Jane", "tags": []},
+        ],
+    });
+    assert_document(&document);
+    assert_eq!(
+        document.get_value("admin.name").unwrap(),
+        Value::Option(None)
+    );
+
+    let document = serde_json::json!({"id": u64::MAX, "offset": -1, "ratio": 1e300});
+    assert_eq!(
+        document.get_value("id").unwrap(),
+        Value::BigUint(u64::MAX.into())
+    );
+    assert_eq!(document.get_value("offset").unwrap(), Value::Integer(-1));
+    assert_eq!(document.get_value("ratio").unwrap(), Value::Float(1e300));
+
+    // Keys starting with `#` are plain keys, unlike protobuf field numbers
+    let document = serde_json::json!({"#tag": "v1", "meta": {"#2": 3}});
+    assert_eq!(document.get_as::<String>("#tag").unwrap(), "v1");
+    assert_eq!(document.get_as::<i64>("meta.#2").unwrap(), 3);
+
+    // Arrays at the root are lists, without a path to their elements
+    let document = serde_json::json!([{"name": "John"}, {"name": "Jane"}]);
+    assert_eq!(
+        document
+            .get_value_by_components(&[])
+            .unwrap()
+            .try_list_len()
+            .unwrap(),
+        2
+    );
+    assert_eq!(document[1].get_as::<String>("name").unwrap(), "Jane");
+    assert!(matches!(
+        document.get_value("name"),
+        Err(StructPathError::TypeMismatch { .. })
+    ));
+    assert!(matches!(
+        document.get_value("[0].name"),
+        Err(StructPathError::InvalidPath(_))
+    ));
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_toml() {
+    let document: toml::Value = toml::from_str(
+        r#"
+        name = "Admins"
+        size = 2
+        score = 1.5
+        active = true
+        created = 2024-01-02T03:04:05Z
+
+        [[members]]
+        name = "John"
+        tags = ["admin", "dev"]
+
+        [[members]]
+        name = "Jane"
+        tags = []
+        "#,
+    )
+    .unwrap();
+    assert_document(&document);
+    assert_eq!(
+        document.get_as::<String>("created").unwrap(),
+        "2024-01-02T03:04:05Z"
+    );
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_yaml() {
+    let document: serde_yaml::Value = serde_yaml::from_str(
+        r#"
+        name: Admins
+        size: 2
+        score: 1.5
+        active: true
+        admin: ~
+        members:
+          - name: John
+            tags: [admin, dev]
+          - !user
+            name: Jane
+            tags: []
+        "#,
+    )
+    .unwrap();
+    assert_document(&document);
+    assert_eq!(
+        document.get_value("admin.name").unwrap(),
+        Value::Option(None)
+    );
+
+    let document: serde_yaml::Value = serde_yaml::from_str("id: 18446744073709551615").unwrap();
+    assert_eq!(
+        document.get_value("id").unwrap(),
+        Value::BigUint(u64::MAX.into())
+    );
+}
+
+#[cfg(all(feature = "json", feature = "serde"))]
+#[test]
+fn test_json_round_trip() {
+    let document = serde_json::json!({"members": [{"name": "John", "tags": ["admin"]}]});
+    let members = serde_json::to_value(document.get_value("members").unwrap()).unwrap();
+    assert_eq!(members, document["members"]);
+}
@@ -0,0 +1,176 @@
+#![cfg(feature = "derive")]
+
+use std::collections::HashMap;
+use structpath::{FromPaths, StructPath, StructPathError, Value};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+enum Kind {
+    #[default]
+    Dog,
+}
+
+#[derive(StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct Pet {
+    name: String,
+    birth_year: Option<i64>,
+    kind: Kind,
+}
+
+#[derive(StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct User {
+    name: String,
+    score: f64,
+    tags: Vec<String>,
+    #[type_hint = "struct"]
+    favourite_pet: Option<Pet>,
+    #[type_hint = "struct"]
+    pets: Vec<Pet>,
+}
+
+fn source(values: &[(&str, Value)]) -> HashMap<String, Value> {
+    values
+        .iter()
+        .map(|(path, value)| (path.to_string(), value.clone()))
+        .collect()
+}
+
+fn string(value: &str) -> Value {
+    Value::String(value.to_string())
+}
+
+#[test]
+fn test_from_paths() {
+    let source = source(&[
+        ("name", string("John")),
+        ("score", Value::Integer(3)),
+        ("tags[0]", string("admin")),
+        ("favourite_pet.name", Value::Option(None)),
+        ("pets[0].name", string("Max")),
+        ("pets[0].birth_year", Value::Integer(2020)),
+        ("pets[1].name", string("Buddy")),
+    ]);
+    let user = User::from_paths(&source, &[]).unwrap();
+
+    assert_eq!(user.name, "John");
+    assert_eq!(user.score, 3.0);
+    assert_eq!(user.tags, vec!["admin"]);
+    assert_eq!(user.favourite_pet, None);
+    assert_eq!(user.pets.len(), 2);
+    assert_eq!(user.pets[0].birth_year, Some(2020));
+    assert_eq!(user.pets[1].birth_year, None);
+    assert_eq!(user.pets[1].kind, Kind::Dog);
+}
+
+#[test]
+fn test_round_trip() {
+    let user = User {
+        name: "Jane".to_string(),
+        score: 1.5,
+        tags: vec![],
+        favourite_pet: Some(Pet {
+            name: "Max".to_string(),
+            birth_year: None,
+            kind: Kind::Dog,
+        }),
+        pets: vec![],
+    };
+    let source: HashMap<String, Value> = ["name", "score", "favourite_pet.name"]
+        .iter()
+        .map(|path| (path.to_string(), user.get_value(path).unwrap()))
+        .collect();
+    assert_eq!(User::from_paths(&source, &[]).unwrap(), user);
+}
+
+#[derive(StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct Readings {
+    points: Vec<Option<i64>>,
+}
+
+#[test]
+fn test_round_trip_null_elements() {
+    for points in [
+        vec![Some(1), None, Some(3)],
+        vec![None, Some(2)],
+        vec![Some(1), None],
+    ] {
+        let readings = Readings { points };
+        let source: HashMap<String, Value> = (0..readings.points.len())
+            .map(|index| {
+                let path = format!("points[{index}]");
+                let value = readings.get_value(&path).unwrap();
+                (path, value)
+            })
+            .collect();
+        assert_eq!(Readings::from_paths(&source, &[]).unwrap(), readings);
+    }
+}
+
+#[test]
+fn test_from_paths_errors() {
+    let err = User::from_paths(&source(&[("name", string("John"))]), &[]).unwrap_err();
+    assert_eq!(err.to_string(), "No value at `score`");
+
+    let err = User::from_paths(
+        &source(&[("name", string("John")), ("score", string("high"))]),
+        &[],
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid value at `score`: Type mismatch: expected Float, got String"
+    );
+
+    let err = User::from_paths(
+        &source(&[
+            ("name", string("John")),
+            ("score", Value::Float(1.0)),
+            ("pets[0].birth_year", Value::Integer(2020)),
+        ]),
+        &[],
+    )
+    .unwrap_err();
+    assert!(matches!(err, StructPathError::MissingPath(path) if path == "pets[0].name"));
+}
+
+#[derive(StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct Blob {
+    data: Vec<u8>,
+    digest: [u8; 4],
+    thumbnail: Option<Vec<u8>>,
+    chunks: Vec<Vec<u8>>,
+}
+
+#[test]
+fn test_bytes() {
+    let blob = Blob {
+        data: vec![1, 2, 3],
+        digest: [0xde, 0xad, 0xbe, 0xef],
+        thumbnail: None,
+        chunks: vec![vec![4], vec![]],
+    };
+    assert_eq!(blob.get_value("data").unwrap().as_bytes(), &[1, 2, 3]);
+    assert_eq!(blob.get_as::<[u8; 4]>("digest").unwrap(), blob.digest);
+    assert_eq!(blob.get_value("chunks[0]").unwrap(), Value::Bytes(vec![4]));
+
+    let source: HashMap<String, Value> = ["data", "digest", "thumbnail", "chunks[0]", "chunks[1]"]
+        .iter()
+        .map(|path| (path.to_string(), blob.get_value(path).unwrap()))
+        .collect();
+    assert_eq!(Blob::from_paths(&source, &[]).unwrap(), blob);
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes_crate() {
+    #[derive(StructPath, FromPaths, Debug, Clone, PartialEq)]
+    struct Frame {
+        payload: bytes::Bytes,
+    }
+
+    let frame = Frame {
+        payload: bytes::Bytes::from_static(b"abc"),
+    };
+    assert_eq!(frame.get_value("payload").unwrap().as_bytes(), b"abc");
+    let source = source(&[("payload", frame.get_value("payload").unwrap())]);
+    assert_eq!(Frame::from_paths(&source, &[]).unwrap(), frame);
+}
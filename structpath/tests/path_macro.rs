@@ -0,0 +1,98 @@
+#![cfg(feature = "derive")]
+
+use structpath::{path, CompiledPath, Path, StructPath, StructPathError};
+
+#[derive(StructPath, Debug, Clone, PartialEq)]
+struct Pet {
+    name: String,
+    birth_year: i64,
+}
+
+#[derive(StructPath, Debug, Clone, PartialEq)]
+struct User {
+    name: String,
+    nickname: Option<String>,
+    #[type_hint = "struct"]
+    favourite_pet: Option<Pet>,
+    #[type_hint = "struct"]
+    pets: Vec<Pet>,
+    #[type_hint = "struct"]
+    lost_pets: Option<Vec<Option<Pet>>>,
+}
+
+fn build_user() -> User {
+    User {
+        name: "John".to_string(),
+        nickname: None,
+        favourite_pet: Some(Pet {
+            name: "Buddy".to_string(),
+            birth_year: 2020,
+        }),
+        pets: vec![Pet {
+            name: "Max".to_string(),
+            birth_year: 2022,
+        }],
+        lost_pets: Some(vec![None]),
+    }
+}
+
+#[test]
+fn test_path_macro() {
+    let user = build_user();
+
+    let name = path!(User, name);
+    assert_eq!(name(&user).unwrap(), "John");
+
+    let birth_year = path!(User, favourite_pet.birth_year);
+    let birth_year: i64 = birth_year(&user).unwrap();
+    assert_eq!(birth_year, 2020);
+
+    let pet_name = path!(User, pets[0].name);
+    let pet_name: &String = pet_name(&user).unwrap();
+    assert_eq!(pet_name, "Max");
+
+    assert!(matches!(
+        path!(User, nickname)(&user),
+        Err(StructPathError::NullValue)
+    ));
+    let err = path!(User, pets[3].name)(&user).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Index 3 out of bounds for pets of length 1 in User at component 0 of `pets[3].name`"
+    );
+    assert!(matches!(
+        path!(User, lost_pets[0].name)(&user),
+        Err(StructPathError::NullValue)
+    ));
+}
+
+/// Deriving does not need `Clone`, only `FromValue` does
+#[derive(StructPath)]
+struct Shelter {
+    name: String,
+    #[type_hint = "struct"]
+    pets: Vec<Pet>,
+}
+
+#[test]
+fn test_not_clone() {
+    let shelter = Shelter {
+        name: "Paws".to_string(),
+        pets: vec![build_user().pets[0].clone()],
+    };
+    assert_eq!(shelter.get_as::<String>("pets[0].name").unwrap(), "Max");
+    assert_eq!(path!(Shelter, name)(&shelter).unwrap(), "Paws");
+
+    let empty = Path { components: vec![] };
+    assert!(matches!(
+        shelter.get_value_by_path(&empty),
+        Err(StructPathError::InvalidPath(_))
+    ));
+    assert!(CompiledPath::<Shelter>::new(empty).is_err());
+}
+
+#[test]
+fn test_path_macro_compile_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
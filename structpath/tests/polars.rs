@@ -0,0 +1,633 @@
+#![cfg(all(feature = "polars", feature = "derive"))]
+
+use structpath::{
+    from_dataframe, DataFrameBuilder, FromPaths, StructInfo, StructPath, StructPathError,
+};
+
+#[derive(StructInfo, StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct Pet {
+    name: String,
+    birth_year: Option<i64>,
+}
+
+#[derive(StructInfo, StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct User {
+    name: String,
+    tags: Vec<String>,
+    #[type_hint = "struct"]
+    pets: Vec<Pet>,
+    #[type_hint = "struct"]
+    toys: Vec<Pet>,
+}
+
+#[derive(StructInfo, StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct Group {
+    name: String,
+    #[type_hint = "struct"]
+    members: Vec<User>,
+}
+
+fn pet(name: &str, birth_year: Option<i64>) -> Pet {
+    Pet {
+        name: name.to_string(),
+        birth_year,
+    }
+}
+
+fn user(name: &str, pets: Vec<Pet>) -> User {
+    User {
+        name: name.to_string(),
+        tags: vec!["admin".to_string()],
+        pets,
+        toys: vec![],
+    }
+}
+
+fn build_groups() -> Vec<Group> {
+    vec![
+        Group {
+            name: "Admins".to_string(),
+            members: vec![
+                user("John", vec![pet("Max", Some(2020)), pet("Buddy", None)]),
+                user("Jane", vec![]),
+            ],
+        },
+        Group {
+            name: "Empty".to_string(),
+            members: vec![],
+        },
+    ]
+}
+
+fn strings(df: &polars::prelude::DataFrame, column: &str) -> Vec<Option<String>> {
+    df.column(column)
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .map(|value| value.map(str::to_string))
+        .collect()
+}
+
+#[test]
+fn test_build() {
+    let df = DataFrameBuilder::new(&["name", "members[0].name"])
+        .unwrap()
+        .build(&build_groups()[..1])
+        .unwrap();
+    assert_eq!(df.height(), 1);
+    assert_eq!(strings(&df, "members[0].name"), vec![Some("John".into())]);
+}
+
+#[test]
+fn test_explode() {
+    let df = DataFrameBuilder::new(&[
+        "name",
+        "members[*].name",
+        "members[*].pets[*].name",
+        "members[*].pets[*].birth_year",
+    ])
+    .unwrap()
+    .explode()
+    .build(&build_groups())
+    .unwrap();
+
+    assert_eq!(df.height(), 4);
+    assert_eq!(
+        strings(&df, "name"),
+        vec![
+            Some("Admins".into()),
+            Some("Admins".into()),
+            Some("Admins".into()),
+            Some("Empty".into())
+        ]
+    );
+    assert_eq!(
+        strings(&df, "members[*].name"),
+        vec![
+            Some("John".into()),
+            Some("John".into()),
+            Some("Jane".into()),
+            None
+        ]
+    );
+    assert_eq!(
+        strings(&df, "members[*].pets[*].name"),
+        vec![Some("Max".into()), Some("Buddy".into()), None, None]
+    );
+    let birth_years: Vec<Option<i64>> = df
+        .column("members[*].pets[*].birth_year")
+        .unwrap()
+        .i64()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(birth_years, vec![Some(2020), None, None, None]);
+}
+
+#[test]
+fn test_explode_scalar_list() {
+    let df = DataFrameBuilder::new(&["members[*].name", "members[*].tags[*]"])
+        .unwrap()
+        .explode()
+        .build(&build_groups()[..1])
+        .unwrap();
+    assert_eq!(
+        strings(&df, "members[*].tags[*]"),
+        vec![Some("admin".into()), Some("admin".into())]
+    );
+}
+
+#[test]
+fn test_chunked() {
+    let builder = DataFrameBuilder::new(&["name", "members[*].name"])
+        .unwrap()
+        .explode();
+    let mut chunked = builder.chunked::<Group>(2).unwrap();
+
+    let groups = build_groups();
+    let chunk = chunked.push(&groups[0]).unwrap().unwrap();
+    assert_eq!(
+        strings(&chunk, "members[*].name"),
+        vec![Some("John".into()), Some("Jane".into())]
+    );
+    assert!(chunked.push(&groups[1]).unwrap().is_none());
+    assert_eq!(chunked.buffered_rows(), 1);
+    assert_eq!(
+        strings(&chunked.flush().unwrap(), "name"),
+        vec![Some("Empty".into())]
+    );
+    assert_eq!(chunked.flush().unwrap().height(), 0);
+}
+
+#[test]
+fn test_chunks() {
+    let builder = DataFrameBuilder::new(&["name", "members[*].pets[*].name"])
+        .unwrap()
+        .explode();
+    let groups: Vec<Group> = (0..5).flat_map(|_| build_groups()).collect();
+    let chunks: Vec<_> = builder
+        .chunked::<Group>(4)
+        .unwrap()
+        .chunks(groups.iter())
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    let heights: Vec<usize> = chunks.iter().map(|chunk| chunk.height()).collect();
+    assert_eq!(heights, vec![4, 4, 4, 4, 4]);
+    let mut stacked = chunks[0].clone();
+    for chunk in &chunks[1..] {
+        stacked.vstack_mut(chunk).unwrap();
+    }
+    assert_eq!(stacked, builder.build(&groups).unwrap());
+
+    let mut chunks = builder.chunked::<Group>(100).unwrap().chunks(groups);
+    assert_eq!(chunks.next().unwrap().unwrap().height(), 20);
+    assert!(chunks.next().is_none());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_build() {
+    let builder = DataFrameBuilder::new(&["name", "members[*].pets[*].birth_year"])
+        .unwrap()
+        .explode();
+    let groups: Vec<Group> = (0..50).flat_map(|_| build_groups()).collect();
+    let df = builder.par_build(&groups, 7).unwrap();
+    assert_eq!(df, builder.build(&groups).unwrap());
+    assert_eq!(builder.par_build::<Group>(&[], 7).unwrap().height(), 0);
+}
+
+#[test]
+fn test_errors() {
+    let builder = DataFrameBuilder::new(&["members[*].name"]).unwrap();
+    assert!(matches!(
+        builder.build(&build_groups()),
+        Err(StructPathError::Polars(_))
+    ));
+
+    let builder = DataFrameBuilder::new(&["members[*].pets[*].name", "members[*].toys[*].name"])
+        .unwrap()
+        .explode();
+    assert!(matches!(
+        builder.build(&build_groups()),
+        Err(StructPathError::Polars(_))
+    ));
+
+    let builder = DataFrameBuilder::new(&["members[*].pets"])
+        .unwrap()
+        .explode();
+    assert!(matches!(
+        builder.build(&build_groups()),
+        Err(StructPathError::Polars(_))
+    ));
+
+    assert!(matches!(
+        DataFrameBuilder::new(&["members[*]pets"]),
+        Err(StructPathError::InvalidPath(_))
+    ));
+}
+
+#[test]
+fn test_from_dataframe() {
+    let mut groups = build_groups();
+    groups.truncate(1);
+    for member in &mut groups[0].members {
+        member.pets.clear();
+    }
+
+    let df = DataFrameBuilder::new(&[
+        "name",
+        "members[0].name",
+        "members[0].tags[0]",
+        "members[1].name",
+        "members[1].tags[0]",
+    ])
+    .unwrap()
+    .build(&groups)
+    .unwrap();
+    assert_eq!(from_dataframe::<Group>(&df).unwrap(), groups);
+
+    let df = df.drop("name").unwrap();
+    assert!(matches!(
+        from_dataframe::<Group>(&df),
+        Err(StructPathError::MissingPath(path)) if path == "name"
+    ));
+
+    // Null elements are kept, the last ones too
+    #[derive(StructInfo, StructPath, FromPaths, Debug, Clone, PartialEq)]
+    struct Readings {
+        points: Vec<Option<i64>>,
+    }
+    let readings = [
+        Readings {
+            points: vec![Some(1), None, Some(3)],
+        },
+        Readings {
+            points: vec![None, Some(2)],
+        },
+        Readings {
+            points: vec![Some(4), None],
+        },
+    ];
+    let df = DataFrameBuilder::new(&["points[0]", "points[1]", "points[2]"])
+        .unwrap()
+        .build(&readings[..1])
+        .unwrap();
+    assert_eq!(from_dataframe::<Readings>(&df).unwrap(), readings[..1]);
+    let df = DataFrameBuilder::new(&["points[0]", "points[1]"])
+        .unwrap()
+        .build(&readings[1..])
+        .unwrap();
+    assert_eq!(from_dataframe::<Readings>(&df).unwrap(), readings[1..]);
+}
+
+#[derive(StructInfo, StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct Visit {
+    #[type_hint = "timestamp"]
+    started_at: std::time::SystemTime,
+    #[type_hint = "duration"]
+    length: Option<std::time::Duration>,
+}
+
+#[test]
+fn test_times() {
+    use polars::prelude::{DataType, TimeUnit};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let visits = vec![
+        Visit {
+            started_at: UNIX_EPOCH + Duration::from_millis(1_500),
+            length: Some(Duration::from_secs(90)),
+        },
+        Visit {
+            started_at: UNIX_EPOCH,
+            length: None,
+        },
+    ];
+    let df = DataFrameBuilder::new(&["started_at", "length"])
+        .unwrap()
+        .build(&visits)
+        .unwrap();
+    assert_eq!(
+        df.column("started_at").unwrap().dtype(),
+        &DataType::Datetime(TimeUnit::Nanoseconds, None)
+    );
+    assert_eq!(
+        df.column("length").unwrap().dtype(),
+        &DataType::Duration(TimeUnit::Nanoseconds)
+    );
+    assert_eq!(df.column("length").unwrap().null_count(), 1);
+    assert_eq!(from_dataframe::<Visit>(&df).unwrap(), visits);
+
+    let millis = polars::prelude::Column::new("started_at".into(), [i64::MAX])
+        .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+        .unwrap();
+    let df = polars::prelude::DataFrame::new(vec![millis]).unwrap();
+    assert!(matches!(
+        from_dataframe::<Visit>(&df),
+        Err(StructPathError::InvalidValue { path, .. }) if path == "started_at"
+    ));
+}
+
+#[derive(StructInfo, StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct Attachment {
+    name: String,
+    content: Option<Vec<u8>>,
+}
+
+#[test]
+fn test_bytes() {
+    use polars::prelude::DataType;
+
+    let attachments = vec![
+        Attachment {
+            name: "a.bin".to_string(),
+            content: Some(vec![0, 1, 2]),
+        },
+        Attachment {
+            name: "b.bin".to_string(),
+            content: None,
+        },
+    ];
+    let df = DataFrameBuilder::new(&["name", "content"])
+        .unwrap()
+        .build(&attachments)
+        .unwrap();
+    assert_eq!(df.column("content").unwrap().dtype(), &DataType::Binary);
+    assert_eq!(from_dataframe::<Attachment>(&df).unwrap(), attachments);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn test_chrono() {
+    use chrono::{DateTime, NaiveDate, Utc};
+    use polars::prelude::{DataType, TimeUnit};
+
+    #[derive(StructInfo, StructPath, FromPaths, Debug, Clone, PartialEq)]
+    struct Booking {
+        created_at: DateTime<Utc>,
+        day: Option<NaiveDate>,
+        confirmed_at: std::time::SystemTime,
+    }
+
+    let bookings = vec![
+        Booking {
+            created_at: DateTime::from_timestamp(1_714_564_800, 500).unwrap(),
+            day: NaiveDate::from_ymd_opt(2024, 5, 1),
+            confirmed_at: std::time::UNIX_EPOCH,
+        },
+        Booking {
+            created_at: DateTime::from_timestamp(-86_400, 0).unwrap(),
+            day: None,
+            confirmed_at: std::time::UNIX_EPOCH,
+        },
+    ];
+    let df = DataFrameBuilder::new(&["created_at", "day", "confirmed_at"])
+        .unwrap()
+        .build(&bookings)
+        .unwrap();
+    assert_eq!(
+        df.column("created_at").unwrap().dtype(),
+        &DataType::Datetime(TimeUnit::Nanoseconds, None)
+    );
+    assert_eq!(df.column("day").unwrap().dtype(), &DataType::Date);
+    assert_eq!(from_dataframe::<Booking>(&df).unwrap(), bookings);
+
+    // Values order chronologically
+    let mut sorted = bookings.clone();
+    sorted.sort_by(|a, b| {
+        a.get_value("created_at")
+            .unwrap()
+            .compare(&b.get_value("created_at").unwrap())
+            .unwrap()
+    });
+    assert_eq!(sorted[0], bookings[1]);
+    assert_eq!(
+        bookings[1]
+            .get_value("day")
+            .unwrap()
+            .compare(&bookings[0].get_value("day").unwrap()),
+        Some(std::cmp::Ordering::Less)
+    );
+}
+
+#[derive(StructInfo, StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct Account {
+    id: u128,
+    balance: Option<i128>,
+}
+
+#[test]
+fn test_big_integers() {
+    use polars::prelude::DataType;
+
+    let accounts = vec![
+        Account {
+            id: 10u128.pow(30),
+            balance: Some(-(10i128.pow(25))),
+        },
+        Account {
+            id: 1,
+            balance: None,
+        },
+    ];
+    let df = DataFrameBuilder::new(&["id", "balance"])
+        .unwrap()
+        .build(&accounts)
+        .unwrap();
+    assert_eq!(
+        df.column("id").unwrap().dtype(),
+        &DataType::Decimal(Some(38), Some(0))
+    );
+    assert_eq!(from_dataframe::<Account>(&df).unwrap(), accounts);
+
+    let ids = polars::prelude::Column::new("id".into(), [u64::MAX, 7]);
+    let df = polars::prelude::DataFrame::new(vec![ids]).unwrap();
+    let ids: Vec<u128> = from_dataframe::<Account>(&df)
+        .unwrap()
+        .iter()
+        .map(|account| account.id)
+        .collect();
+    assert_eq!(ids, vec![u64::MAX.into(), 7]);
+
+    let too_large = Account {
+        id: u128::MAX,
+        balance: None,
+    };
+    assert!(DataFrameBuilder::new(&["id"])
+        .unwrap()
+        .build(std::slice::from_ref(&too_large))
+        .is_err());
+
+    // A failing record adds no rows, even to the columns before the failure
+    let builder = DataFrameBuilder::new(&["balance", "id"]).unwrap();
+    let mut chunked = builder.chunked::<Account>(10).unwrap();
+    assert!(chunked.push(&accounts[0]).unwrap().is_none());
+    assert!(chunked.push(&too_large).is_err());
+    assert_eq!(chunked.buffered_rows(), 1);
+    assert!(chunked.push(&accounts[1]).unwrap().is_none());
+    assert_eq!(chunked.flush().unwrap(), builder.build(&accounts).unwrap());
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn test_decimal() {
+    use polars::prelude::DataType;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    #[derive(StructInfo, StructPath, FromPaths, Debug, Clone, PartialEq)]
+    struct Invoice {
+        total: Decimal,
+        discount: Option<Decimal>,
+    }
+
+    let invoices: Vec<Invoice> = ["12.5", "0.005", "-3"]
+        .iter()
+        .map(|total| Invoice {
+            total: Decimal::from_str(total).unwrap(),
+            discount: None,
+        })
+        .collect();
+    let builder = DataFrameBuilder::new(&["total", "discount"]).unwrap();
+    let df = builder.build(&invoices).unwrap();
+    // Values are brought to the largest scale of the column
+    assert_eq!(
+        df.column("total").unwrap().dtype(),
+        &DataType::Decimal(Some(38), Some(3))
+    );
+    assert_eq!(
+        df.column("total").unwrap().get(0).unwrap(),
+        polars::prelude::AnyValue::Decimal(12_500, 3)
+    );
+    assert_eq!(from_dataframe::<Invoice>(&df).unwrap(), invoices);
+
+    #[cfg(feature = "rayon")]
+    assert_eq!(builder.par_build(&invoices, 1).unwrap(), df);
+
+    // Chunks keep the scale of the first one, so that they stack
+    let stream: Vec<Invoice> = ["12.5", "3.10", "-3", "0.005"]
+        .iter()
+        .map(|total| Invoice {
+            total: Decimal::from_str(total).unwrap(),
+            discount: None,
+        })
+        .collect();
+    let mut chunks = builder.chunked::<Invoice>(1).unwrap().chunks(&stream);
+    let mut stacked = chunks.next().unwrap().unwrap();
+    for _ in 0..2 {
+        stacked
+            .vstack_mut(&chunks.next().unwrap().unwrap())
+            .unwrap();
+    }
+    let totals: Vec<_> = stacked
+        .column("total")
+        .unwrap()
+        .decimal()
+        .unwrap()
+        .physical()
+        .into_iter()
+        .collect();
+    assert_eq!(totals, vec![Some(125), Some(31), Some(-30)]);
+    // 0.005 has more digits than the scale of 1
+    assert!(matches!(
+        chunks.next(),
+        Some(Err(StructPathError::Polars(_)))
+    ));
+
+    let builder = builder.decimal_scale(3);
+    let chunks = builder
+        .chunked::<Invoice>(1)
+        .unwrap()
+        .chunks(&stream)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let mut stacked = chunks[0].clone();
+    for chunk in &chunks[1..] {
+        stacked.vstack_mut(chunk).unwrap();
+    }
+    assert_eq!(stacked, builder.build(&stream).unwrap());
+    assert_eq!(
+        stacked.column("total").unwrap().dtype(),
+        &DataType::Decimal(Some(38), Some(3))
+    );
+}
+
+/// An in-house leaf type, extracted as a string
+#[derive(Debug, Clone, PartialEq)]
+struct Sku(String);
+
+impl structpath::PathLeaf for Sku {
+    fn field_type() -> structpath_types::FieldType {
+        structpath_types::FieldType::String
+    }
+
+    fn to_value(&self) -> structpath::Value {
+        structpath::Value::String(format!("SKU-{}", self.0))
+    }
+}
+
+impl structpath::FromValue for Sku {
+    fn from_value(value: structpath::Value) -> Result<Self, StructPathError> {
+        let sku = String::from_value(value)?;
+        Ok(Sku(sku.trim_start_matches("SKU-").to_string()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+struct Opaque;
+
+#[derive(StructInfo, StructPath, FromPaths, Debug, Clone, PartialEq)]
+struct Order {
+    sku: Sku,
+    client: std::net::IpAddr,
+    related: Vec<Sku>,
+    replaces: Option<Sku>,
+    opaque: Opaque,
+}
+
+#[test]
+fn test_path_leaf() {
+    use polars::prelude::DataType;
+    use structpath_types::FieldType;
+
+    let orders = vec![Order {
+        sku: Sku("42".to_string()),
+        client: std::net::Ipv4Addr::LOCALHOST.into(),
+        related: vec![Sku("7".to_string())],
+        replaces: None,
+        opaque: Opaque,
+    }];
+    assert_eq!(
+        orders[0].get_value("sku").unwrap(),
+        structpath::Value::String("SKU-42".to_string())
+    );
+    assert_eq!(
+        Order::get_field_info(&structpath::Path::from_str("replaces").unwrap())
+            .unwrap()
+            .r#type,
+        FieldType::Option(Box::new(FieldType::String))
+    );
+    assert_eq!(
+        Order::get_field_info(&structpath::Path::from_str("opaque").unwrap())
+            .unwrap()
+            .r#type,
+        FieldType::Unknown
+    );
+    assert_eq!(
+        orders[0].value_fields(),
+        ["sku", "client", "related", "replaces"]
+    );
+    assert!(matches!(
+        orders[0].get_value("opaque").unwrap(),
+        structpath::Value::Boxed(_)
+    ));
+
+    let df = DataFrameBuilder::new(&["sku", "client", "related[0]", "replaces"])
+        .unwrap()
+        .build(&orders)
+        .unwrap();
+    assert_eq!(df.column("client").unwrap().dtype(), &DataType::String);
+    assert_eq!(from_dataframe::<Order>(&df).unwrap(), orders);
+}
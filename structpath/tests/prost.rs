@@ -0,0 +1,132 @@
+#![cfg(all(feature = "polars", feature = "prost", feature = "derive"))]
+
+use polars::prelude::{col, Column, DataFrame, GetOutput, IntoLazy};
+use prost::Message;
+use structpath::{decode_paths, decode_paths_dtype, StructInfo, StructPath, StructPathError};
+
+#[derive(StructInfo, StructPath, Clone, PartialEq, Message)]
+struct Pet {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int64, tag = "2")]
+    birth_year: i64,
+}
+
+#[derive(StructInfo, StructPath, Clone, PartialEq, Message)]
+struct User {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(string, optional, tag = "2")]
+    email: Option<String>,
+    #[type_hint = "struct"]
+    #[prost(message, optional, tag = "3")]
+    favourite_pet: Option<Pet>,
+    #[type_hint = "struct"]
+    #[prost(message, repeated, tag = "4")]
+    pets: Vec<Pet>,
+}
+
+fn encoded_users() -> Column {
+    let users = [
+        User {
+            name: "John".to_string(),
+            email: Some("john@example.com".to_string()),
+            favourite_pet: Some(Pet {
+                name: "Buddy".to_string(),
+                birth_year: 2020,
+            }),
+            pets: vec![],
+        },
+        User {
+            name: "Jane".to_string(),
+            ..Default::default()
+        },
+    ];
+    let mut bytes: Vec<Option<Vec<u8>>> = users.iter().map(|u| Some(u.encode_to_vec())).collect();
+    bytes.push(None);
+    Column::new("payload".into(), bytes)
+}
+
+const PATHS: [&str; 3] = ["name", "email", "favourite_pet.birth_year"];
+
+/// Check the values at `PATHS` decoded from `encoded_users`
+fn check_decoded(df: &DataFrame) {
+    assert_eq!(df.height(), 3);
+    let names: Vec<Option<&str>> = df
+        .column("name")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(names, vec![Some("John"), Some("Jane"), None]);
+    let emails: Vec<Option<&str>> = df
+        .column("email")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(emails, vec![Some("john@example.com"), None, None]);
+    let birth_years: Vec<Option<i64>> = df
+        .column("favourite_pet.birth_year")
+        .unwrap()
+        .i64()
+        .unwrap()
+        .into_iter()
+        .collect();
+    assert_eq!(birth_years, vec![Some(2020), None, None]);
+}
+
+#[test]
+fn test_decode_paths() {
+    let column = decode_paths::<User>(&encoded_users(), &PATHS).unwrap();
+    assert_eq!(column.name().as_str(), "payload");
+    assert_eq!(column.dtype(), &decode_paths_dtype::<User>(&PATHS).unwrap());
+
+    let df = column.struct_().unwrap().clone().unnest();
+    check_decoded(&df);
+}
+
+#[test]
+fn test_decode_paths_lazily() {
+    let df = DataFrame::new(vec![encoded_users()]).unwrap();
+    let decode = |column: Column| Ok(Some(decode_paths::<User>(&column, &PATHS)?));
+    let dtype = decode_paths_dtype::<User>(&PATHS).unwrap();
+    let df = df
+        .lazy()
+        .with_column(col("payload").map(decode, GetOutput::from_type(dtype)))
+        .unnest(["payload"])
+        .collect()
+        .unwrap();
+    check_decoded(&df);
+
+    let decode = |column: Column| Ok(Some(decode_paths::<User>(&column, &["pets"])?));
+    let err = DataFrame::new(vec![encoded_users()])
+        .unwrap()
+        .lazy()
+        .select([col("payload").map(decode, GetOutput::same_type())])
+        .collect()
+        .unwrap_err();
+    assert!(err.to_string().contains("`pets`"), "{err}");
+}
+
+#[test]
+fn test_decode_paths_errors() {
+    let garbage = Column::new("payload".into(), vec![vec![0xffu8, 0xff]]);
+    assert!(matches!(
+        decode_paths::<User>(&garbage, &["name"]),
+        Err(StructPathError::Decode { row: 0, .. })
+    ));
+
+    assert!(matches!(
+        decode_paths::<User>(&encoded_users(), &["pets"]),
+        Err(StructPathError::Polars(_))
+    ));
+
+    let strings = Column::new("payload".into(), vec!["John"]);
+    assert!(matches!(
+        decode_paths::<User>(&strings, &["name"]),
+        Err(StructPathError::Polars(_))
+    ));
+}
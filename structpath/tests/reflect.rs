@@ -0,0 +1,330 @@
+#![cfg(feature = "reflect")]
+
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, Value as ReflectValue};
+use structpath::{
+    load_descriptor_pool, message_field_info, message_fields_info, Path, StructPath,
+    StructPathError, Value,
+};
+use structpath_types::{FieldType, ProtoField, ProtoLabel};
+
+/// The descriptors of `reflect.proto`, checked in so that the tests do not
+/// need `protoc`, and regenerated with
+/// `protoc --include_imports --descriptor_set_out=tests/proto/reflect.bin --proto_path=tests/proto tests/proto/reflect.proto`
+fn pool() -> DescriptorPool {
+    load_descriptor_pool(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/proto/reflect.bin"
+    ))
+    .unwrap()
+}
+
+fn pet(descriptor: &MessageDescriptor, name: &str, birth_year: i32) -> DynamicMessage {
+    let mut pet = DynamicMessage::new(descriptor.clone());
+    pet.set_field_by_name("name", ReflectValue::String(name.to_string()));
+    pet.set_field_by_name("birth_year", ReflectValue::I32(birth_year));
+    pet.set_field_by_name("kind", ReflectValue::EnumNumber(1));
+    pet
+}
+
+fn build_user(pool: &DescriptorPool) -> DynamicMessage {
+    let pet_descriptor = pool.get_message_by_name("reflect.Pet").unwrap();
+    let mut user = DynamicMessage::new(pool.get_message_by_name("reflect.User").unwrap());
+    user.set_field_by_name("name", ReflectValue::String("John".to_string()));
+    user.set_field_by_name("id", ReflectValue::U64(7));
+    user.set_field_by_name("score", ReflectValue::F64(1.5));
+    user.set_field_by_name(
+        "pets",
+        ReflectValue::List(vec![
+            ReflectValue::Message(pet(&pet_descriptor, "Max", 2020)),
+            ReflectValue::Message(pet(&pet_descriptor, "Kitty", 2022)),
+        ]),
+    );
+    user.set_field_by_name(
+        "tags",
+        ReflectValue::List(vec![ReflectValue::String("admin".to_string())]),
+    );
+    user
+}
+
+#[test]
+fn test_dynamic_message() {
+    let user = build_user(&pool());
+
+    assert_eq!(user.get_as::<String>("name").unwrap(), "John");
+    assert_eq!(user.get_value("id").unwrap(), Value::Integer(7));
+    let mut large = user.clone();
+    large.set_field_by_name("id", ReflectValue::U64(u64::MAX));
+    assert_eq!(
+        large.get_value("id").unwrap(),
+        Value::BigUint(u64::MAX.into())
+    );
+    assert_eq!(user.get_value("score").unwrap(), Value::Float(1.5));
+    assert_eq!(user.get_value("active").unwrap(), Value::Boolean(false));
+    assert_eq!(user.get_as::<String>("pets[1].name").unwrap(), "Kitty");
+    assert_eq!(user.get_value("pets[0].kind").unwrap(), Value::Integer(1));
+    assert_eq!(user.get_value("pets").unwrap().try_list_len().unwrap(), 2);
+    assert_eq!(user.get_as::<String>("tags[0]").unwrap(), "admin");
+    assert_eq!(user.get_as::<String>("#6[1].#1").unwrap(), "Kitty");
+    assert_eq!(
+        user.get_value("favourite_pet").unwrap(),
+        Value::Option(None)
+    );
+    assert_eq!(
+        user.get_value("favourite_pet.name").unwrap(),
+        Value::Option(None)
+    );
+    assert_eq!(
+        user.get_value("pets[0]")
+            .unwrap()
+            .as_unboxed::<DynamicMessage>()
+            .value_fields(),
+        vec!["name", "birth_year", "kind"]
+    );
+}
+
+#[test]
+fn test_dynamic_message_errors() {
+    let user = build_user(&pool());
+
+    let err = user.get_value("pets[0].nmae").unwrap_err();
+    assert!(matches!(
+        err,
+        StructPathError::FieldNotFound { index: 1, .. }
+    ));
+    assert_eq!(err.suggestion(), Some("name"));
+    assert!(matches!(
+        user.get_value("name[0]"),
+        Err(StructPathError::FieldNotFound { .. })
+    ));
+    assert!(matches!(
+        user.get_value("pets.name"),
+        Err(StructPathError::FieldNotFound { .. })
+    ));
+    assert!(matches!(
+        user.get_value("pets[2].name"),
+        Err(StructPathError::IndexOutOfBounds {
+            index: 2,
+            component: 0,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_message_field_info() {
+    let pool = pool();
+    let descriptor = pool.get_message_by_name("reflect.User").unwrap();
+
+    let info = message_fields_info(&descriptor);
+    assert_eq!(info.name, "reflect.User");
+    assert_eq!(info.fields[1].r#type, FieldType::Integer);
+    assert_eq!(info.fields[7].r#type, FieldType::Unknown);
+    assert_eq!(info.proto_name.as_deref(), Some("reflect.User"));
+    assert_eq!(
+        info.fields[7].proto,
+        Some(ProtoField {
+            number: 8,
+            r#type: "map".to_string(),
+            label: ProtoLabel::Repeated,
+        })
+    );
+
+    let path = |path: &str| Path::from_str(path).unwrap();
+    assert_eq!(
+        message_field_info(&descriptor, &path("pets[0].birth_year"))
+            .unwrap()
+            .r#type,
+        FieldType::Integer
+    );
+    assert_eq!(
+        message_field_info(&descriptor, &path("pets[0].birth_year"))
+            .unwrap()
+            .proto,
+        Some(ProtoField {
+            number: 2,
+            r#type: "int32".to_string(),
+            label: ProtoLabel::Singular,
+        })
+    );
+    assert_eq!(
+        message_field_info(&descriptor, &path("#6[0].#2"))
+            .unwrap()
+            .name,
+        "birth_year"
+    );
+    assert_eq!(
+        message_field_info(&descriptor, &path("favourite_pet.name"))
+            .unwrap()
+            .r#type,
+        FieldType::Option(Box::new(FieldType::String))
+    );
+    assert_eq!(
+        message_field_info(&descriptor, &path("tags"))
+            .unwrap()
+            .r#type,
+        FieldType::Vec(Box::new(FieldType::String))
+    );
+    assert!(matches!(
+        message_field_info(&descriptor, &path("pets[0].nmae")),
+        Err(StructPathError::FieldNotFound { index: 1, .. })
+    ));
+}
+
+fn well_known<T: prost::Message>(pool: &DescriptorPool, name: &str, value: &T) -> ReflectValue {
+    let mut message = DynamicMessage::new(pool.get_message_by_name(name).unwrap());
+    message.transcode_from(value).unwrap();
+    ReflectValue::Message(message)
+}
+
+#[test]
+fn test_well_known_types() {
+    use prost::Message;
+
+    let pool = pool();
+    let mut metadata = prost_types::Struct::default();
+    metadata.fields.insert(
+        "source".to_string(),
+        prost_types::Value {
+            kind: Some(prost_types::value::Kind::StringValue("api".to_string())),
+        },
+    );
+    let pet = pet(
+        &pool.get_message_by_name("reflect.Pet").unwrap(),
+        "Max",
+        2020,
+    );
+
+    let mut event = DynamicMessage::new(pool.get_message_by_name("reflect.Event").unwrap());
+    let timestamp = prost_types::Timestamp {
+        seconds: 1_714_564_800,
+        nanos: 5,
+    };
+    let fields = [
+        (
+            "created_at",
+            well_known(&pool, "google.protobuf.Timestamp", &timestamp),
+        ),
+        (
+            "elapsed",
+            well_known(
+                &pool,
+                "google.protobuf.Duration",
+                &prost_types::Duration {
+                    seconds: 90,
+                    nanos: 0,
+                },
+            ),
+        ),
+        (
+            "note",
+            well_known(&pool, "google.protobuf.StringValue", &"hello".to_string()),
+        ),
+        (
+            "metadata",
+            well_known(&pool, "google.protobuf.Struct", &metadata),
+        ),
+        (
+            "payload",
+            well_known(
+                &pool,
+                "google.protobuf.Any",
+                &prost_types::Any {
+                    type_url: "type.googleapis.com/reflect.Pet".to_string(),
+                    value: pet.encode_to_vec(),
+                },
+            ),
+        ),
+    ];
+    for (name, value) in fields {
+        event.set_field_by_name(name, value);
+    }
+    event.set_field_by_name("checksum", ReflectValue::Bytes(vec![7, 8].into()));
+
+    let some = |value| Value::Option(Some(Box::new(value)));
+    assert_eq!(
+        event.get_value("created_at").unwrap(),
+        some(Value::DateTime {
+            seconds: 1_714_564_800,
+            nanos: 5
+        })
+    );
+    assert_eq!(
+        event.get_value("elapsed").unwrap(),
+        some(Value::Duration {
+            seconds: 90,
+            nanos: 0
+        })
+    );
+    assert_eq!(event.get_as::<String>("note").unwrap(), "hello");
+    assert_eq!(event.get_value("count").unwrap(), Value::Option(None));
+    assert_eq!(event.get_as::<String>("metadata.source").unwrap(), "api");
+    assert_eq!(event.get_as::<String>("payload.name").unwrap(), "Max");
+    assert_eq!(event.get_value("#7").unwrap(), Value::Bytes(vec![7, 8]));
+    assert!(matches!(
+        event.get_value("created_at.seconds"),
+        Err(StructPathError::FieldNotFound { .. })
+    ));
+
+    let info = message_fields_info(&pool.get_message_by_name("reflect.Event").unwrap());
+    let types: Vec<_> = info
+        .fields
+        .iter()
+        .map(|field| field.r#type.clone())
+        .collect();
+    let optional = |field_type| FieldType::Option(Box::new(field_type));
+    assert_eq!(
+        types,
+        vec![
+            optional(FieldType::DateTime),
+            optional(FieldType::Duration),
+            optional(FieldType::String),
+            optional(FieldType::Integer),
+            optional(FieldType::StructPath),
+            optional(FieldType::StructPath),
+            FieldType::Bytes,
+        ]
+    );
+}
+
+#[test]
+fn test_load_descriptor_set() {
+    assert!(pool().get_message_by_name("reflect.Pet").is_some());
+
+    assert!(matches!(
+        load_descriptor_pool("missing.proto"),
+        Err(StructPathError::Descriptor(_))
+    ));
+    assert!(matches!(
+        load_descriptor_pool("missing.bin"),
+        Err(StructPathError::Descriptor(_))
+    ));
+}
+
+/// Compiling `.proto` files needs `protoc`, so this test only checks the
+/// errors when it is not installed
+#[test]
+fn test_compile_proto() {
+    let proto = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/proto/reflect.proto");
+    let protoc = std::env::var_os("PROTOC").unwrap_or_else(|| "protoc".into());
+    let installed = std::process::Command::new(protoc)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    match load_descriptor_pool(proto) {
+        Ok(compiled) => {
+            let names = |pool: &DescriptorPool| {
+                let mut names: Vec<_> = pool
+                    .all_messages()
+                    .map(|message| message.full_name().to_string())
+                    .collect();
+                names.sort();
+                names
+            };
+            assert_eq!(names(&compiled), names(&pool()));
+        }
+        Err(err) => {
+            assert!(!installed, "{err}");
+            assert!(matches!(err, StructPathError::Descriptor(_)));
+        }
+    }
+}
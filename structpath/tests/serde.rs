@@ -0,0 +1,148 @@
+#![cfg(all(feature = "serde", feature = "derive"))]
+
+use serde_json::json;
+use structpath::{to_json, StructInfo, StructPath, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Dog,
+    Cat,
+}
+
+#[derive(StructInfo, StructPath, Debug, Clone, PartialEq)]
+struct Pet {
+    name: String,
+    birth_year: Option<i64>,
+    kind: Kind,
+}
+
+#[derive(StructInfo, StructPath, Debug, Clone, PartialEq)]
+struct User {
+    name: String,
+    score: f64,
+    active: bool,
+    tags: Vec<String>,
+    #[type_hint = "struct"]
+    favourite_pet: Option<Pet>,
+    #[type_hint = "struct"]
+    pets: Vec<Pet>,
+    kinds: Vec<Kind>,
+}
+
+fn build_user() -> User {
+    User {
+        name: "John".to_string(),
+        score: 1.5,
+        active: true,
+        tags: vec!["admin".to_string()],
+        favourite_pet: None,
+        pets: vec![
+            Pet {
+                name: "Max".to_string(),
+                birth_year: Some(2020),
+                kind: Kind::Dog,
+            },
+            Pet {
+                name: "Kitty".to_string(),
+                birth_year: None,
+                kind: Kind::Cat,
+            },
+        ],
+        kinds: vec![Kind::Dog],
+    }
+}
+
+#[test]
+fn test_to_json() {
+    assert_eq!(
+        to_json(&build_user()).unwrap(),
+        json!({
+            "name": "John",
+            "score": 1.5,
+            "active": true,
+            "tags": ["admin"],
+            "favourite_pet": null,
+            "pets": [
+                {"name": "Max", "birth_year": 2020},
+                {"name": "Kitty", "birth_year": null},
+            ],
+        })
+    );
+}
+
+#[test]
+fn test_serialize_value() {
+    let user = build_user();
+    let to_value = |path: &str| serde_json::to_value(user.get_value(path).unwrap());
+
+    assert_eq!(to_value("pets[1].name").unwrap(), json!("Kitty"));
+    assert_eq!(to_value("pets[0].birth_year").unwrap(), json!(2020));
+    assert_eq!(to_value("tags").unwrap(), json!(["admin"]));
+    assert_eq!(
+        to_value("pets[0]").unwrap(),
+        json!({"name": "Max", "birth_year": 2020})
+    );
+    assert_eq!(to_value("pets").unwrap()[1]["name"], json!("Kitty"));
+
+    // Nested structs still downcast to themselves
+    assert_eq!(user.get_as::<Pet>("pets[0]").unwrap(), user.pets[0]);
+
+    assert!(to_value("pets[0].kind").is_err());
+    assert!(to_value("kinds").is_err());
+    assert!(serde_json::to_value(Value::Boxed(Box::new(Kind::Dog))).is_err());
+}
+
+#[test]
+fn test_serialize_times() {
+    let datetime = Value::DateTime {
+        seconds: 1_714_564_800,
+        nanos: 500_000_000,
+    };
+    assert_eq!(
+        serde_json::to_value(&datetime).unwrap(),
+        json!("2024-05-01T12:00:00.500Z")
+    );
+    let before_epoch = Value::DateTime {
+        seconds: -1,
+        nanos: 1_000,
+    };
+    assert_eq!(
+        serde_json::to_value(&before_epoch).unwrap(),
+        json!("1969-12-31T23:59:59.000001Z")
+    );
+    let duration = Value::Duration {
+        seconds: -1,
+        nanos: -500_000_000,
+    };
+    assert_eq!(serde_json::to_value(&duration).unwrap(), json!("-1.500s"));
+    assert_eq!(
+        serde_json::to_value(Value::Date(19_844)).unwrap(),
+        json!("2024-05-01")
+    );
+    assert_eq!(
+        serde_json::to_value(Value::Date(-1)).unwrap(),
+        json!("1969-12-31")
+    );
+}
+
+#[test]
+fn test_serialize_numbers() {
+    let decimal = |mantissa, scale| Value::Decimal { mantissa, scale };
+    assert_eq!(
+        serde_json::to_value(decimal(-5, 3)).unwrap(),
+        json!("-0.005")
+    );
+    assert_eq!(
+        serde_json::to_value(decimal(1250, 2)).unwrap(),
+        json!("12.50")
+    );
+    assert_eq!(serde_json::to_value(decimal(7, 0)).unwrap(), json!("7"));
+    assert_eq!(
+        serde_json::to_value(Value::BigUint(u64::MAX.into())).unwrap(),
+        json!(u64::MAX)
+    );
+    assert_eq!(
+        serde_json::to_string(&Value::BigInt(-(10i128.pow(20)))).unwrap(),
+        "-100000000000000000000"
+    );
+}
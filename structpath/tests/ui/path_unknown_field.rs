@@ -0,0 +1,16 @@
+use structpath::{path, StructPath};
+
+#[derive(StructPath, Debug, Clone)]
+struct Pet {
+    name: String,
+}
+
+#[derive(StructPath, Debug, Clone)]
+struct User {
+    #[type_hint = "struct"]
+    pets: Vec<Pet>,
+}
+
+fn main() {
+    let _ = path!(User, pets[0].nmae);
+}
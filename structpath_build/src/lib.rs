@@ -0,0 +1,296 @@
+//! Build helpers deriving the `structpath` traits on the messages generated by
+//! `prost-build`
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() -> std::io::Result<()> {
+//!     structpath_build::configure(prost_build::Config::new())
+//!         .compile_protos(&["protos/user.proto"], &["protos"])
+//! }
+//! ```
+
+use prost_types::field_descriptor_proto::Type;
+use prost_types::{DescriptorProto, FieldDescriptorProto, FileDescriptorSet};
+use std::path::Path;
+
+/// The derives added to every generated message, and to the enums generated
+/// for oneofs
+const DERIVES: &str =
+    "#[derive(::structpath::StructPath, ::structpath::StructInfo, ::structpath::FromPaths)]";
+
+/// The attribute naming the `StructInfo` of messages after their
+/// `prost::Name::full_name`
+const PROTO_MESSAGE: &str = "#[proto_message]";
+
+/// Derive the `structpath` traits on the messages compiled with `config`
+///
+/// As the `StructInfo` derive refers to `structpath_types`, the crate including
+/// the generated code depends on it as well as on `structpath` and `prost`.
+pub fn configure(config: prost_build::Config) -> Builder {
+    Builder { config }
+}
+
+/// A `prost_build::Config` deriving the `structpath` traits, see [`configure`]
+#[derive(Debug)]
+pub struct Builder {
+    config: prost_build::Config,
+}
+
+impl Builder {
+    /// Compile `protos`, as `prost_build::Config::compile_protos` does
+    pub fn compile_protos(
+        mut self,
+        protos: &[impl AsRef<Path>],
+        includes: &[impl AsRef<Path>],
+    ) -> std::io::Result<()> {
+        let file_descriptor_set = self.config.load_fds(protos, includes)?;
+        self.compile_fds(file_descriptor_set)
+    }
+
+    /// Compile the files of `file_descriptor_set`, as
+    /// `prost_build::Config::compile_fds` does
+    pub fn compile_fds(mut self, file_descriptor_set: FileDescriptorSet) -> std::io::Result<()> {
+        annotate(&mut self.config, &file_descriptor_set);
+        self.config.compile_fds(file_descriptor_set)
+    }
+}
+
+/// Add the attributes needed by the `structpath` derives to `config`
+///
+/// Every message derives `StructPath`, `StructInfo` and `FromPaths`, and
+/// fields, including those of nested messages, get a type hint: `struct`
+/// for messages, and `timestamp` and `duration` for the `Timestamp` and
+/// `Duration` well-known types, which convert to native values. The wrapper
+/// types, such as `StringValue`, are generated as optional scalars and need no
+/// hint. Enum fields, generated as `i32`s, get `enum` and are integers, as
+/// for `DynamicMessage`s, and map fields get `map` and are navigated by key,
+/// as in `labels.team`. The enums generated for oneofs derive the traits too,
+/// with a field per variant, as in `favorite_parent.father.name`: the derives
+/// tell the fields holding them by their `#[prost(oneof)]` attribute, so that
+/// only the variants get hints. Type names
+/// are enabled so that `StructInfo` reports the fully qualified name of each
+/// message.
+///
+/// Messages using `Struct`, `Value` or `Any` need the `prost` feature of
+/// `structpath`, which implements its traits on the `prost_types` messages.
+pub fn annotate(config: &mut prost_build::Config, file_descriptor_set: &FileDescriptorSet) {
+    config.enable_type_names();
+    config.message_attribute(".", DERIVES);
+    config.message_attribute(".", PROTO_MESSAGE);
+    let hints = field_hints(file_descriptor_set);
+    for (path, hint) in &hints.fields {
+        config.field_attribute(path, format!("#[type_hint = \"{hint}\"]"));
+    }
+    for path in &hints.oneofs {
+        config.enum_attribute(path, DERIVES);
+    }
+}
+
+/// The type hint of the fields holding messages of type `type_name`, if any
+fn message_hint(type_name: &str) -> Option<&'static str> {
+    match type_name {
+        ".google.protobuf.Timestamp" => Some("timestamp"),
+        ".google.protobuf.Duration" => Some("duration"),
+        ".google.protobuf.Struct" | ".google.protobuf.Value" | ".google.protobuf.Any" => {
+            Some("struct")
+        }
+        // Wrappers are generated as scalars, and the other well-known types
+        // are left boxed
+        name if name.starts_with(".google.protobuf.") => None,
+        _ => Some("struct"),
+    }
+}
+
+/// The type hints of the fields of a file descriptor set, and the oneofs
+#[derive(Debug, Default, PartialEq)]
+struct FieldHints {
+    /// The fully qualified paths of the fields, as in `.package.Message.field`,
+    /// or `.package.Message.oneof.field` for the members of a oneof, with
+    /// their type hint
+    ///
+    /// Oneofs themselves get no hint: `prost-build` would also give it to
+    /// their members, as the path of a oneof is a prefix of theirs.
+    fields: Vec<(String, &'static str)>,
+    /// The fully qualified paths of the oneofs, as in `.package.Message.oneof`
+    oneofs: Vec<String>,
+}
+
+fn field_hints(file_descriptor_set: &FileDescriptorSet) -> FieldHints {
+    let mut hints = FieldHints::default();
+    for file in &file_descriptor_set.file {
+        let scope = match file.package() {
+            "" => String::new(),
+            package => format!(".{package}"),
+        };
+        for message in &file.message_type {
+            message_field_hints(&scope, message, &mut hints);
+        }
+    }
+    hints
+}
+
+/// The type hint of `field`
+fn field_hint(field: &FieldDescriptorProto, map_entries: &[String]) -> Option<&'static str> {
+    match field.r#type() {
+        Type::Enum => Some("enum"),
+        Type::Message | Type::Group if map_entries.iter().any(|e| e == field.type_name()) => {
+            Some("map")
+        }
+        Type::Message | Type::Group => message_hint(field.type_name()),
+        _ => None,
+    }
+}
+
+fn message_field_hints(scope: &str, message: &DescriptorProto, hints: &mut FieldHints) {
+    let path = format!("{scope}.{}", message.name());
+    let map_entries: Vec<String> = message
+        .nested_type
+        .iter()
+        .filter(|nested| is_map_entry(nested))
+        .map(|nested| format!("{path}.{}", nested.name()))
+        .collect();
+
+    // Members of real oneofs are variants of the generated enum, while
+    // proto3 `optional` fields are plain fields in a synthetic oneof
+    let oneof = |field: &FieldDescriptorProto| match field.oneof_index {
+        Some(index) if !field.proto3_optional() => message.oneof_decl.get(index as usize),
+        _ => None,
+    };
+
+    for field in &message.field {
+        let Some(hint) = field_hint(field, &map_entries) else {
+            continue;
+        };
+        let field_path = match oneof(field) {
+            Some(oneof) => format!("{path}.{}.{}", oneof.name(), field.name()),
+            None => format!("{path}.{}", field.name()),
+        };
+        hints.fields.push((field_path, hint));
+    }
+
+    for (index, oneof) in message.oneof_decl.iter().enumerate() {
+        let is_real = message
+            .field
+            .iter()
+            .any(|field| field.oneof_index == Some(index as i32) && !field.proto3_optional());
+        if is_real {
+            hints.oneofs.push(format!("{path}.{}", oneof.name()));
+        }
+    }
+
+    for nested in &message.nested_type {
+        if !is_map_entry(nested) {
+            message_field_hints(&path, nested, hints);
+        }
+    }
+}
+
+fn is_map_entry(message: &DescriptorProto) -> bool {
+    message
+        .options
+        .as_ref()
+        .is_some_and(|options| options.map_entry())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::field_descriptor_proto::Label;
+    use prost_types::{FileDescriptorProto, MessageOptions, OneofDescriptorProto};
+
+    fn field(name: &str, r#type: Type, type_name: Option<&str>) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            r#type: Some(r#type as i32),
+            type_name: type_name.map(str::to_string),
+            label: Some(Label::Optional as i32),
+            ..Default::default()
+        }
+    }
+
+    fn message(name: &str, field: Vec<FieldDescriptorProto>) -> DescriptorProto {
+        DescriptorProto {
+            name: Some(name.to_string()),
+            field,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_field_hints() {
+        let mut toy = field("favourite_toy", Type::Message, Some(".sample.User.Pet.Toy"));
+        toy.oneof_index = Some(0);
+        toy.proto3_optional = Some(true);
+        let mut pet = message("Pet", vec![field("name", Type::String, None), toy]);
+        pet.nested_type.push(message("Toy", vec![]));
+
+        let mut labels_entry = message("LabelsEntry", vec![]);
+        labels_entry.options = Some(MessageOptions {
+            map_entry: Some(true),
+            ..Default::default()
+        });
+        let mut father = field("father", Type::Message, Some(".sample.User.Pet"));
+        father.oneof_index = Some(0);
+        let mut nickname = field("nickname", Type::String, None);
+        nickname.oneof_index = Some(0);
+        let mut rank = field("rank", Type::Enum, Some(".sample.User.Loyalty"));
+        rank.oneof_index = Some(0);
+
+        let mut user = message(
+            "User",
+            vec![
+                field("name", Type::String, None),
+                field("loyalty", Type::Enum, Some(".sample.User.Loyalty")),
+                field("favourite_pet", Type::Message, Some(".sample.User.Pet")),
+                field(
+                    "created_at",
+                    Type::Message,
+                    Some(".google.protobuf.Timestamp"),
+                ),
+                field("alias", Type::Message, Some(".google.protobuf.StringValue")),
+                field("labels", Type::Message, Some(".sample.User.LabelsEntry")),
+                father,
+                nickname,
+                rank,
+            ],
+        );
+        user.nested_type = vec![pet, labels_entry];
+        user.oneof_decl.push(OneofDescriptorProto {
+            name: Some("parent".to_string()),
+            ..Default::default()
+        });
+
+        let file_descriptor_set = FileDescriptorSet {
+            file: vec![
+                FileDescriptorProto {
+                    package: Some("sample".to_string()),
+                    message_type: vec![user],
+                    ..Default::default()
+                },
+                FileDescriptorProto {
+                    message_type: vec![message(
+                        "Group",
+                        vec![field("admin", Type::Message, Some(".sample.User"))],
+                    )],
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let hints = field_hints(&file_descriptor_set);
+        assert_eq!(
+            hints.fields,
+            vec![
+                (".sample.User.loyalty".to_string(), "enum"),
+                (".sample.User.favourite_pet".to_string(), "struct"),
+                (".sample.User.created_at".to_string(), "timestamp"),
+                (".sample.User.labels".to_string(), "map"),
+                (".sample.User.parent.father".to_string(), "struct"),
+                (".sample.User.parent.rank".to_string(), "enum"),
+                (".sample.User.Pet.favourite_toy".to_string(), "struct"),
+                (".Group.admin".to_string(), "struct"),
+            ]
+        );
+        assert_eq!(hints.oneofs, vec![".sample.User.parent".to_string()]);
+    }
+}
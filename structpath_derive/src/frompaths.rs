@@ -0,0 +1,194 @@
+use crate::structinfo::{
+    get_angle_bracketed_inner, innermost_type, leaf_dispatch, oneof_variants, parse_field_type,
+    OneofVariant,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use structpath_types::FieldType;
+use syn::{Data, DeriveInput, Fields, Type};
+
+/// The type inside `Option` or `Vec`
+fn inner_type(ty: &Type) -> &Type {
+    match ty {
+        Type::Path(type_path) => get_angle_bracketed_inner(type_path).unwrap_or(ty),
+        _ => ty,
+    }
+}
+
+/// Whether `field_type` has an unknown type inside its `Option` and `Vec`
+/// layers
+fn wraps_unknown(field_type: &FieldType) -> bool {
+    match field_type {
+        FieldType::Option(inner) | FieldType::Vec(inner) => wraps_unknown(inner),
+        FieldType::Unknown => true,
+        _ => false,
+    }
+}
+
+/// Call `method` on the dispatch between `PathLeaf` and boxing of `ty`
+fn dispatch(ty: &Type, method: TokenStream) -> TokenStream {
+    leaf_dispatch(quote! { ::structpath::Leaf::<#ty>::new() }, method)
+}
+
+/// Expression reading a field of type `ty` at `path` from `source`, as a
+/// `Result`
+///
+/// Fields of unknown types are read through their `PathLeaf` implementation,
+/// and set to their `Default` without one, as are the `Option`s and `Vec`s
+/// of them.
+fn read_field(field_type: &FieldType, ty: &Type, path: TokenStream) -> TokenStream {
+    match field_type {
+        FieldType::Unknown => dispatch(ty, quote! { read_or_default(source, #path) }),
+        FieldType::Option(_) | FieldType::Vec(_) if wraps_unknown(field_type) => {
+            let is_leaf = dispatch(innermost_type(ty), quote! { is_leaf() });
+            let read = read_wrapped(field_type, ty, path);
+            quote! {
+                if #is_leaf {
+                    #read
+                } else {
+                    Ok::<_, ::structpath::StructPathError>(Default::default())
+                }
+            }
+        }
+        _ => read_wrapped(field_type, ty, path),
+    }
+}
+
+/// Expression reading a field like [`read_field`], once the type of any
+/// unknown leaf is known to be a `PathLeaf`
+fn read_wrapped(field_type: &FieldType, ty: &Type, path: TokenStream) -> TokenStream {
+    match field_type {
+        FieldType::String
+        | FieldType::Integer
+        | FieldType::Float
+        | FieldType::Boolean
+        | FieldType::Decimal
+        | FieldType::BigInt
+        | FieldType::DateTime
+        | FieldType::Date
+        | FieldType::Duration
+        | FieldType::Bytes => quote! {
+            ::structpath::read_value::<#ty>(source, #path, false)
+        },
+        FieldType::StructPath => quote! {
+            <#ty as ::structpath::FromPaths>::from_paths(source, #path)
+        },
+        FieldType::Option(inner) => match inner.as_ref() {
+            FieldType::String
+            | FieldType::Integer
+            | FieldType::Float
+            | FieldType::Boolean
+            | FieldType::Decimal
+            | FieldType::BigInt
+            | FieldType::DateTime
+            | FieldType::Date
+            | FieldType::Duration
+            | FieldType::Bytes => {
+                quote! {
+                    ::structpath::read_value::<#ty>(source, #path, true)
+                }
+            }
+            FieldType::Unknown => dispatch(inner_type(ty), quote! { read_option(source, #path) }),
+            _ => {
+                let inner = read_wrapped(inner, inner_type(ty), path.clone());
+                quote! {
+                    if source.is_present(#path) {
+                        #inner.map(Some)
+                    } else {
+                        Ok(None)
+                    }
+                }
+            }
+        },
+        FieldType::Vec(elem) => {
+            let elem = read_wrapped(elem, inner_type(ty), quote! { element });
+            quote! {
+                ::structpath::read_list(source, #path, |element| #elem)
+            }
+        }
+        FieldType::Unknown => dispatch(ty, quote! { read_value(source, #path) }),
+    }
+}
+
+fn not_derivable() -> TokenStream {
+    quote! {
+        compile_error!("FromPaths can only be derived for structs with named fields and for enums whose variants each hold one value");
+    }
+}
+
+/// Implement `FromPaths` for an enum generated by prost for a oneof: the
+/// active variant is the first one with a value present below its field
+fn derive_oneof_impl(type_name: &syn::Ident, variants: &[OneofVariant]) -> TokenStream {
+    let variants = variants.iter().map(|variant| {
+        let ident = variant.ident;
+        let name = &variant.field.name;
+        let read = read_field(&variant.field.r#type, variant.ty, quote! { &path });
+        quote! {
+            let path = ::structpath::child_path(prefix, #name);
+            if source.is_present(&path) {
+                return Ok(Self::#ident(#read?));
+            }
+        }
+    });
+
+    quote! {
+        impl ::structpath::FromPaths for #type_name {
+            fn from_paths(
+                source: &dyn ::structpath::PathSource,
+                prefix: &[::structpath::PathComponent],
+            ) -> Result<Self, ::structpath::StructPathError> {
+                #(#variants)*
+                let path = ::structpath::Path { components: prefix.to_vec() };
+                Err(::structpath::StructPathError::MissingPath(path.to_string()))
+            }
+        }
+    }
+}
+
+pub fn derive_from_paths_impl(input: DeriveInput) -> TokenStream {
+    let type_name = input.ident;
+
+    let fields: Vec<TokenStream> = match input.data {
+        Data::Struct(data_struct) if matches!(data_struct.fields, Fields::Named(_)) => {
+            if let Fields::Named(fields_named) = data_struct.fields {
+                fields_named
+                    .named
+                    .iter()
+                    .map(|field| {
+                        let field_name = field.ident.clone().unwrap();
+                        let field_type = parse_field_type(&field.ty, &field.attrs);
+                        let read = read_field(&field_type, &field.ty, quote! { &path });
+                        quote! {
+                            #field_name: {
+                                let path = ::structpath::child_path(prefix, stringify!(#field_name));
+                                #read?
+                            }
+                        }
+                    })
+                    .collect()
+            } else {
+                unreachable!()
+            }
+        }
+        Data::Enum(data_enum) => {
+            return match oneof_variants(&data_enum) {
+                Some(variants) => derive_oneof_impl(&type_name, &variants),
+                None => not_derivable(),
+            }
+        }
+        _ => return not_derivable(),
+    };
+
+    quote! {
+        impl ::structpath::FromPaths for #type_name {
+            fn from_paths(
+                source: &dyn ::structpath::PathSource,
+                prefix: &[::structpath::PathComponent],
+            ) -> Result<Self, ::structpath::StructPathError> {
+                Ok(Self {
+                    #(#fields),*
+                })
+            }
+        }
+    }
+}
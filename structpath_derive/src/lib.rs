@@ -1,6 +1,10 @@
+mod frompaths;
+mod path;
 mod structinfo;
 mod structpath;
 
+use frompaths::derive_from_paths_impl;
+use path::{path_impl, PathInput};
 use proc_macro::TokenStream;
 use structinfo::derive_struct_info_impl;
 use structpath::derive_struct_path_impl;
@@ -12,8 +16,33 @@ pub fn derive_struct_path(input: TokenStream) -> TokenStream {
     derive_struct_path_impl(input).into()
 }
 
-#[proc_macro_derive(StructInfo, attributes(type_hint))]
+/// Describe the fields of a struct, see `structpath::StructInfo`
+///
+/// Fields of prost-generated messages carry the protobuf metadata of their
+/// `#[prost(...)]` attribute. A message marked `#[proto_message]` is also
+/// named after its `prost::Name::full_name`.
+#[proc_macro_derive(StructInfo, attributes(type_hint, proto_message))]
 pub fn derive_struct_info(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     derive_struct_info_impl(input).into()
 }
+
+/// Build a struct back from the values at its paths, see `structpath::FromPaths`
+#[proc_macro_derive(FromPaths, attributes(type_hint))]
+pub fn derive_from_paths(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_from_paths_impl(input).into()
+}
+
+/// Statically-checked path: `path!(User, pets[0].name)`
+///
+/// Expands to an accessor `Fn(&User) -> Result<&String, StructPathError>`,
+/// returning `Copy` values such as `i64` directly rather than references.
+/// Every component must name a field of a type deriving `StructPath`, so a
+/// typo is reported at compile time, as in ``no field `nmae` in `Pet` ``,
+/// instead of as a `FieldNotFound` error.
+#[proc_macro]
+pub fn path(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as PathInput);
+    path_impl(input).into()
+}
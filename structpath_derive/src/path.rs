@@ -0,0 +1,237 @@
+use crate::structinfo::{get_angle_bracketed_inner, OneofVariant};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::{bracketed, Field, Ident, LitInt, Token, Type};
+
+/// Input of the `path!` macro: `path!(User, pets[0].name)`
+pub struct PathInput {
+    root: Type,
+    components: Vec<(Ident, Option<LitInt>)>,
+}
+
+impl Parse for PathInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let root = input.parse()?;
+        input.parse::<Token![,]>()?;
+
+        let mut components = Vec::new();
+        loop {
+            let field: Ident = input.parse()?;
+            let index = if input.peek(syn::token::Bracket) {
+                let content;
+                bracketed!(content in input);
+                let index: LitInt = content.parse()?;
+                index.base10_parse::<usize>()?;
+                Some(index)
+            } else {
+                None
+            };
+            components.push((field, index));
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![.]>()?;
+        }
+
+        Ok(Self { root, components })
+    }
+}
+
+fn getter_name(field: &Ident) -> Ident {
+    format_ident!("__structpath_get_{}", field)
+}
+
+fn indexer_name(field: &Ident) -> Ident {
+    format_ident!("__structpath_index_{}", field)
+}
+
+/// Return the inner type if `ty` is `wrapper<inner>` (e.g. `Option<T>`)
+fn unwrap_generic<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    match ty {
+        Type::Path(type_path) => match type_path.path.segments.last() {
+            Some(segment) if segment.ident == wrapper => get_angle_bracketed_inner(type_path),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Build an expression turning `expr: &ty` into `Result<&leaf, _>`, unwrapping
+/// any `Option` layers, and return it together with the leaf type
+fn unwrap_options(ty: &Type, expr: TokenStream) -> (TokenStream, &Type) {
+    match unwrap_generic(ty, "Option") {
+        Some(inner) => {
+            let (inner_expr, leaf) = unwrap_options(inner, quote! { value });
+            let expr = quote! {
+                match #expr.as_ref() {
+                    Some(value) => #inner_expr,
+                    None => Err(::structpath::StructPathError::NullValue),
+                }
+            };
+            (expr, leaf)
+        }
+        None => (quote! { Ok(#expr) }, ty),
+    }
+}
+
+/// Typed accessors backing the `path!` macro
+///
+/// For every field, a getter returning a reference to the field with options
+/// unwrapped, and for vector fields an indexer returning a reference to the
+/// element. They share the visibility of the field.
+pub fn field_accessors<'a>(
+    type_name: &Ident,
+    fields: impl Iterator<Item = &'a Field>,
+) -> TokenStream {
+    let accessors = fields.map(|field| {
+        let field_name = field.ident.as_ref().unwrap();
+        let vis = &field.vis;
+
+        let getter = getter_name(field_name);
+        let (get_expr, leaf) = unwrap_options(&field.ty, quote! { (&self.#field_name) });
+        let get = quote! {
+            #[doc(hidden)]
+            #vis fn #getter(&self) -> Result<&#leaf, ::structpath::StructPathError> {
+                #get_expr
+            }
+        };
+
+        let index = unwrap_generic(leaf, "Vec").map(|elem_type| {
+            let indexer = indexer_name(field_name);
+            let (elem_expr, elem_leaf) = unwrap_options(elem_type, quote! { elem });
+            quote! {
+                #[doc(hidden)]
+                #vis fn #indexer(&self, index: usize) -> Result<&#elem_leaf, ::structpath::StructPathError> {
+                    let vec = self.#getter()?;
+                    let elem = vec.get(index).ok_or_else(|| {
+                        ::structpath::StructPathError::index_out_of_bounds(
+                            stringify!(#field_name),
+                            index,
+                            vec.len(),
+                            &[::structpath::PathComponent::ArrayIndex(
+                                stringify!(#field_name).to_string(),
+                                index,
+                            )],
+                            stringify!(#type_name),
+                        )
+                    })?;
+                    #elem_expr
+                }
+            }
+        });
+
+        quote! {
+            #get
+            #index
+        }
+    });
+
+    quote! {
+        #(#accessors)*
+    }
+}
+
+/// Typed accessors backing the `path!` macro for the enum generated by prost
+/// for a oneof
+///
+/// The getter of each variant returns a reference to its value, and fails
+/// with `NullValue` when another variant is active, as for a `None` field.
+pub fn variant_accessors(vis: &syn::Visibility, variants: &[OneofVariant]) -> TokenStream {
+    let accessors = variants.iter().map(|variant| {
+        let ident = variant.ident;
+        let ty = variant.ty;
+        let getter = getter_name(&format_ident!("{}", variant.field.name));
+        quote! {
+            #[doc(hidden)]
+            #vis fn #getter(&self) -> Result<&#ty, ::structpath::StructPathError> {
+                match self {
+                    Self::#ident(value) => Ok(value),
+                    _ => Err(::structpath::StructPathError::NullValue),
+                }
+            }
+        }
+    });
+
+    quote! {
+        #(#accessors)*
+    }
+}
+
+pub fn path_impl(input: PathInput) -> TokenStream {
+    let root = &input.root;
+    // The components of the path, to report errors against the whole path
+    let components = input.components.iter().map(|(field, index)| match index {
+        Some(index) => quote! {
+            ::structpath::PathComponent::ArrayIndex(stringify!(#field).to_string(), #index)
+        },
+        None => quote! { ::structpath::PathComponent::Field(stringify!(#field).to_string()) },
+    });
+    let components = quote! { [#(#components),*] };
+    let steps = input
+        .components
+        .iter()
+        .enumerate()
+        .map(|(depth, (field, index))| {
+            let (accessor, args, params, message, map_err) = match index {
+                Some(index) => (
+                    indexer_name(field),
+                    quote! { (#index) },
+                    quote! { , _index: usize },
+                    format!("no list field `{field}` in `{{Self}}`"),
+                    quote! { .map_err(|e| e.nested_at(&#components, #depth)) },
+                ),
+                None => (
+                    getter_name(field),
+                    quote! { () },
+                    quote! {},
+                    format!("no field `{field}` in `{{Self}}`"),
+                    quote! {},
+                ),
+            };
+            // Types without the accessor fall back to the method of `Fallback`,
+            // whose bound fails with an error naming the field and the type.
+            // Past such a field, `Missing` satisfies the bounds so only the
+            // first unknown field is reported.
+            quote_spanned! { field.span() =>
+                let value = {
+                    #[diagnostic::on_unimplemented(
+                        message = #message,
+                        label = "unknown field",
+                        note = "paths go through the fields visible here of types deriving `StructPath`"
+                    )]
+                    trait NoField {}
+                    #[diagnostic::do_not_recommend]
+                    impl NoField for Missing {}
+                    #[allow(dead_code)]
+                    trait Fallback {
+                        fn #accessor(&self #params) -> Result<&Missing, ::structpath::StructPathError>
+                        where
+                            Self: NoField,
+                        {
+                            unreachable!("path! fallback is never called: its `NoField` bound fails to compile first")
+                        }
+                    }
+                    impl<T: ?Sized> Fallback for T {}
+                    value.#accessor #args #map_err?
+                };
+            }
+        });
+
+    quote! {
+        {
+            let accessor = ::structpath::typed_accessor::<#root, _, _>(|value| {
+                #[allow(dead_code)]
+                struct Missing;
+                #(#steps)*
+                Ok(value)
+            });
+            #[allow(unused_imports)]
+            use ::structpath::{CopyAccessor as _, RefAccessor as _};
+            (&::structpath::Accessor::of(&accessor))
+                .output()
+                .finish(accessor)
+        }
+    }
+}
@@ -1,13 +1,13 @@
 use proc_macro2::TokenStream;
 use quote::quote;
-use structpath_types::FieldType;
+use structpath_types::{FieldInfo, FieldType, ProtoField, ProtoLabel};
 use syn::PathArguments::AngleBracketed;
 use syn::{
-    AngleBracketedGenericArguments, Attribute, Data, DeriveInput, Expr, Fields, GenericArgument,
-    Lit, Meta, Type,
+    punctuated::Punctuated, AngleBracketedGenericArguments, Attribute, Data, DataEnum, DeriveInput,
+    Expr, Fields, GenericArgument, Ident, Lit, Meta, Token, Type,
 };
 
-fn get_angle_bracketed_inner(type_path: &syn::TypePath) -> Option<&Type> {
+pub fn get_angle_bracketed_inner(type_path: &syn::TypePath) -> Option<&Type> {
     type_path.path.segments.last().and_then(|segment| {
         if let AngleBracketed(AngleBracketedGenericArguments { args, .. }) = &segment.arguments {
             args.first().and_then(|arg| {
@@ -23,7 +23,14 @@ fn get_angle_bracketed_inner(type_path: &syn::TypePath) -> Option<&Type> {
     })
 }
 
-fn is_structpath(attrs: &[Attribute]) -> bool {
+/// The value of the `type_hint` attribute: `struct` for nested structs,
+/// `timestamp`, `date` and `duration` for times, `enum` for the `i32` numbers
+/// of protobuf enums, `map` for maps navigated by key, and any other value,
+/// such as `boxed`, for a type named like a known one but unrelated to it
+///
+/// The fields of prost-generated messages holding a oneof, marked
+/// `#[prost(oneof = "...")]`, are structs without a hint.
+fn type_hint(attrs: &[Attribute]) -> Option<String> {
     for attr in attrs {
         // Check if this is our type_hint attribute
         if attr.path().is_ident("type_hint") {
@@ -33,21 +40,21 @@ fn is_structpath(attrs: &[Attribute]) -> bool {
                     // #[type_hint = "struct"]
                     if let Expr::Lit(expr_lit) = &meta_name_value.value {
                         if let Lit::Str(lit_str) = &expr_lit.lit {
-                            return lit_str.value() == "struct";
+                            return Some(lit_str.value());
                         }
                     }
                 }
                 Meta::List(meta_list) => {
                     // #[type_hint("struct")]
                     if let Ok(lit_str) = syn::parse2::<syn::LitStr>(meta_list.tokens.clone()) {
-                        return lit_str.value() == "struct";
+                        return Some(lit_str.value());
                     }
                 }
-                _ => return false,
+                _ => return None,
             }
         }
     }
-    false
+    is_prost_oneof(attrs).then(|| "struct".to_string())
 }
 
 pub fn parse_field_type(field_type: &Type, attrs: &[Attribute]) -> FieldType {
@@ -57,32 +64,278 @@ pub fn parse_field_type(field_type: &Type, attrs: &[Attribute]) -> FieldType {
                 let segment_name = segment.ident.to_string();
 
                 match segment_name.as_str() {
-                    "String" => FieldType::String,
-                    "i64" => FieldType::Integer,
-                    "f64" => FieldType::Float,
-                    "bool" => FieldType::Boolean,
                     "Vec" => {
-                        let inner_type =
-                            parse_field_type(get_angle_bracketed_inner(type_path).unwrap(), attrs);
-                        FieldType::Vec(Box::new(inner_type))
+                        let inner = get_angle_bracketed_inner(type_path).unwrap();
+                        if is_u8(inner) {
+                            return FieldType::Bytes;
+                        }
+                        FieldType::Vec(Box::new(parse_field_type(inner, attrs)))
                     }
                     "Option" => {
                         let inner_type =
                             parse_field_type(get_angle_bracketed_inner(type_path).unwrap(), attrs);
                         FieldType::Option(Box::new(inner_type))
                     }
-                    _ if is_structpath(attrs) => FieldType::StructPath,
-                    _ => FieldType::Unknown,
+                    // The hint takes precedence over the name of the type
+                    name => match type_hint(attrs).as_deref() {
+                        Some("struct") => FieldType::StructPath,
+                        Some("timestamp") => FieldType::DateTime,
+                        Some("date") => FieldType::Date,
+                        Some("duration") => FieldType::Duration,
+                        Some("enum") => FieldType::Integer,
+                        Some("map") => FieldType::StructPath,
+                        Some(_) => FieldType::Unknown,
+                        None => match name {
+                            "String" => FieldType::String,
+                            "i64" => FieldType::Integer,
+                            "f64" => FieldType::Float,
+                            "bool" => FieldType::Boolean,
+                            // `bytes::Bytes`, as generated by prost for
+                            // `bytes` fields with the `bytes` option
+                            "Bytes" => FieldType::Bytes,
+                            _ => FieldType::Unknown,
+                        },
+                    },
                 }
             }
             None => FieldType::Unknown,
         },
+        syn::Type::Array(array) if is_u8(&array.elem) => FieldType::Bytes,
         _ => FieldType::Unknown,
     }
 }
 
+fn is_u8(field_type: &Type) -> bool {
+    matches!(field_type, syn::Type::Path(type_path) if type_path.path.is_ident("u8"))
+}
+
+/// The string literal assigned in a `name = "value"` attribute argument
+fn lit_str_value(meta: &syn::MetaNameValue) -> Option<String> {
+    match &meta.value {
+        Expr::Lit(expr_lit) => match &expr_lit.lit {
+            Lit::Str(lit_str) => Some(lit_str.value()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The arguments of the `#[prost(...)]` attribute of a field, if any
+fn prost_args(attrs: &[Attribute]) -> Option<Punctuated<Meta, Token![,]>> {
+    let attr = attrs.iter().find(|attr| attr.path().is_ident("prost"))?;
+    attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        .ok()
+}
+
+/// Whether the field holds a oneof, as in
+/// `#[prost(oneof = "user::FavoriteParent", tags = "9, 10")]`
+fn is_prost_oneof(attrs: &[Attribute]) -> bool {
+    prost_args(attrs).is_some_and(|args| args.iter().any(|arg| arg.path().is_ident("oneof")))
+}
+
+/// Protobuf metadata of a field of a prost-generated message, from its
+/// `#[prost(int64, optional, tag = "2")]` attribute
+///
+/// Oneofs, tagged with several `tags`, have no single field number and are
+/// left without metadata.
+pub fn proto_field(attrs: &[Attribute]) -> Option<ProtoField> {
+    let args = prost_args(attrs)?;
+
+    let mut number = None;
+    let mut r#type = None;
+    let mut label = ProtoLabel::Singular;
+    for arg in &args {
+        let Some(name) = arg.path().get_ident().map(|ident| ident.to_string()) else {
+            continue;
+        };
+        match (name.as_str(), arg) {
+            ("tag", Meta::NameValue(meta)) => {
+                number = lit_str_value(meta).and_then(|tag| tag.parse().ok());
+            }
+            ("optional", _) => label = ProtoLabel::Optional,
+            ("required", _) => label = ProtoLabel::Required,
+            ("repeated", _) => label = ProtoLabel::Repeated,
+            ("enumeration", _) => r#type = Some("enum".to_string()),
+            ("map" | "hash_map" | "btree_map", _) => {
+                r#type = Some("map".to_string());
+                label = ProtoLabel::Repeated;
+            }
+            (
+                "double" | "float" | "int32" | "int64" | "uint32" | "uint64" | "sint32" | "sint64"
+                | "fixed32" | "fixed64" | "sfixed32" | "sfixed64" | "bool" | "string" | "bytes"
+                | "message" | "group",
+                _,
+            ) => r#type = Some(name),
+            _ => {}
+        }
+    }
+
+    Some(ProtoField {
+        number: number?,
+        r#type: r#type?,
+        label,
+    })
+}
+
+/// A variant of an enum generated by prost for a oneof, holding the value of
+/// one of its fields
+pub struct OneofVariant<'a> {
+    pub ident: &'a Ident,
+    pub ty: &'a Type,
+    pub attrs: &'a [Attribute],
+    /// The field of the oneof, named after the variant in snake case, of the
+    /// type of its value
+    pub field: FieldInfo,
+}
+
+/// The variants of an enum whose variants each hold one value, as generated
+/// by prost for oneofs, or `None` for other enums
+pub fn oneof_variants(data_enum: &DataEnum) -> Option<Vec<OneofVariant<'_>>> {
+    data_enum
+        .variants
+        .iter()
+        .map(|variant| match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                let ty = &fields.unnamed[0].ty;
+                Some(OneofVariant {
+                    ident: &variant.ident,
+                    ty,
+                    attrs: &variant.attrs,
+                    field: FieldInfo {
+                        name: snake_case(&variant.ident.to_string()),
+                        r#type: parse_field_type(ty, &variant.attrs),
+                        nested: None,
+                        proto: proto_field(&variant.attrs),
+                    },
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The snake case field name of a variant, as in `favourite_pet` for
+/// `FavouritePet`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// Whether the struct is a prost-generated message marked with
+/// `#[proto_message]`, implementing `prost::Name`
+fn is_proto_message(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("proto_message"))
+}
+
+/// The type inside `Option` and `Vec` layers of a field type: its nested
+/// struct or its leaf
+pub fn innermost_type(field_type: &Type) -> &Type {
+    match field_type {
+        syn::Type::Path(type_path) => match type_path.path.segments.last() {
+            Some(segment) if segment.ident == "Option" || segment.ident == "Vec" => {
+                match get_angle_bracketed_inner(type_path) {
+                    Some(inner) => innermost_type(inner),
+                    None => field_type,
+                }
+            }
+            _ => field_type,
+        },
+        _ => field_type,
+    }
+}
+
+/// Call `method` on the dispatch `leaf`, a `structpath::Leaf`, which resolves
+/// to the `PathLeaf` implementation of its type if there is one, and to
+/// boxing otherwise
+pub fn leaf_dispatch(leaf: TokenStream, method: TokenStream) -> TokenStream {
+    quote! {
+        {
+            #[allow(unused_imports)]
+            use ::structpath::{BoxedDispatch as _, LeafDispatch as _};
+            (&#leaf).#method
+        }
+    }
+}
+
+/// Expression building `field_type`, the type of unknown leaves, of type
+/// `leaf`, coming from their `PathLeaf` implementation
+fn field_type_expr(field_type: &FieldType, leaf: &Type) -> TokenStream {
+    match field_type {
+        FieldType::Option(inner) => {
+            let inner = field_type_expr(inner, leaf);
+            quote! { ::structpath_types::FieldType::Option(Box::new(#inner)) }
+        }
+        FieldType::Vec(inner) => {
+            let inner = field_type_expr(inner, leaf);
+            quote! { ::structpath_types::FieldType::Vec(Box::new(#inner)) }
+        }
+        FieldType::Unknown => leaf_dispatch(
+            quote! { ::structpath::Leaf::<#leaf>::new() },
+            quote! { field_type() },
+        ),
+        field_type => quote! { #field_type },
+    }
+}
+
+fn has_nested_struct(field_type: &FieldType) -> bool {
+    match field_type {
+        FieldType::StructPath => true,
+        FieldType::Option(inner) | FieldType::Vec(inner) => has_nested_struct(inner),
+        _ => false,
+    }
+}
+
+/// Expression building the `FieldInfo` of field `name` of type `ty`, parsed
+/// as `field_type`
+fn field_info(name: &str, field_type: &FieldType, ty: &Type, attrs: &[Attribute]) -> TokenStream {
+    let nested = if has_nested_struct(field_type) {
+        let nested_type = innermost_type(ty);
+        quote! {
+            Some(<#nested_type as ::structpath::StructInfo>::get_fields_info)
+        }
+    } else {
+        quote! { None }
+    };
+    let proto = match proto_field(attrs) {
+        Some(proto_field) => quote! { Some(#proto_field) },
+        None => quote! { None },
+    };
+    let field_type = field_type_expr(field_type, innermost_type(ty));
+    quote! {
+        ::structpath_types::FieldInfo {
+            name: #name.to_string(),
+            r#type: #field_type,
+            nested: #nested,
+            proto: #proto,
+        }
+    }
+}
+
+fn not_derivable() -> TokenStream {
+    quote! {
+        compile_error!("StructInfo can only be derived for structs with named fields and for enums whose variants each hold one value");
+    }
+}
+
 pub fn derive_struct_info_impl(input: DeriveInput) -> TokenStream {
     let type_name = input.ident;
+    let proto_name = if is_proto_message(&input.attrs) {
+        quote! { Some(<#type_name as ::prost::Name>::full_name()) }
+    } else {
+        quote! { None }
+    };
 
     let fields: Vec<TokenStream> = match input.data {
         Data::Struct(data_struct) if matches!(data_struct.fields, Fields::Named(_)) => {
@@ -91,34 +344,71 @@ pub fn derive_struct_info_impl(input: DeriveInput) -> TokenStream {
                     .named
                     .iter()
                     .map(|field| {
-                        let field_name = field.ident.clone().unwrap();
+                        let field_name = field.ident.as_ref().unwrap().to_string();
                         let field_type = parse_field_type(&field.ty, &field.attrs);
-                        quote! {
-                            ::structpath_types::FieldInfo {
-                                name: stringify!(#field_name).to_string(),
-                                r#type: #field_type,
-                            }
-                        }
+                        field_info(&field_name, &field_type, &field.ty, &field.attrs)
                     })
                     .collect()
             } else {
                 unreachable!()
             }
         }
-        _ => {
-            return quote! {
-                compile_error!("StructInfo can only be derived for structs with named fields");
-            }
-        }
+        // A oneof is described as a struct with an optional field per variant
+        Data::Enum(data_enum) => match oneof_variants(&data_enum) {
+            Some(variants) => variants
+                .iter()
+                .map(|variant| {
+                    let field_type = FieldType::Option(Box::new(variant.field.r#type.clone()));
+                    field_info(&variant.field.name, &field_type, variant.ty, variant.attrs)
+                })
+                .collect(),
+            None => return not_derivable(),
+        },
+        _ => return not_derivable(),
     };
 
     quote! {
         impl ::structpath::StructInfo for #type_name {
             fn get_fields_info() -> ::structpath_types::FieldsInfo {
                 ::structpath_types::FieldsInfo {
+                    name: stringify!(#type_name).to_string(),
                     fields: vec![#(#fields),*],
+                    proto_name: #proto_name,
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_field_type() {
+        let field: syn::Field = syn::parse_quote! { payload: Option<Bytes> };
+        assert_eq!(
+            parse_field_type(&field.ty, &field.attrs),
+            FieldType::Option(Box::new(FieldType::Bytes))
+        );
+
+        // A type named like a known one is told apart by its hint
+        let field: syn::Field = syn::parse_quote! {
+            #[type_hint = "boxed"]
+            payload: Option<Bytes>
+        };
+        assert_eq!(
+            parse_field_type(&field.ty, &field.attrs),
+            FieldType::Option(Box::new(FieldType::Unknown))
+        );
+
+        let field: syn::Field = syn::parse_quote! {
+            #[prost(oneof = "user::FavoriteParent", tags = "9, 10")]
+            favorite_parent: Option<user::FavoriteParent>
+        };
+        assert_eq!(
+            parse_field_type(&field.ty, &field.attrs),
+            FieldType::Option(Box::new(FieldType::StructPath))
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::structinfo::parse_field_type;
+use crate::path::{field_accessors, variant_accessors};
+use crate::structinfo::{
+    innermost_type, leaf_dispatch, oneof_variants, parse_field_type, proto_field, OneofVariant,
+};
 use proc_macro2::TokenStream;
 use quote::quote;
 use structpath_types::{FieldInfo, FieldType};
@@ -8,8 +11,9 @@ fn value_from_field(field_type: &FieldType, field_value: TokenStream) -> TokenSt
         FieldType::String => quote! {
             ::structpath::Value::String(#field_value.clone())
         },
+        // `i64`, or the `i32` of a protobuf enum
         FieldType::Integer => quote! {
-            ::structpath::Value::Integer(#field_value)
+            ::structpath::Value::from(#field_value)
         },
         FieldType::Float => quote! {
             ::structpath::Value::Float(#field_value)
@@ -17,40 +21,94 @@ fn value_from_field(field_type: &FieldType, field_value: TokenStream) -> TokenSt
         FieldType::Boolean => quote! {
             ::structpath::Value::Boolean(#field_value)
         },
-        FieldType::StructPath => quote! {
-            ::structpath::Value::Boxed(Box::new(#field_value.clone()))
+        FieldType::BigInt => quote! {
+            ::structpath::Value::from(#field_value)
         },
-        FieldType::Unknown => quote! {
-            ::structpath::Value::Boxed(Box::new(#field_value.clone()))
+        FieldType::Decimal | FieldType::DateTime | FieldType::Date | FieldType::Duration => {
+            quote! {
+                ::structpath::Value::from(#field_value.clone())
+            }
+        }
+        FieldType::Bytes => quote! {
+            ::structpath::Value::Bytes(#field_value.to_vec())
         },
-        FieldType::Vec(_) => quote! {
-            ::structpath::Value::Vec(Box::new(#field_value.clone()))
+        FieldType::StructPath => quote! {
+            ::structpath::Value::from_struct(#field_value.clone())
         },
-        FieldType::Option(inner) => {
-            let inner_value = value_from_field(inner, quote! { t });
-            match inner.as_ref() {
-                FieldType::String
-                | FieldType::StructPath
-                | FieldType::Unknown
-                | FieldType::Vec(_)
-                | FieldType::Option(_) => quote! {
+        FieldType::Unknown => leaf_dispatch(
+            quote! { ::structpath::Leaf::of(&#field_value) },
+            quote! { to_value(&#field_value) },
+        ),
+        FieldType::Vec(elem_type) => {
+            let elem_value = value_from_field(elem_type, quote! { (*t) });
+            quote! {
+                ::structpath::Value::list(#field_value.clone(), |t| #elem_value)
+            }
+        }
+        FieldType::Option(inner) => match inner.as_ref() {
+            FieldType::String
+            | FieldType::Decimal
+            | FieldType::DateTime
+            | FieldType::Date
+            | FieldType::Duration
+            | FieldType::Bytes
+            | FieldType::StructPath
+            | FieldType::Unknown
+            | FieldType::Vec(_)
+            | FieldType::Option(_) => {
+                let inner_value = value_from_field(inner, quote! { (*t) });
+                quote! {
                     ::structpath::Value::Option(#field_value.as_ref().map(|t| Box::new(#inner_value)))
-                },
-                _ => quote! {
+                }
+            }
+            _ => {
+                let inner_value = value_from_field(inner, quote! { t });
+                quote! {
                     ::structpath::Value::Option(#field_value.map(|t| Box::new(#inner_value)))
-                },
+                }
             }
+        },
+    }
+}
+
+/// The pattern matching the names of a field in a path component: its name,
+/// and `#5` for the field number 5 of a protobuf message
+fn field_pattern(field: &FieldInfo) -> TokenStream {
+    let name = &field.name;
+    match &field.proto {
+        Some(proto) => {
+            let number = format!("#{}", proto.number);
+            quote! { #name | #number }
         }
+        None => quote! { #name },
+    }
+}
+
+/// Whether a `Value` can represent the values of `field_type`, all the way
+/// down, without resorting to the `PathLeaf` implementation of its type
+fn is_value_type(field_type: &FieldType) -> bool {
+    match field_type {
+        FieldType::Option(inner) | FieldType::Vec(inner) => is_value_type(inner),
+        FieldType::Unknown => false,
+        _ => true,
+    }
+}
+
+fn not_derivable() -> TokenStream {
+    quote! {
+        compile_error!("StructPath can only be derived for structs with named fields and for enums whose variants each hold one value");
     }
 }
 
 pub fn derive_struct_path_impl(input: syn::DeriveInput) -> TokenStream {
     let type_name = input.ident;
 
-    let fields: Vec<FieldInfo> = match input.data {
+    let (fields, leaf_types, accessors): (Vec<FieldInfo>, Vec<syn::Type>, TokenStream) = match input
+        .data
+    {
         syn::Data::Struct(data_struct) if matches!(data_struct.fields, syn::Fields::Named(_)) => {
             if let syn::Fields::Named(fields_named) = data_struct.fields {
-                fields_named
+                let fields = fields_named
                     .named
                     .iter()
                     .map(|field| {
@@ -59,177 +117,836 @@ pub fn derive_struct_path_impl(input: syn::DeriveInput) -> TokenStream {
                         FieldInfo {
                             name: field_name.to_string(),
                             r#type: field_type,
+                            nested: None,
+                            proto: proto_field(&field.attrs),
                         }
                     })
-                    .collect()
+                    .collect();
+                let leaf_types = fields_named
+                    .named
+                    .iter()
+                    .map(|field| innermost_type(&field.ty).clone())
+                    .collect();
+                (
+                    fields,
+                    leaf_types,
+                    field_accessors(&type_name, fields_named.named.iter()),
+                )
             } else {
-                return quote! {
-                    compile_error!("StructPath can only be derived for structs with named fields");
-                };
+                return not_derivable();
             }
         }
-        _ => {
-            return quote! {
-                compile_error!("StructPath can only be derived for structs with named fields");
+        syn::Data::Enum(data_enum) => {
+            return match oneof_variants(&data_enum) {
+                Some(variants) => derive_oneof_impl(&type_name, &input.vis, &variants),
+                None => not_derivable(),
             }
         }
+        _ => return not_derivable(),
     };
 
-    let expr_final_field = fields.iter().map(|field| {
-        let field_name = syn::Ident::new(&field.name, proc_macro2::Span::call_site());
-        let field_expr = value_from_field(&field.r#type, quote! { self.#field_name });
-        quote! {
-            stringify!(#field_name) => Ok(#field_expr)
+    // Fields of unknown types are value fields if their type is a `PathLeaf`
+    let push_value_fields = fields.iter().zip(&leaf_types).map(|(field, leaf)| {
+        let name = &field.name;
+        if is_value_type(&field.r#type) {
+            quote! { fields.push(#name.to_string()); }
+        } else {
+            let is_leaf = leaf_dispatch(
+                quote! { ::structpath::Leaf::<#leaf>::new() },
+                quote! { is_leaf() },
+            );
+            quote! {
+                if #is_leaf {
+                    fields.push(#name.to_string());
+                }
+            }
         }
     });
 
-    let expr_final_index = fields.iter().filter_map(|field| {
-        let field_name = syn::Ident::new(&field.name, proc_macro2::Span::call_site());
-        match &field.r#type {
-            FieldType::Vec(elem_type) => {
-                let field_expr = value_from_field(elem_type, quote! { self.#field_name[index] });
-                Some(quote! {
-                    stringify!(#field_name) => Ok(#field_expr)
-                })
+    // Error for an index beyond the length of `vec`
+    let out_of_bounds = |vec: TokenStream| {
+        quote! {
+            Err(::structpath::StructPathError::index_out_of_bounds(
+                field.clone(),
+                index,
+                #vec.len(),
+                components,
+                stringify!(#type_name),
+            ))
+        }
+    };
+
+    let ((final_field_names, expr_final_field), (final_index_names, expr_final_index)) = final_arms(
+        &fields,
+        |field_type, field_value| {
+            let value = value_from_field(field_type, field_value);
+            quote! { Ok(#value) }
+        },
+        &quote! { Ok(::structpath::Value::Option(None)) },
+        out_of_bounds,
+    );
+
+    let ((nested_field_names, expr_nested_field), (nested_index_names, expr_nested_index)) =
+        nested_arms(
+            &fields,
+            |s| quote! { #s.get_value_by_components(remaining_path).map_err(|e| e.nested(components)) },
+            &quote! { Ok(::structpath::Value::Option(None)) },
+            out_of_bounds,
+        );
+
+    let ((_, borrow_final_field), (_, borrow_final_index)) = final_arms(
+        &fields,
+        |field_type, field_value| {
+            let borrowed = borrowed_field(field_type, field_value);
+            quote! { Ok(#borrowed) }
+        },
+        &quote! { Ok(::structpath::Borrowed::None) },
+        out_of_bounds,
+    );
+    let ((_, borrow_nested_field), (_, borrow_nested_index)) = nested_arms(
+        &fields,
+        |s| quote! { #s.borrow_by_components(remaining_path).map_err(|e| e.nested(components)) },
+        &quote! { Ok(::structpath::Borrowed::None) },
+        out_of_bounds,
+    );
+
+    let ((_, batch_nested_field), (_, batch_nested_index)) = nested_arms(
+        &fields,
+        |s| quote! { #s.get_values_by_trie(node, paths, depth + 1, out) },
+        &quote! { node.fill(out, || Ok(::structpath::Value::Option(None))) },
+        |_| quote! { ::structpath::resolve_each(self, node, paths, depth, out) },
+    );
+
+    let names = [
+        &final_field_names[..],
+        &final_index_names,
+        &nested_field_names,
+        &nested_index_names,
+    ];
+    let get_value_body = resolve_components(
+        &type_name,
+        quote! { Err(::structpath::StructPathError::empty_path()) },
+        names,
+        [
+            &expr_final_field,
+            &expr_final_index,
+            &expr_nested_field,
+            &expr_nested_index,
+        ],
+    );
+    let borrow_body = resolve_components(
+        &type_name,
+        quote! { Ok(::structpath::Borrowed::Struct(self)) },
+        names,
+        [
+            &borrow_final_field,
+            &borrow_final_index,
+            &borrow_nested_field,
+            &borrow_nested_index,
+        ],
+    );
+
+    let compile_path = compile_path_impl(&type_name, &fields, names);
+    let from_value = from_value_impl(&type_name);
+
+    quote! {
+
+        impl ::structpath::StructPath for #type_name {
+            fn value_fields(&self) -> Vec<String> {
+                let mut fields = Vec::new();
+                #(#push_value_fields)*
+                fields
             }
-            FieldType::Option(mid_type) if matches!(**mid_type, FieldType::Vec(_)) => {
-                if let FieldType::Vec(ref inner_type) = **mid_type {
-                    let field_expr = value_from_field(inner_type, quote! { vec[index] });
-                    Some(quote! {
-                        stringify!(#field_name) => Ok(
-                            match self.#field_name.as_ref() {
-                                Some(vec) => #field_expr,
-                                None => ::structpath::Value::Option(None),
-                            }
-                        )
-                    })
-                } else {
-                    None
+
+            fn get_value_by_components(&self, components: &[::structpath::PathComponent]) -> Result<::structpath::Value, ::structpath::StructPathError> {
+                #get_value_body
+            }
+
+            fn borrow_by_components(&self, components: &[::structpath::PathComponent]) -> Result<::structpath::Borrowed<'_>, ::structpath::StructPathError> {
+                #borrow_body
+            }
+
+            fn get_values_by_trie(
+                &self,
+                trie: &::structpath::PathTrie,
+                paths: &[::structpath::Path],
+                depth: usize,
+                out: &mut [Option<Result<::structpath::Value, ::structpath::StructPathError>>],
+            ) {
+                for (path_component, node) in trie.children() {
+                    if !node.leaves().is_empty() {
+                        let value = self.get_value_by_components(std::slice::from_ref(path_component));
+                        ::structpath::assign(node, paths, depth, out, value);
+                    }
+                    if node.children().is_empty() {
+                        continue;
+                    }
+                    match *path_component {
+                        ::structpath::PathComponent::Field(ref field) => match field.as_str() {
+                            #(#batch_nested_field,)*
+                            _ => ::structpath::resolve_each(self, node, paths, depth, out),
+                        },
+                        ::structpath::PathComponent::ArrayIndex(ref field, index) => match field.as_str() {
+                            #(#batch_nested_index,)*
+                            _ => ::structpath::resolve_each(self, node, paths, depth, out),
+                        },
+                    }
                 }
             }
-            _ => None,
         }
+
+        impl #type_name {
+            #accessors
+        }
+
+        #compile_path
+
+        #from_value
+    }
+}
+
+/// Implement `StructPath` and `CompilePath` for an enum generated by prost for
+/// a oneof, as a struct with an optional field per variant, set for the
+/// active one
+fn derive_oneof_impl(
+    type_name: &syn::Ident,
+    vis: &syn::Visibility,
+    variants: &[OneofVariant],
+) -> TokenStream {
+    let names: Vec<&str> = variants.iter().map(|v| v.field.name.as_str()).collect();
+    let nested: Vec<&OneofVariant> = variants
+        .iter()
+        .filter(|v| v.field.r#type == FieldType::StructPath)
+        .collect();
+    let nested_names: Vec<&str> = nested.iter().map(|v| v.field.name.as_str()).collect();
+
+    // Match arm of the field of `variant`, giving `active` when it is the
+    // active variant, of value `value`, and `inactive` otherwise
+    let arm = |variant: &OneofVariant, active: TokenStream, inactive: &TokenStream| {
+        let ident = variant.ident;
+        let pattern = field_pattern(&variant.field);
+        quote! {
+            #pattern => match self {
+                Self::#ident(value) => #active,
+                _ => #inactive,
+            }
+        }
+    };
+    let option_value = |variant: &OneofVariant| {
+        let value = value_from_field(&variant.field.r#type, quote! { (*value) });
+        quote! { ::structpath::Value::Option(Some(Box::new(#value))) }
+    };
+
+    let none = quote! { Ok(::structpath::Value::Option(None)) };
+    let final_field: Vec<TokenStream> = variants
+        .iter()
+        .map(|v| {
+            let value = option_value(v);
+            arm(v, quote! { Ok(#value) }, &none)
+        })
+        .collect();
+    let nested_field: Vec<TokenStream> = nested
+        .iter()
+        .map(|v| {
+            let descend = quote! {
+                value.get_value_by_components(remaining_path).map_err(|e| e.nested(components))
+            };
+            arm(v, descend, &none)
+        })
+        .collect();
+
+    let borrowed_none = quote! { Ok(::structpath::Borrowed::None) };
+    let borrow_final_field: Vec<TokenStream> = variants
+        .iter()
+        .map(|v| {
+            let borrowed = borrowed_field(&v.field.r#type, quote! { (*value) });
+            arm(v, quote! { Ok(#borrowed) }, &borrowed_none)
+        })
+        .collect();
+    let borrow_nested_field: Vec<TokenStream> = nested
+        .iter()
+        .map(|v| {
+            let descend = quote! {
+                value.borrow_by_components(remaining_path).map_err(|e| e.nested(components))
+            };
+            arm(v, descend, &borrowed_none)
+        })
+        .collect();
+
+    let names = [&names[..], &[], &nested_names, &[]];
+    let from_value = from_value_impl(type_name);
+    let get_value_body = resolve_components(
+        type_name,
+        quote! { Err(::structpath::StructPathError::empty_path()) },
+        names,
+        [&final_field, &[], &nested_field, &[]],
+    );
+    let borrow_body = resolve_components(
+        type_name,
+        quote! { Ok(::structpath::Borrowed::Struct(self)) },
+        names,
+        [&borrow_final_field, &[], &borrow_nested_field, &[]],
+    );
+
+    // Only the active variant is listed
+    let value_fields = variants.iter().map(|v| {
+        let ident = v.ident;
+        let name = &v.field.name;
+        let is_value = if is_value_type(&v.field.r#type) {
+            quote! { true }
+        } else {
+            let leaf = innermost_type(v.ty);
+            leaf_dispatch(
+                quote! { ::structpath::Leaf::<#leaf>::new() },
+                quote! { is_leaf() },
+            )
+        };
+        quote! { Self::#ident(_) if #is_value => vec![#name.to_string()] }
     });
 
-    let expr_nested_field = fields.iter().filter_map(|field| {
-        let field_name = syn::Ident::new(&field.name, proc_macro2::Span::call_site());
-        match &field.r#type {
-            FieldType::StructPath => Some(quote! {
-                stringify!(#field_name) => self.#field_name.get_value_by_path(&remaining_path)
-            }),
-            FieldType::Option(inner_type) if matches!(**inner_type, FieldType::StructPath) => {
-                Some(quote! {
-                    stringify!(#field_name) => match self.#field_name.as_ref() {
-                        Some(s) => s.get_value_by_path(&remaining_path),
-                        None => Ok(::structpath::Value::Option(None))
-                    }
+    let compile_final = variants.iter().map(|v| {
+        let ident = v.ident;
+        let pattern = field_pattern(&v.field);
+        let value = option_value(v);
+        quote! {
+            #pattern => Ok(Box::new(
+                |s: &Self| -> Result<::structpath::Value, ::structpath::StructPathError> {
+                    Ok(match s {
+                        Self::#ident(value) => #value,
+                        _ => ::structpath::Value::Option(None),
+                    })
+                }
+            ))
+        }
+    });
+    let compile_nested = nested.iter().map(|v| {
+        let ident = v.ident;
+        let pattern = field_pattern(&v.field);
+        quote! {
+            #pattern => ::structpath::compile_nested(components, |s: &Self| {
+                Ok(match s {
+                    Self::#ident(value) => Some(value),
+                    _ => None,
                 })
-            }
-            _ => None,
+            })
         }
     });
+    let not_found = |names: &[&str]| {
+        quote! {
+            _ => Err(::structpath::StructPathError::field_not_found(
+                field.clone(),
+                components,
+                stringify!(#type_name),
+                &[#(#names),*],
+            ))
+        }
+    };
+    let [final_names, _, nested_names, _] = names;
+    let final_not_found = not_found(final_names);
+    let nested_not_found = not_found(nested_names);
+    // Variants are never lists
+    let index_not_found = quote! {
+        ::structpath::PathComponent::ArrayIndex(ref field, _) => {
+            Err(::structpath::StructPathError::field_not_found(
+                field.clone(),
+                components,
+                stringify!(#type_name),
+                &[],
+            ))
+        }
+    };
 
-    let expr_nested_index = fields
+    let accessors = variant_accessors(vis, variants);
+
+    quote! {
+        #[allow(unreachable_patterns)]
+        impl ::structpath::StructPath for #type_name {
+            fn value_fields(&self) -> Vec<String> {
+                match self {
+                    #(#value_fields,)*
+                    _ => Vec::new(),
+                }
+            }
+
+            fn get_value_by_components(&self, components: &[::structpath::PathComponent]) -> Result<::structpath::Value, ::structpath::StructPathError> {
+                #get_value_body
+            }
+
+            fn borrow_by_components(&self, components: &[::structpath::PathComponent]) -> Result<::structpath::Borrowed<'_>, ::structpath::StructPathError> {
+                #borrow_body
+            }
+        }
+
+        #[allow(unreachable_patterns)]
+        impl #type_name {
+            #accessors
+        }
+
+        impl ::structpath::CompilePath for #type_name {
+            fn compile_components(
+                components: &[::structpath::PathComponent],
+            ) -> Result<::structpath::PathFn<Self>, ::structpath::StructPathError> {
+                let Some(path_component) = components.first() else {
+                    return Err(::structpath::StructPathError::empty_path());
+                };
+
+                if components.len() > 1 {
+                    return match *path_component {
+                        ::structpath::PathComponent::Field(ref field) => match field.as_str() {
+                            #(#compile_nested,)*
+                            #nested_not_found,
+                        },
+                        #index_not_found
+                    };
+                }
+
+                match *path_component {
+                    ::structpath::PathComponent::Field(ref field) => match field.as_str() {
+                        #(#compile_final,)*
+                        #final_not_found,
+                    },
+                    #index_not_found
+                }
+            }
+        }
+
+        #from_value
+    }
+}
+
+/// Implement `FromValue` for `type_name`, reading boxed structs back
+///
+/// Boxed values are cloned, so the impl only holds for `Clone` types: the
+/// higher-ranked bound is not checked where the impl is declared, letting
+/// other types derive `StructPath` without it.
+fn from_value_impl(type_name: &syn::Ident) -> TokenStream {
+    quote! {
+        impl ::structpath::FromValue for #type_name
+        where
+            for<'a> #type_name: ::std::clone::Clone + ::std::marker::Send + ::std::marker::Sync + 'static,
+        {
+            fn from_value(value: ::structpath::Value) -> Result<Self, ::structpath::StructPathError> {
+                value.try_into_unboxed::<Self>()
+            }
+        }
+    }
+}
+
+/// The `Borrowed` view of the value of type `field_type` at `field_value`
+fn borrowed_field(field_type: &FieldType, field_value: TokenStream) -> TokenStream {
+    match field_type {
+        FieldType::StructPath => quote! { ::structpath::Borrowed::Struct(&#field_value) },
+        FieldType::Vec(_) => quote! { ::structpath::Borrowed::List(#field_value.len()) },
+        FieldType::Option(inner) => {
+            let inner = match inner.as_ref() {
+                FieldType::StructPath | FieldType::Vec(_) | FieldType::Option(_) => {
+                    let inner = borrowed_field(inner, quote! { (*t) });
+                    quote! { Some(t) => #inner }
+                }
+                _ => quote! { Some(_) => ::structpath::Borrowed::Value },
+            };
+            quote! {
+                match #field_value.as_ref() {
+                    #inner,
+                    None => ::structpath::Borrowed::None,
+                }
+            }
+        }
+        _ => quote! { ::structpath::Borrowed::Value },
+    }
+}
+
+/// Body of a method resolving `components`, from the names and match arms of
+/// the fields reached by the last component, by name and by index, and of the
+/// ones descended into by the other components
+///
+/// `root` is the result for the empty path.
+fn resolve_components(
+    type_name: &syn::Ident,
+    root: TokenStream,
+    [final_field_names, final_index_names, nested_field_names, nested_index_names]: [&[&str]; 4],
+    [final_field, final_index, nested_field, nested_index]: [&[TokenStream]; 4],
+) -> TokenStream {
+    let not_found = |names: &[&str]| {
+        quote! {
+            _ => Err(::structpath::StructPathError::field_not_found(
+                field.clone(),
+                components,
+                stringify!(#type_name),
+                &[#(#names),*],
+            ))
+        }
+    };
+    let final_field_not_found = not_found(final_field_names);
+    let final_index_not_found = not_found(final_index_names);
+    let nested_field_not_found = not_found(nested_field_names);
+    let nested_index_not_found = not_found(nested_index_names);
+
+    quote! {
+        let Some(path_component) = components.first() else {
+            return #root;
+        };
+
+        if components.len() > 1 {
+            let remaining_path = &components[1..];
+            return match *path_component {
+                ::structpath::PathComponent::Field(ref field) => match field.as_str() {
+                    #(#nested_field,)*
+                    #nested_field_not_found,
+                },
+                ::structpath::PathComponent::ArrayIndex(ref field, index) => match field.as_str() {
+                    #(#nested_index,)*
+                    #nested_index_not_found,
+                },
+            }
+        }
+
+        match *path_component {
+            ::structpath::PathComponent::Field(ref field) => match field.as_str() {
+                #(#final_field,)*
+                #final_field_not_found,
+            },
+            ::structpath::PathComponent::ArrayIndex(ref field, index) => match field.as_str() {
+                #(#final_index,)*
+                #final_index_not_found,
+            },
+        }
+    }
+}
+
+/// Match arms reading the field reached by the last component of a path, by
+/// name and by index
+///
+/// `read` builds the result for the field or element of the given type at the
+/// given place, `missing` the one used when an optional vector is `None`, and
+/// `out_of_bounds` the one used when the index is beyond the given vector.
+#[allow(clippy::type_complexity)]
+fn final_arms<'a>(
+    fields: &'a [FieldInfo],
+    read: impl Fn(&FieldType, TokenStream) -> TokenStream,
+    missing: &TokenStream,
+    out_of_bounds: impl Fn(TokenStream) -> TokenStream,
+) -> (
+    (Vec<&'a str>, Vec<TokenStream>),
+    (Vec<&'a str>, Vec<TokenStream>),
+) {
+    let final_field = fields
+        .iter()
+        .map(|field| {
+            let field_name = syn::Ident::new(&field.name, proc_macro2::Span::call_site());
+            let pattern = field_pattern(field);
+            let expr = read(&field.r#type, quote! { self.#field_name });
+            (field.name.as_str(), quote! { #pattern => #expr })
+        })
+        .unzip();
+
+    let final_index = fields
         .iter()
         .filter_map(|field| {
             let field_name = syn::Ident::new(&field.name, proc_macro2::Span::call_site());
-            match &field.r#type {
-                FieldType::Vec(inner_type) => {
-                    if **inner_type == FieldType::StructPath {
-                        Some(
-                            quote! {
-                                stringify!(#field_name) => self.#field_name[index].get_value_by_path(&remaining_path)
-                            }
-                        )
-                    } else if **inner_type == FieldType::Option(Box::new(FieldType::StructPath)) {
-                        Some(
-                            quote! {
-                                stringify!(#field_name) => match self.#field_name[index].as_ref() {
-                                    Some(s) => s.get_value_by_path(&remaining_path),
-                                    None => Ok(::structpath::Value::Option(None))
-                                }
+            let pattern = field_pattern(field);
+            let expr = match &field.r#type {
+                FieldType::Vec(elem_type) => {
+                    let elem = read(elem_type, quote! { (*elem) });
+                    let out_of_bounds = out_of_bounds(quote! { self.#field_name });
+                    quote! {
+                        match self.#field_name.get(index) {
+                            Some(elem) => #elem,
+                            None => #out_of_bounds,
+                        }
+                    }
+                }
+                FieldType::Option(mid_type) => match mid_type.as_ref() {
+                    FieldType::Vec(elem_type) => {
+                        let elem = read(elem_type, quote! { (*elem) });
+                        let out_of_bounds = out_of_bounds(quote! { vec });
+                        quote! {
+                            match self.#field_name.as_ref() {
+                                Some(vec) => match vec.get(index) {
+                                    Some(elem) => #elem,
+                                    None => #out_of_bounds,
+                                },
+                                None => #missing,
                             }
-                        )
-                    } else {
-                        None
+                        }
                     }
+                    _ => return None,
                 },
-                FieldType::Option(mid_type) if matches!(**mid_type, FieldType::Vec(_)) => {
-                    if let FieldType::Vec(ref inner_type) = **mid_type {
-                        if **inner_type == FieldType::StructPath {
-                            Some(
-                                quote! {
-                                    stringify!(#field_name) => match self.#field_name.as_ref() {
-                                        Some(vec) => vec[index].get_value_by_path(&remaining_path),
-                                        None => Ok(::structpath::Value::Option(None)),
-                                    }
-                                }
-                            )
-                        } else if **inner_type == FieldType::Option(Box::new(FieldType::StructPath)) {
-                            Some(
-                                quote! {
-                                    stringify!(#field_name) => match self.#field_name.as_ref() {
-                                        Some(vec) => match vec[index].as_ref() {
-                                            Some(s) => s.get_value_by_path(&remaining_path),
-                                            None => Ok(::structpath::Value::Option(None))
-                                        },
-                                        None => Ok(::structpath::Value::Option(None)),
-                                    }
-                                }
-                            )
-                        } else {
-                            None
+                _ => return None,
+            };
+            Some((field.name.as_str(), quote! { #pattern => #expr }))
+        })
+        .unzip();
+
+    (final_field, final_index)
+}
+
+/// Match arms descending into nested struct fields, by name and by index
+///
+/// `descend` builds the expression visiting a reference to the nested struct,
+/// `missing` the expression used when an optional one is `None`, and
+/// `out_of_bounds` the one used when the index is beyond the given vector.
+#[allow(clippy::type_complexity)]
+fn nested_arms<'a>(
+    fields: &'a [FieldInfo],
+    descend: impl Fn(TokenStream) -> TokenStream,
+    missing: &TokenStream,
+    out_of_bounds: impl Fn(TokenStream) -> TokenStream,
+) -> (
+    (Vec<&'a str>, Vec<TokenStream>),
+    (Vec<&'a str>, Vec<TokenStream>),
+) {
+    let struct_path = FieldType::StructPath;
+    let optional_struct_path = FieldType::Option(Box::new(FieldType::StructPath));
+
+    // Expression visiting `target`, of type `ty`, if it is a (maybe optional) struct
+    let visit = |ty: &FieldType, target: TokenStream| {
+        if *ty == struct_path {
+            Some(descend(target))
+        } else if *ty == optional_struct_path {
+            let inner = descend(quote! { s });
+            Some(quote! {
+                match #target.as_ref() {
+                    Some(s) => #inner,
+                    None => #missing,
+                }
+            })
+        } else {
+            None
+        }
+    };
+
+    let nested_field = fields
+        .iter()
+        .filter_map(|field| {
+            let field_name = syn::Ident::new(&field.name, proc_macro2::Span::call_site());
+            let pattern = field_pattern(field);
+            let expr = visit(&field.r#type, quote! { self.#field_name })?;
+            Some((field.name.as_str(), quote! { #pattern => #expr }))
+        })
+        .unzip();
+
+    let nested_index = fields
+        .iter()
+        .filter_map(|field| {
+            let field_name = syn::Ident::new(&field.name, proc_macro2::Span::call_site());
+            let pattern = field_pattern(field);
+            let expr = match &field.r#type {
+                FieldType::Vec(elem_type) => {
+                    let inner = visit(elem_type, quote! { (*elem) })?;
+                    let out_of_bounds = out_of_bounds(quote! { self.#field_name });
+                    quote! {
+                        match self.#field_name.get(index) {
+                            Some(elem) => #inner,
+                            None => #out_of_bounds,
                         }
-                    } else {
-                        None
                     }
+                }
+                FieldType::Option(mid_type) => match mid_type.as_ref() {
+                    FieldType::Vec(elem_type) => {
+                        let inner = visit(elem_type, quote! { (*elem) })?;
+                        let out_of_bounds = out_of_bounds(quote! { vec });
+                        quote! {
+                            match self.#field_name.as_ref() {
+                                Some(vec) => match vec.get(index) {
+                                    Some(elem) => #inner,
+                                    None => #out_of_bounds,
+                                },
+                                None => #missing,
+                            }
+                        }
+                    }
+                    _ => return None,
                 },
+                _ => return None,
+            };
+            Some((field.name.as_str(), quote! { #pattern => #expr }))
+        })
+        .unzip();
+
+    (nested_field, nested_index)
+}
+
+/// Generate the `CompilePath` implementation
+///
+/// Mirrors the arms of `get_value_by_path`, but each arm builds a closure
+/// instead of reading the field, so name matching happens only once.
+fn compile_path_impl(
+    type_name: &syn::Ident,
+    fields: &[FieldInfo],
+    [final_field_names, final_index_names, nested_field_names, nested_index_names]: [&[&str]; 4],
+) -> TokenStream {
+    let mut final_field = Vec::new();
+    let mut final_index = Vec::new();
+    let mut nested_field = Vec::new();
+    let mut nested_index = Vec::new();
+
+    for field in fields {
+        let field_name = syn::Ident::new(&field.name, proc_macro2::Span::call_site());
+        let pattern = field_pattern(field);
+
+        let field_expr = value_from_field(&field.r#type, quote! { s.#field_name });
+        final_field.push(quote! {
+            #pattern => Ok(Box::new(
+                |s: &Self| -> Result<::structpath::Value, ::structpath::StructPathError> {
+                    Ok(#field_expr)
+                }
+            ))
+        });
+
+        // Element `index` of `vec`, mapped by `elem`, or the error for an
+        // index beyond its length. The closures capture the field name and
+        // the components of the path to report it.
+        let get_elem = |vec: TokenStream, elem: TokenStream| {
+            quote! {
+                match #vec.get(index) {
+                    Some(elem) => Ok(#elem),
+                    None => Err(::structpath::StructPathError::index_out_of_bounds(
+                        field.clone(),
+                        index,
+                        #vec.len(),
+                        &path,
+                        stringify!(#type_name),
+                    )),
+                }
+            }
+        };
+
+        let index_expr = match &field.r#type {
+            FieldType::Vec(elem_type) => Some(get_elem(
+                quote! { s.#field_name },
+                value_from_field(elem_type, quote! { (*elem) }),
+            )),
+            FieldType::Option(mid_type) => match mid_type.as_ref() {
+                FieldType::Vec(elem_type) => {
+                    let elem_expr = get_elem(
+                        quote! { vec },
+                        value_from_field(elem_type, quote! { (*elem) }),
+                    );
+                    Some(quote! {
+                        match s.#field_name.as_ref() {
+                            Some(vec) => #elem_expr,
+                            None => Ok(::structpath::Value::Option(None)),
+                        }
+                    })
+                }
                 _ => None,
+            },
+            _ => None,
+        };
+        if let Some(index_expr) = index_expr {
+            final_index.push(quote! {
+                #pattern => {
+                    let (field, path) = (field.clone(), components.to_vec());
+                    Ok(Box::new(
+                        move |s: &Self| -> Result<::structpath::Value, ::structpath::StructPathError> {
+                            #index_expr
+                        }
+                    ))
+                }
+            });
+        }
+
+        let struct_path = FieldType::StructPath;
+        let optional_struct_path = FieldType::Option(Box::new(FieldType::StructPath));
+
+        let project_field = match &field.r#type {
+            t if *t == struct_path => Some(quote! { Ok(Some(&s.#field_name)) }),
+            t if *t == optional_struct_path => Some(quote! { Ok(s.#field_name.as_ref()) }),
+            _ => None,
+        };
+        if let Some(project) = project_field {
+            nested_field.push(quote! {
+                #pattern => ::structpath::compile_nested(components, |s: &Self| #project)
+            });
+        }
+
+        // The nested struct at `index` of a vector
+        let elem = |elem_type: &FieldType| {
+            if *elem_type == struct_path {
+                Some(quote! { Some(elem) })
+            } else if *elem_type == optional_struct_path {
+                Some(quote! { elem.as_ref() })
+            } else {
+                None
             }
-        });
+        };
+        let project_index = match &field.r#type {
+            FieldType::Vec(elem_type) => {
+                elem(elem_type).map(|elem| get_elem(quote! { s.#field_name }, elem))
+            }
+            FieldType::Option(mid_type) => match mid_type.as_ref() {
+                FieldType::Vec(elem_type) => elem(elem_type).map(|elem| {
+                    let elem_expr = get_elem(quote! { vec }, elem);
+                    quote! {
+                        match s.#field_name.as_ref() {
+                            Some(vec) => #elem_expr,
+                            None => Ok(None),
+                        }
+                    }
+                }),
+                _ => None,
+            },
+            _ => None,
+        };
+        if let Some(project) = project_index {
+            nested_index.push(quote! {
+                #pattern => {
+                    let (field, path) = (field.clone(), components.to_vec());
+                    ::structpath::compile_nested(components, move |s: &Self| #project)
+                }
+            });
+        }
+    }
+
+    let not_found = |names: &[&str]| {
+        quote! {
+            _ => Err(::structpath::StructPathError::field_not_found(
+                field.clone(),
+                components,
+                stringify!(#type_name),
+                &[#(#names),*],
+            ))
+        }
+    };
+    let final_field_not_found = not_found(final_field_names);
+    let final_index_not_found = not_found(final_index_names);
+    let nested_field_not_found = not_found(nested_field_names);
+    let nested_index_not_found = not_found(nested_index_names);
 
     quote! {
+        impl ::structpath::CompilePath for #type_name {
+            fn compile_components(
+                components: &[::structpath::PathComponent],
+            ) -> Result<::structpath::PathFn<Self>, ::structpath::StructPathError> {
+                let Some(path_component) = components.first() else {
+                    return Err(::structpath::StructPathError::empty_path());
+                };
 
-        impl ::structpath::StructPath for #type_name {
-            fn get_value_by_path(&self, path: &::structpath::Path) -> Result<::structpath::Value, ::structpath::StructPathError> {
-                if path.components.len() > 1 {
-                    let path_component = path.components[0].clone();
-                    let remaining_path = ::structpath::Path {
-                        components: path.components[1..].to_vec(),
-                    };
-                    return match path_component {
-                        ::structpath::PathComponent::Field(field) => match field.as_str() {
-                            #(#expr_nested_field,)*
-                            _ => Err(::structpath::StructPathError::FieldNotFound(field)),
+                if components.len() > 1 {
+                    return match *path_component {
+                        ::structpath::PathComponent::Field(ref field) => match field.as_str() {
+                            #(#nested_field,)*
+                            #nested_field_not_found,
                         },
-                        ::structpath::PathComponent::ArrayIndex(field, index) => match field.as_str() {
-                            #(#expr_nested_index,)*
-                            _ => Err(::structpath::StructPathError::FieldNotFound(field)),
+                        ::structpath::PathComponent::ArrayIndex(ref field, index) => match field.as_str() {
+                            #(#nested_index,)*
+                            #nested_index_not_found,
                         },
-                    }
+                    };
                 }
 
-                let path_component = path.components[0].clone();
-
-                match path_component {
-                    ::structpath::PathComponent::Field(field) => match field.as_str() {
-                        #(#expr_final_field,)*
-                        _ => Err(::structpath::StructPathError::FieldNotFound(field)),
+                match *path_component {
+                    ::structpath::PathComponent::Field(ref field) => match field.as_str() {
+                        #(#final_field,)*
+                        #final_field_not_found,
                     },
-                    ::structpath::PathComponent::ArrayIndex(field, index) => match field.as_str() {
-                        #(#expr_final_index,)*
-                        _ => Err(::structpath::StructPathError::FieldNotFound(field)),
+                    ::structpath::PathComponent::ArrayIndex(ref field, index) => match field.as_str() {
+                        #(#final_index,)*
+                        #final_index_not_found,
                     },
                 }
             }
-
-            fn get_value(&self, path: &str) -> Result<::structpath::Value, ::structpath::StructPathError> {
-                let path = ::structpath::Path::from_str(path);
-                match path {
-                    Ok(path) => self.get_value_by_path(&path),
-                    Err(e) => Err(::structpath::StructPathError::InvalidPath(e.to_string())),
-                }
-            }
         }
     }
 }
@@ -7,6 +7,18 @@ pub enum FieldType {
     Integer,
     Float,
     Boolean,
+    /// Exact decimal number, such as a `rust_decimal::Decimal`
+    Decimal,
+    /// Integer beyond `i64`: `i128` or `u128`
+    BigInt,
+    /// Instant in UTC, such as a protobuf `Timestamp`
+    DateTime,
+    /// Calendar date, such as a `chrono::NaiveDate`
+    Date,
+    /// Signed span of time, such as a protobuf `Duration`
+    Duration,
+    /// Binary data, such as `Vec<u8>` or a protobuf `bytes` field
+    Bytes,
     StructPath,
     Option(Box<FieldType>),
     Vec(Box<FieldType>),
@@ -20,6 +32,16 @@ impl ToTokens for FieldType {
             FieldType::Integer => tokens.extend(quote! { ::structpath_types::FieldType::Integer }),
             FieldType::Float => tokens.extend(quote! { ::structpath_types::FieldType::Float }),
             FieldType::Boolean => tokens.extend(quote! { ::structpath_types::FieldType::Boolean }),
+            FieldType::Decimal => tokens.extend(quote! { ::structpath_types::FieldType::Decimal }),
+            FieldType::BigInt => tokens.extend(quote! { ::structpath_types::FieldType::BigInt }),
+            FieldType::DateTime => {
+                tokens.extend(quote! { ::structpath_types::FieldType::DateTime })
+            }
+            FieldType::Date => tokens.extend(quote! { ::structpath_types::FieldType::Date }),
+            FieldType::Duration => {
+                tokens.extend(quote! { ::structpath_types::FieldType::Duration })
+            }
+            FieldType::Bytes => tokens.extend(quote! { ::structpath_types::FieldType::Bytes }),
             FieldType::StructPath => {
                 tokens.extend(quote! { ::structpath_types::FieldType::StructPath })
             }
@@ -34,15 +56,86 @@ impl ToTokens for FieldType {
     }
 }
 
-#[derive(Debug)]
+/// Label of a protobuf field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtoLabel {
+    /// Proto3 field without presence
+    Singular,
+    Optional,
+    Required,
+    /// Repeated field, maps included
+    Repeated,
+}
+
+/// Protobuf metadata of a field, from the `#[prost(...)]` attribute of a
+/// generated message or from a descriptor
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtoField {
+    pub number: u32,
+    /// Proto type name, as in `int64`, `sint32` or `fixed32`, or `message`,
+    /// `enum`, `map` and `group` for the other kinds
+    pub r#type: String,
+    pub label: ProtoLabel,
+}
+
+impl ToTokens for ProtoLabel {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            ProtoLabel::Singular => {
+                tokens.extend(quote! { ::structpath_types::ProtoLabel::Singular })
+            }
+            ProtoLabel::Optional => {
+                tokens.extend(quote! { ::structpath_types::ProtoLabel::Optional })
+            }
+            ProtoLabel::Required => {
+                tokens.extend(quote! { ::structpath_types::ProtoLabel::Required })
+            }
+            ProtoLabel::Repeated => {
+                tokens.extend(quote! { ::structpath_types::ProtoLabel::Repeated })
+            }
+        }
+    }
+}
+
+impl ToTokens for ProtoField {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ProtoField {
+            number,
+            r#type,
+            label,
+        } = self;
+        tokens.extend(quote! {
+            ::structpath_types::ProtoField {
+                number: #number,
+                r#type: #r#type.to_string(),
+                label: #label,
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct FieldsInfo {
+    /// Name of the struct
+    pub name: String,
     pub fields: Vec<FieldInfo>,
+    /// Fully qualified name of the protobuf message, as in `sample.User`
+    pub proto_name: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct FieldInfo {
     pub name: String,
     pub r#type: FieldType,
+    /// Fields of the nested struct, for `StructPath` fields, possibly inside
+    /// `Option` or `Vec`
+    ///
+    /// Kept as a function so that recursive structs can be described.
+    pub nested: Option<fn() -> FieldsInfo>,
+    /// Protobuf metadata, for the fields of protobuf messages
+    ///
+    /// `None` for oneofs, which span several field numbers.
+    pub proto: Option<ProtoField>,
 }
 
 #[cfg(test)]
@@ -83,6 +176,30 @@ mod tests {
             ":: structpath_types :: FieldType :: Boolean"
         );
 
+        let field_type = FieldType::DateTime;
+        let mut tokens = TokenStream::new();
+        field_type.to_tokens(&mut tokens);
+        assert_eq!(
+            tokens.to_string(),
+            ":: structpath_types :: FieldType :: DateTime"
+        );
+
+        let field_type = FieldType::Decimal;
+        let mut tokens = TokenStream::new();
+        field_type.to_tokens(&mut tokens);
+        assert_eq!(
+            tokens.to_string(),
+            ":: structpath_types :: FieldType :: Decimal"
+        );
+
+        let field_type = FieldType::Bytes;
+        let mut tokens = TokenStream::new();
+        field_type.to_tokens(&mut tokens);
+        assert_eq!(
+            tokens.to_string(),
+            ":: structpath_types :: FieldType :: Bytes"
+        );
+
         let field_type = FieldType::StructPath;
         let mut tokens = TokenStream::new();
         field_type.to_tokens(&mut tokens);
@@ -109,4 +226,19 @@ mod tests {
             ":: structpath_types :: FieldType :: Unknown"
         );
     }
+
+    #[test]
+    fn test_proto_field_to_tokens() {
+        let proto_field = ProtoField {
+            number: 2,
+            r#type: "sint64".to_string(),
+            label: ProtoLabel::Optional,
+        };
+        let mut tokens = TokenStream::new();
+        proto_field.to_tokens(&mut tokens);
+        assert_eq!(
+            tokens.to_string(),
+            ":: structpath_types :: ProtoField { number : 2u32 , r#type : \"sint64\" . to_string () , label : :: structpath_types :: ProtoLabel :: Optional , }"
+        );
+    }
 }
@@ -1,3 +1,3 @@
 mod field;
 
-pub use field::{FieldInfo, FieldType, FieldsInfo};
+pub use field::{FieldInfo, FieldType, FieldsInfo, ProtoField, ProtoLabel};